@@ -0,0 +1,26 @@
+use aoc::{
+    days::day1::{level2, scaled_input, top_k},
+    util::random::Rng,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const SIZE: usize = 100_000_000;
+
+// Both levels' work on 100MB of elves: collecting every total, and keeping
+// only the top three while reading the lines
+fn bench_top_three(c: &mut Criterion) {
+    let input = scaled_input(&mut Rng::new(1), SIZE);
+    let streamed: i64 = top_k(input.as_bytes(), 3).unwrap().iter().sum();
+    assert_eq!(streamed, level2(&input));
+    let mut group = c.benchmark_group("day1_top_three");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("collected", |b| b.iter(|| level2(&input)));
+    group.bench_function("streaming", |b| {
+        b.iter(|| top_k(input.as_bytes(), 3).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_top_three);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+use std::fmt::Write;
+
+use aoc::days::day5::{top_crates, CraneModel};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const STACKS: usize = 9;
+const HEIGHT: usize = 20;
+const MOVES: usize = 1_000_000;
+
+// Random moves that never take more crates than a stack holds
+fn generate_input() -> String {
+    let mut input = String::new();
+    for _ in 0..HEIGHT {
+        let row = (0..STACKS).map(|i| format!("[{}]", (b'A' + i as u8) as char));
+        writeln!(input, "{}", row.collect::<Vec<_>>().join(" ")).unwrap();
+    }
+    let labels = (1..=STACKS).map(|i| format!(" {i} "));
+    writeln!(input, "{}\n", labels.collect::<Vec<_>>().join(" ")).unwrap();
+    let mut heights = [HEIGHT; STACKS];
+    let mut seed: u64 = 17;
+    let mut next = |bound: usize| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize % bound
+    };
+    for _ in 0..MOVES {
+        let source = loop {
+            let source = next(STACKS);
+            if heights[source] > 1 {
+                break source;
+            }
+        };
+        let target = (source + 1 + next(STACKS - 1)) % STACKS;
+        let count = 1 + next(heights[source].min(5) - 1);
+        heights[source] -= count;
+        heights[target] += count;
+        writeln!(input, "move {count} from {} to {}", source + 1, target + 1).unwrap();
+    }
+    input.trim_end().to_string()
+}
+
+fn bench_crane_models(c: &mut Criterion) {
+    let input = generate_input();
+    let mut group = c.benchmark_group("day5_million_moves");
+    group.sample_size(10);
+    group.bench_function("crate_mover_9000", |b| {
+        b.iter(|| top_crates(&input, CraneModel::CrateMover9000))
+    });
+    group.bench_function("crate_mover_9001", |b| {
+        b.iter(|| top_crates(&input, CraneModel::CrateMover9001))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_crane_models);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+use std::{collections::HashMap, fmt::Write};
+
+use aoc::days::day7::level1;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const DEPTH: usize = 2_000;
+const FILES: usize = 5;
+
+// One long chain of directories, each also holding a few files and a leaf
+// directory, walked down and back up again with `cd ..`.
+fn generate_input() -> String {
+    let mut input = String::from("$ cd /\n");
+    for depth in 0..DEPTH {
+        input.push_str("$ ls\n");
+        writeln!(input, "dir d{depth}\ndir leaf").unwrap();
+        for file in 0..FILES {
+            writeln!(input, "{} f{file}", 1 + (depth * 7 + file * 13) % 997).unwrap();
+        }
+        input.push_str("$ cd leaf\n$ ls\n42 x\n$ cd ..\n");
+        writeln!(input, "$ cd d{depth}").unwrap();
+    }
+    for _ in 0..DEPTH {
+        input.push_str("$ cd ..\n");
+    }
+    input.trim_end().to_string()
+}
+
+// The nested map version day 7 used before the arena, kept as a baseline
+enum Entry<'a> {
+    File(i64),
+    Dir(HashMap<&'a str, Entry<'a>>),
+}
+
+fn subdir<'a, 'b>(
+    root: &'b mut HashMap<&'a str, Entry<'a>>,
+    path: &[&'a str],
+) -> &'b mut HashMap<&'a str, Entry<'a>> {
+    path.iter().fold(root, |dir, name| match dir.get_mut(name) {
+        Some(Entry::Dir(child)) => child,
+        _ => unreachable!(),
+    })
+}
+
+fn sizes(dir: &HashMap<&str, Entry>, out: &mut Vec<i64>) -> i64 {
+    let size = dir
+        .values()
+        .map(|entry| match entry {
+            Entry::File(size) => *size,
+            Entry::Dir(child) => sizes(child, out),
+        })
+        .sum();
+    out.push(size);
+    size
+}
+
+fn nested_level1(input: &str) -> i64 {
+    let mut root = HashMap::new();
+    let mut path = Vec::new();
+    for line in input.lines() {
+        match line.split(' ').collect::<Vec<_>>()[..] {
+            ["$", "cd", "/"] => path.clear(),
+            ["$", "cd", ".."] => {
+                path.pop();
+            }
+            ["$", "cd", name] => {
+                subdir(&mut root, &path)
+                    .entry(name)
+                    .or_insert_with(|| Entry::Dir(HashMap::new()));
+                path.push(name);
+            }
+            ["$", "ls"] => {}
+            ["dir", name] => {
+                subdir(&mut root, &path)
+                    .entry(name)
+                    .or_insert_with(|| Entry::Dir(HashMap::new()));
+            }
+            [size, name] => {
+                subdir(&mut root, &path).insert(name, Entry::File(size.parse().unwrap()));
+            }
+            _ => unreachable!(),
+        }
+    }
+    let mut out = Vec::new();
+    sizes(&root, &mut out);
+    out.into_iter().filter(|size| *size <= 100_000).sum()
+}
+
+fn bench_deep_tree(c: &mut Criterion) {
+    let input = generate_input();
+    assert_eq!(level1(&input), nested_level1(&input));
+    let mut group = c.benchmark_group("day7_deep_tree");
+    group.sample_size(10);
+    group.bench_function("arena", |b| b.iter(|| level1(&input)));
+    group.bench_function("nested", |b| b.iter(|| nested_level1(&input)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_deep_tree);
+criterion_main!(benches);
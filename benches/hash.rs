@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+
+use aoc::util::{hash::FastHashSet, random::Rng};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// Positions of a random walk, like a rope's tail visiting cells on day 9
+fn walk(steps: usize) -> Vec<(i64, i64)> {
+    let mut rng = Rng::new(9);
+    let mut pos = (0, 0);
+    (0..steps)
+        .map(|_| {
+            let (dx, dy) = [(1, 0), (-1, 0), (0, 1), (0, -1)][rng.below(4)];
+            pos = (pos.0 + dx, pos.1 + dy);
+            pos
+        })
+        .collect()
+}
+
+// Counting distinct visited cells with std's SipHash and with the hasher
+// util::hash picked, which the ahash and fxhash features select between
+fn bench_visited(c: &mut Criterion) {
+    let mut group = c.benchmark_group("visited_set");
+    for steps in [10_000, 1_000_000] {
+        let positions = walk(steps);
+        group.bench_with_input(BenchmarkId::new("siphash", steps), &positions, |b, p| {
+            b.iter(|| p.iter().collect::<HashSet<_>>().len())
+        });
+        group.bench_with_input(BenchmarkId::new("fast", steps), &positions, |b, p| {
+            b.iter(|| p.iter().collect::<FastHashSet<_>>().len())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_visited);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+use aoc::util::parse::ints;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, i64, u32},
+    sequence::{pair, preceded, separated_pair},
+    IResult,
+};
+
+const LINES: usize = 10_000;
+
+type Pair<T> = ((T, T), (T, T));
+
+fn sensor_lines() -> Vec<String> {
+    let mut seed: i64 = 17;
+    let mut next = || {
+        seed = (seed * 1_103_515_245 + 12345).rem_euclid(1 << 31);
+        seed % 8_000_000 - 4_000_000
+    };
+    (0..LINES)
+        .map(|_| {
+            let [x, y, x2, y2] = [next(), next(), next(), next()];
+            format!("Sensor at x={x}, y={y}: closest beacon is at x={x2}, y={y2}")
+        })
+        .collect()
+}
+
+fn range_lines() -> Vec<String> {
+    (0..LINES as u32)
+        .map(|i| format!("{}-{},{}-{}", i % 97, i % 97 + 5, i % 89, i % 89 + 40))
+        .collect()
+}
+
+// The same shapes day 15 and day 4 parse with nom
+fn nom_sensor(input: &str) -> IResult<&str, Pair<i64>> {
+    let pos = |input| pair(preceded(tag("x="), i64), preceded(tag(", y="), i64))(input);
+    pair(
+        preceded(tag("Sensor at "), pos),
+        preceded(tag(": closest beacon is at "), pos),
+    )(input)
+}
+
+fn nom_ranges(input: &str) -> IResult<&str, Pair<u32>> {
+    let range = |input| separated_pair(u32, char('-'), u32)(input);
+    separated_pair(range, char(','), range)(input)
+}
+
+fn bench_ints(c: &mut Criterion) {
+    let sensors = sensor_lines();
+    let ranges = range_lines();
+    let mut group = c.benchmark_group("ints");
+    group.bench_function("sensors_ints", |b| {
+        b.iter(|| {
+            for line in &sensors {
+                black_box(ints::<i64>(line));
+            }
+        })
+    });
+    group.bench_function("sensors_nom", |b| {
+        b.iter(|| {
+            for line in &sensors {
+                black_box(nom_sensor(line).unwrap());
+            }
+        })
+    });
+    group.bench_function("ranges_ints", |b| {
+        b.iter(|| {
+            for line in &ranges {
+                black_box(ints::<u32>(line));
+            }
+        })
+    });
+    group.bench_function("ranges_nom", |b| {
+        b.iter(|| {
+            for line in &ranges {
+                black_box(nom_ranges(line).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ints);
+criterion_main!(benches);
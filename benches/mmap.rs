@@ -0,0 +1,98 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use aoc::{
+    days::{day12, day8},
+    util::{input::map_file, random::Rng},
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+// Counts live heap bytes and the most there were at once, to show how much
+// of the peak is the input String that mapping the file avoids
+struct PeakAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+// The most heap in use while `f` runs, above what was in use before
+fn peak_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = LIVE.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let result = f();
+    (result, PEAK.load(Ordering::Relaxed) - before)
+}
+
+const SIZE: usize = 16_000_000;
+
+// A plain of a's with S in one corner and E in the other, at the top of a
+// ramp along the bottom edge
+fn day12_input(size: usize) -> String {
+    let ramp = "bcdefghijklmnopqrstuvwxyzE";
+    let side = (size as f64).sqrt().ceil().max(ramp.len() as f64 + 1.0) as usize;
+    let mut rows = vec!["a".repeat(side); side];
+    rows[0].replace_range(0..1, "S");
+    rows[side - 1].replace_range(side - ramp.len().., ramp);
+    rows.join("\n")
+}
+
+fn bench_day(
+    c: &mut Criterion,
+    name: &str,
+    input: &str,
+    from_str: fn(&str) -> usize,
+    from_bytes: fn(&[u8]) -> usize,
+) {
+    let path = std::env::temp_dir().join(format!("aoc-bench-{name}.txt"));
+    fs::write(&path, input).unwrap();
+    let read = |path: &Path| from_str(&fs::read_to_string(path).unwrap());
+    let mapped = |path: &Path| from_bytes(&map_file(path).unwrap());
+    let (read_answer, read_peak) = peak_during(|| read(&path));
+    let (mapped_answer, mapped_peak) = peak_during(|| mapped(&path));
+    assert_eq!(read_answer, mapped_answer);
+    println!(
+        "{name}: peak heap {} MB read into a String, {} MB mapped",
+        read_peak >> 20,
+        mapped_peak >> 20
+    );
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("read", |b| b.iter(|| read(&path)));
+    group.bench_function("mapped", |b| b.iter(|| mapped(&path)));
+    group.finish();
+    fs::remove_file(&path).unwrap();
+}
+
+fn bench_mmap(c: &mut Criterion) {
+    let forest = day8::scaled_input(&mut Rng::new(1), SIZE);
+    bench_day(c, "day8_level1", &forest, day8::level1, day8::level1_bytes);
+    let map = day12_input(SIZE / 4);
+    bench_day(c, "day12_level1", &map, day12::level1, day12::level1_bytes);
+}
+
+criterion_group!(benches, bench_mmap);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use aoc::util::rope_list::RopeList;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const MOVES: usize = 5_000;
+
+fn values(len: usize) -> Vec<i64> {
+    let mut seed: i64 = 17;
+    (0..len)
+        .map(|_| {
+            seed = (seed * 1_103_515_245 + 12345).rem_euclid(1 << 31);
+            seed - (1 << 30)
+        })
+        .collect()
+}
+
+fn naive_moves(values: &[i64]) -> Vec<(usize, i64)> {
+    let mut list = values.iter().copied().enumerate().collect::<Vec<_>>();
+    for id in 0..MOVES.min(values.len()) {
+        let index = list.iter().position(|(i, _)| *i == id).unwrap();
+        let item = list.remove(index);
+        let target = (index as i64 + item.1).rem_euclid(list.len() as i64) as usize;
+        list.insert(target, item);
+    }
+    list
+}
+
+fn rope_list_moves(values: &[i64]) -> RopeList<i64> {
+    let mut list: RopeList<i64> = values.iter().copied().collect();
+    for id in 0..MOVES.min(values.len()) {
+        let index = list.index_of(id);
+        list.move_by(index, *list.value(id));
+    }
+    list
+}
+
+fn bench_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rope_list_moves");
+    group.sample_size(10);
+    for len in [5_000, 500_000] {
+        let input = values(len);
+        group.bench_with_input(BenchmarkId::new("naive_vec", len), &input, |b, input| {
+            b.iter(|| naive_moves(input))
+        });
+        group.bench_with_input(BenchmarkId::new("rope_list", len), &input, |b, input| {
+            b.iter(|| rope_list_moves(input))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_moves);
+criterion_main!(benches);
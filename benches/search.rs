@@ -0,0 +1,59 @@
+use aoc::days::day12::{shortest_climb, shortest_trail, AtMostOneUp, Lowercase, MoveCost};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+// The real heightmap if there is one, otherwise a generated one of about
+// the same size: a plain of a's with a ramp up to E along the bottom edge
+fn heightmap() -> String {
+    std::fs::read_to_string("input/day12.txt").unwrap_or_else(|_| {
+        let ramp = "bcdefghijklmnopqrstuvwxyzE";
+        let mut rows = vec!["a".repeat(160); 41];
+        rows[0].replace_range(0..1, "S");
+        rows[40].replace_range(160 - ramp.len().., ramp);
+        rows.join("\n")
+    })
+}
+
+// The puzzle's unit costs, without saying they are, so the search goes
+// through the priority queue with the distance estimate as before
+struct Heap;
+
+impl MoveCost for Heap {
+    fn cost(&self, from: i64, to: i64) -> Option<usize> {
+        AtMostOneUp.cost(from, to)
+    }
+
+    fn cheapest(&self) -> usize {
+        1
+    }
+}
+
+// Both levels breadth first, as day 12 searches them, and through the heap
+fn bench_search(c: &mut Criterion) {
+    let map = heightmap();
+    let heap = Heap;
+    assert_eq!(
+        shortest_climb(&map, &Lowercase, &AtMostOneUp).unwrap(),
+        shortest_climb(&map, &Lowercase, &heap).unwrap()
+    );
+    assert_eq!(
+        shortest_trail(&map, &Lowercase, &AtMostOneUp).unwrap(),
+        shortest_trail(&map, &Lowercase, &heap).unwrap()
+    );
+    let mut group = c.benchmark_group("day12_search");
+    group.bench_function("climb_bfs", |b| {
+        b.iter(|| shortest_climb(&map, &Lowercase, &AtMostOneUp).unwrap())
+    });
+    group.bench_function("climb_heap", |b| {
+        b.iter(|| shortest_climb(&map, &Lowercase, &heap).unwrap())
+    });
+    group.bench_function("trail_bfs", |b| {
+        b.iter(|| shortest_trail(&map, &Lowercase, &AtMostOneUp).unwrap())
+    });
+    group.bench_function("trail_heap", |b| {
+        b.iter(|| shortest_trail(&map, &Lowercase, &heap).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+use aoc::util::{hash::FastHashSet, random::Rng, visited::DenseVisited};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// A rope's tail wandering around the start, like day 9's inputs
+fn random_walk(steps: usize) -> Vec<(i64, i64)> {
+    let mut rng = Rng::new(9);
+    let mut pos = (0, 0);
+    (0..steps)
+        .map(|_| {
+            let (dx, dy) = [(1, 0), (-1, 0), (0, 1), (0, -1)][rng.below(4)];
+            pos = (pos.0 + dx, pos.1 + dy);
+            pos
+        })
+        .collect()
+}
+
+// Out along a line and back in a zigzag, far past the largest window, the
+// worst case for the bitmap: it grows all the way and then hashes anyway
+fn long_walk(steps: usize) -> Vec<(i64, i64)> {
+    (0..steps as i64)
+        .map(|i| (i / 2, i % 2 * (i / 1000)))
+        .collect()
+}
+
+fn bench_visited(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dense_visited");
+    for (name, positions) in [
+        ("random", random_walk(1_000_000)),
+        ("long", long_walk(1_000_000)),
+    ] {
+        group.bench_with_input(BenchmarkId::new("hash", name), &positions, |b, p| {
+            // One at a time like day 9, rather than collected with the
+            // whole length reserved up front
+            b.iter(|| {
+                let mut visited = FastHashSet::default();
+                p.iter().for_each(|point| {
+                    visited.insert(*point);
+                });
+                visited.len()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("dense", name), &positions, |b, p| {
+            b.iter(|| {
+                let mut visited = DenseVisited::new();
+                p.iter().for_each(|point| {
+                    visited.insert(*point);
+                });
+                visited.len()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_visited);
+criterion_main!(benches);
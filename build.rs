@@ -0,0 +1,25 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+// With the embed-inputs feature, writes `inputs.rs` listing every
+// input/dayN.txt for util::infra to `include_str!`, so the binary runs
+// without the input directory next to it. Days without an input file are
+// left out and read from disk at runtime like without the feature.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=CARGO_FEATURE_EMBED_INPUTS");
+    if env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_none() {
+        return;
+    }
+    let dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("input");
+    println!("cargo:rerun-if-changed={}", dir.display());
+    let mut inputs = String::from("pub const INPUTS: &[(u32, &str)] = &[\n");
+    for day in 1..=25 {
+        let path = dir.join(format!("day{day}.txt"));
+        if path.is_file() {
+            writeln!(inputs, "    ({day}, include_str!({:?})),", path.display()).unwrap();
+        }
+    }
+    inputs += "];\n";
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("inputs.rs");
+    fs::write(out, inputs).unwrap();
+}
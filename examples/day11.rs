@@ -0,0 +1,5 @@
+mod legacy;
+
+fn main() -> anyhow::Result<()> {
+    legacy::forward(11)
+}
@@ -0,0 +1,13 @@
+// Keeps `cargo run --example dayN -- --level L [--submit]` working: every
+// dayN example is a one line file calling `forward`, which runs
+// `aoc run --day N` with the remaining arguments.
+use aoc::{runner::run, util::config::Config};
+use pico_args::Arguments;
+
+pub fn forward(day: u32) -> anyhow::Result<()> {
+    let mut args = vec!["--day".into(), day.to_string().into()];
+    args.extend(std::env::args_os().skip(1));
+    let mut pargs = Arguments::from_vec(args);
+    let config = Config::resolve(&mut pargs)?;
+    run(pargs, &config)
+}
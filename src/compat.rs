@@ -0,0 +1,78 @@
+// The days in the shape of the cargo-aoc tooling: a generator parsing the
+// input into an owned value, and a solver per part borrowing it. These are
+// the `Day` methods under cargo-aoc's names, so nothing here solves
+// anything. With the cargo-aoc-compat feature they also carry cargo-aoc's
+// attributes, and `cargo aoc bench -d 12` times them like any cargo-aoc
+// solution. cargo aoc keeps its inputs in input/2022/dayN.txt rather than
+// next to ours.
+macro_rules! compat {
+    ($($day:ident: $feature:literal,)*) => {
+        $(
+            #[cfg(feature = $feature)]
+            pub mod $day {
+                use crate::{days::$day::Solver, util::day::Day};
+
+                pub type Parsed = <Solver as Day>::Parsed;
+
+                #[cfg_attr(
+                    feature = "cargo-aoc-compat",
+                    aoc_runner_derive::aoc_generator($day)
+                )]
+                pub fn generator(input: &str) -> Parsed {
+                    Solver::parse(input)
+                }
+
+                #[cfg_attr(feature = "cargo-aoc-compat", aoc_runner_derive::aoc($day, part1))]
+                pub fn part1(parsed: &Parsed) -> String {
+                    Solver::level1(parsed)
+                }
+
+                #[cfg_attr(feature = "cargo-aoc-compat", aoc_runner_derive::aoc($day, part2))]
+                pub fn part2(parsed: &Parsed) -> String {
+                    Solver::level2(parsed)
+                }
+            }
+        )*
+    };
+}
+
+compat! {
+    day1: "day01",
+    day2: "day02",
+    day3: "day03",
+    day4: "day04",
+    day5: "day05",
+    day6: "day06",
+    day7: "day07",
+    day8: "day08",
+    day9: "day09",
+    day10: "day10",
+    day11: "day11",
+    day12: "day12",
+    day13: "day13",
+    day14: "day14",
+    day15: "day15",
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "day01")]
+    #[test]
+    fn generator_then_solvers() {
+        let parsed = super::day1::generator(include_str!("./days/test_input/day1.txt"));
+        assert_eq!(super::day1::part1(&parsed), "24000");
+        assert_eq!(super::day1::part2(&parsed), "45000");
+    }
+
+    #[cfg(all(feature = "cargo-aoc-compat", feature = "day01"))]
+    #[test]
+    fn registered_with_cargo_aoc() {
+        use aoc_runner::ArcStr;
+
+        use crate::{Day1Part2, Factory};
+
+        let input = ArcStr::from(include_str!("./days/test_input/day1.txt"));
+        let runner = Factory::day1_part2(input).unwrap();
+        assert_eq!(runner.run().to_string(), "45000");
+    }
+}
@@ -1,26 +1,232 @@
-use itertools::Itertools;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    io::{self, BufRead},
+};
 
-use crate::util::*;
+use nom::{
+    character::complete::{i64, line_ending},
+    combinator::map,
+    multi::{many1, separated_list1},
+    sequence::pair,
+    IResult,
+};
+
+use crate::{
+    prelude::*,
+    util::{
+        day::{Answer, Day, Explain, Param, Perturb},
+        infra::Level,
+        random::Rng,
+    },
+};
+
+// One elf's snacks in calories, with elves numbered from 1 in input order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elf {
+    pub index: usize,
+    pub snacks: Vec<i64>,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expedition {
+    pub elves: Vec<Elf>,
+}
+
+impl Expedition {
+    // The first elf among those carrying the most calories
+    pub fn elf_carrying_most(&self) -> Option<&Elf> {
+        self.top(1).into_iter().next()
+    }
+
+    // The `k` elves carrying the most, most first and in input order among
+    // equal totals
+    pub fn top(&self, k: usize) -> Vec<&Elf> {
+        self.elves
+            .iter()
+            .sorted_by_key(|elf| (Reverse(elf.total), elf.index))
+            .take(k)
+            .collect()
+    }
+
+    // In input order
+    pub fn elves_above(&self, threshold: i64) -> impl Iterator<Item = &Elf> + '_ {
+        self.elves.iter().filter(move |elf| elf.total > threshold)
+    }
+}
+
+// Elves are separated by one or more blank lines
+fn expedition(input: &str) -> IResult<&str, Expedition> {
+    map(
+        separated_list1(
+            pair(line_ending, many1(line_ending)),
+            separated_list1(line_ending, i64),
+        ),
+        |elves| Expedition {
+            elves: elves
+                .into_iter()
+                .enumerate()
+                .map(|(i, snacks)| Elf {
+                    index: i + 1,
+                    total: snacks.iter().sum(),
+                    snacks,
+                })
+                .collect(),
+        },
+    )(input)
+}
+
+pub fn parse_expedition(input: &str) -> Result<Expedition, ParseError> {
+    parse_complete(expedition, input)
+}
+
+fn top_total(expedition: &Expedition, k: usize) -> i64 {
+    expedition.top(k).iter().map(|elf| elf.total).sum()
+}
+
+// The `k` largest elf totals, largest first, in a single pass over the
+// lines that holds no more than `k` totals at a time
+pub fn top_k(mut reader: impl BufRead, k: usize) -> io::Result<Vec<i64>> {
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    let mut finish = |total: Option<i64>| {
+        if let Some(total) = total {
+            heap.push(Reverse(total));
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+    };
+    let mut line = String::new();
+    let mut total = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            finish(total.take());
+            continue;
+        }
+        let calories: i64 = line
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        total = Some(total.unwrap_or(0) + calories);
+    }
+    finish(total);
+    Ok(heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(total)| total)
+        .collect())
+}
+
+pub fn stream(reader: &mut dyn BufRead, level: Level) -> io::Result<Answer> {
+    let k = match level {
+        Level::One => 1,
+        Level::Two => 3,
+    };
+    let total: i64 = top_k(reader, k)?.iter().sum();
+    Ok(Answer::Number(total.into()))
+}
+
+// Elves carrying a few snacks each until the input is `size` bytes long
+pub fn scaled_input(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::with_capacity(size + 16);
+    while input.len() < size {
+        if !input.is_empty() {
+            input.push('\n');
+        }
+        for _ in 0..1 + rng.below(5) {
+            input += &format!("{}\n", rng.range(1000..=60000));
+        }
+    }
+    input
+}
 
 pub fn level1(input: &str) -> i64 {
-    let groups = input
-        .split("\n\n")
-        .map(|group| group.lines().map(|x| x.parse::<i64>().unwrap()).sum());
-    groups.max().unwrap()
+    top_total(&parse_or_report(expedition, input), 1)
 }
 
 pub fn level2(input: &str) -> i64 {
-    let mut groups = input
-        .split("\n\n")
-        .map(|group| group.lines().map(|x| x.parse::<i64>().unwrap()).sum())
-        .collect_vec();
-    groups.sort();
-    groups[groups.len() - 3..].iter().sum()
+    top_total(&parse_or_report(expedition, input), 3)
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Expedition;
+
+    fn parse(input: &str) -> Expedition {
+        parse_or_report(expedition, input)
+    }
+
+    fn level1(expedition: &Expedition) -> String {
+        top_total(expedition, 1).to_string()
+    }
+
+    fn level2(expedition: &Expedition) -> String {
+        top_total(expedition, 3).to_string()
+    }
+}
+
+impl Explain for Solver {
+    fn explain(input: &str, level: Level) -> Vec<String> {
+        let expedition = parse_or_report(expedition, input);
+        let k = match level {
+            Level::One => 1,
+            Level::Two => 3,
+        };
+        let snacks: usize = expedition.elves.iter().map(|elf| elf.snacks.len()).sum();
+        let mut steps = vec![format!(
+            "{} elves carry {snacks} snacks",
+            expedition.elves.len()
+        )];
+        let top = expedition.top(k);
+        steps.extend(top.iter().map(|elf| {
+            format!(
+                "  elf {} carries {} = {}",
+                elf.index,
+                elf.snacks.iter().join(" + "),
+                elf.total
+            )
+        }));
+        // Elves left out only because they are later in the input
+        if let Some(last) = top.last() {
+            let tied = expedition.elves_above(last.total - 1).count() - top.len();
+            if tied > 0 {
+                steps.push(format!(
+                    "  {tied} more carry {} as well, which changes nothing",
+                    last.total
+                ));
+            }
+        }
+        steps.push(format!(
+            "That makes {}, the answer",
+            top_total(&expedition, k)
+        ));
+        steps
+    }
+}
+
+impl Perturb for Solver {
+    const PARAMS: &'static [Param<Expedition>] = &[Param {
+        name: "snacks",
+        about: "the calories of every snack",
+        apply: |expedition, delta| {
+            for elf in &mut expedition.elves {
+                elf.snacks.iter_mut().for_each(|snack| *snack += delta);
+                elf.total = elf.snacks.iter().sum();
+            }
+        },
+    }];
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::day;
 
     #[test]
     fn level1_given_example() {
@@ -33,4 +239,69 @@ mod test {
         let test_input = include_str!("./test_input/day1.txt");
         assert_eq!(level2(test_input), 45000)
     }
+
+    #[test]
+    fn streams_top_k() {
+        let test_input = include_str!("./test_input/day1.txt");
+        assert_eq!(
+            top_k(test_input.as_bytes(), 3).unwrap(),
+            [24000, 11000, 10000]
+        );
+        assert_eq!(top_k(test_input.as_bytes(), 100).unwrap().len(), 5);
+        let mut rng = Rng::new(1);
+        let input = scaled_input(&mut rng, 10_000);
+        for (level, expected) in [(Level::One, level1(&input)), (Level::Two, level2(&input))] {
+            let answer = stream(&mut input.as_bytes(), level).unwrap();
+            assert_eq!(answer, Answer::Number(expected.into()));
+        }
+        let error = top_k("1\nx\n".as_bytes(), 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn queries_the_elves() {
+        let test_input = include_str!("./test_input/day1.txt");
+        let expedition = parse_expedition(test_input).unwrap();
+        assert_eq!(
+            expedition.elf_carrying_most(),
+            Some(&Elf {
+                index: 4,
+                snacks: vec![7000, 8000, 9000],
+                total: 24000
+            })
+        );
+        let above = expedition.elves_above(10000).map(|elf| elf.index);
+        assert_eq!(above.collect_vec(), [3, 4]);
+        let ties = parse_expedition("5\n\n3\n\n5\n").unwrap();
+        assert_eq!(ties.elf_carrying_most().unwrap().index, 1);
+        assert_eq!(
+            Solver::explain("5\n\n3\n\n5\n", Level::One),
+            [
+                "3 elves carry 3 snacks",
+                "  elf 1 carries 5 = 5",
+                "  1 more carry 5 as well, which changes nothing",
+                "That makes 5, the answer"
+            ]
+        );
+        let steps = Solver::explain(test_input, Level::Two);
+        assert_eq!(steps[0], "5 elves carry 10 snacks");
+        assert_eq!(steps[1], "  elf 4 carries 7000 + 8000 + 9000 = 24000");
+        assert_eq!(steps.last().unwrap(), "That makes 45000, the answer");
+    }
+
+    #[test]
+    fn perturbs_every_snack() {
+        let test_input = include_str!("./test_input/day1.txt");
+        let answer = day::perturbed::<Solver>(test_input, "snacks", 1, Level::Two).unwrap();
+        assert_eq!(answer, Answer::Number(45006));
+    }
+
+    #[test]
+    fn parses_crlf_and_blank_lines() {
+        let expedition = parse_expedition("1\r\n2\r\n\r\n\r\n3\r\n\r\n\r\n").unwrap();
+        let totals = expedition.elves.iter().map(|elf| elf.total).collect_vec();
+        assert_eq!(totals, [3, 3]);
+        let error = parse_expedition("1\n\n2\n3x\n").unwrap_err();
+        assert_eq!(error.line_col(), (4, 2));
+    }
 }
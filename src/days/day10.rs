@@ -1,82 +1,250 @@
+use std::fmt::{self, Display};
+
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{i32, line_ending},
-    combinator::{flat_map, map, value},
+    character::complete::{i32, i64, line_ending},
+    combinator::{map, value},
     multi::separated_list0,
     sequence::preceded,
     IResult,
 };
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{
+        day::{Answer, Day},
+        export::{Field, Row},
+        glyphs::{self, Glyphs},
+    },
+};
 
-#[derive(Debug, Clone)]
-enum Op {
+// One instruction of the handheld's CPU. Beyond the puzzle's noop and addx,
+// jmp moves the program counter by its offset, like the console of 2020's
+// day 8, and mulx multiplies X. Each takes `cycles()` cycles and only
+// changes the registers once the last of them is over. X is a 32 bit
+// register, so arithmetic wraps around instead of overflowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
     Noop,
     Addx(i32),
+    Jmp(isize),
+    Mulx(i32),
+}
+
+impl Instruction {
+    pub fn cycles(self) -> usize {
+        match self {
+            Instruction::Noop | Instruction::Jmp(_) => 1,
+            Instruction::Addx(_) | Instruction::Mulx(_) => 2,
+        }
+    }
 }
 
-fn parse_line(line: &str) -> IResult<&str, Op> {
+// The disassembly, which assembles back to the same instruction
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Addx(y) => write!(f, "addx {y}"),
+            Instruction::Jmp(offset) => write!(f, "jmp {offset}"),
+            Instruction::Mulx(y) => write!(f, "mulx {y}"),
+        }
+    }
+}
+
+fn parse_instruction(line: &str) -> IResult<&str, Instruction> {
     alt((
-        value(Op::Noop, tag("noop")),
-        map(preceded(tag("addx "), i32), Op::Addx),
+        value(Instruction::Noop, tag("noop")),
+        map(preceded(tag("addx "), i32), Instruction::Addx),
+        map(preceded(tag("jmp "), i64), |offset| {
+            Instruction::Jmp(offset as isize)
+        }),
+        map(preceded(tag("mulx "), i32), Instruction::Mulx),
     ))(line)
 }
 
-fn parse_input(input: &str) -> IResult<&str, Vec<(usize, i32)>> {
-    let mut step = 1;
-    let mut x = 1;
-    map(
-        separated_list0(
-            line_ending,
-            map(parse_line, move |op| match op {
-                Op::Noop => {
-                    let result = vec![(step, x)];
-                    step += 1;
-                    result
-                }
-                Op::Addx(y) => {
-                    let result = vec![(step, x), (step + 1, x)];
-                    step += 2;
-                    x += y;
-                    result
+fn parse_program(input: &str) -> IResult<&str, Vec<Instruction>> {
+    separated_list0(line_ending, parse_instruction)(input)
+}
+
+// One instruction per line, with nothing else in `source`
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_complete(parse_program, source)
+}
+
+pub fn disassemble(program: &[Instruction]) -> String {
+    program.iter().join("\n")
+}
+
+// The registers during a cycle, counting from 1, and the instruction the
+// cycle is spent on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuState {
+    pub cycle: usize,
+    pub x: i32,
+    pub executing: Instruction,
+}
+
+impl CpuState {
+    pub fn signal_strength(&self) -> i64 {
+        self.cycle as i64 * i64::from(self.x)
+    }
+}
+
+impl Row for CpuState {
+    const COLUMNS: &'static [&'static str] = &["cycle", "x", "executing", "signal_strength"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            self.cycle.into(),
+            self.x.into(),
+            self.executing.to_string().into(),
+            self.signal_strength().into(),
+        ]
+    }
+}
+
+// The screen's 40 by 6 pixels, one per cycle
+pub const SCREEN_CYCLES: usize = 240;
+
+// Where a program stops running even if it never leaves, since a jmp back
+// like `jmp 0` can keep it going forever
+pub const MAX_CYCLES: usize = 100_000;
+
+// A program running, one cycle per item until the program counter leaves
+// the program or `MAX_CYCLES` cycles are over
+#[derive(Debug, Clone)]
+pub struct Cpu<'a> {
+    program: &'a [Instruction],
+    pc: usize,
+    x: i32,
+    cycle: usize,
+    // Cycles spent on the instruction at `pc` so far
+    busy: usize,
+}
+
+impl Cpu<'_> {
+    pub fn run(program: &[Instruction]) -> impl Iterator<Item = CpuState> + '_ {
+        Cpu {
+            program,
+            pc: 0,
+            x: 1,
+            cycle: 0,
+            busy: 0,
+        }
+    }
+}
+
+impl Iterator for Cpu<'_> {
+    type Item = CpuState;
+
+    fn next(&mut self) -> Option<CpuState> {
+        if self.cycle == MAX_CYCLES {
+            return None;
+        }
+        let instruction = *self.program.get(self.pc)?;
+        self.cycle += 1;
+        self.busy += 1;
+        let state = CpuState {
+            cycle: self.cycle,
+            x: self.x,
+            executing: instruction,
+        };
+        if self.busy == instruction.cycles() {
+            self.busy = 0;
+            self.pc = match instruction {
+                // Past the end either way, so an overflow halts
+                Instruction::Jmp(offset) => {
+                    self.pc.checked_add_signed(offset).unwrap_or(usize::MAX)
                 }
-            }),
-        ),
-        |acc| acc.into_iter().flatten().collect(),
-    )(input)
-}
-
-pub fn level1(input: &str) -> i32 {
-    parse_input(input)
-        .unwrap()
-        .1
-        .into_iter()
-        .filter_map(|(i, x)| (i % 40 == 20).then_some((i as i32) * x))
+                _ => self.pc + 1,
+            };
+            match instruction {
+                Instruction::Addx(y) => self.x = self.x.wrapping_add(y),
+                Instruction::Mulx(y) => self.x = self.x.wrapping_mul(y),
+                Instruction::Noop | Instruction::Jmp(_) => {}
+            }
+        }
+        Some(state)
+    }
+}
+
+fn signal_strength(states: impl Iterator<Item = CpuState>) -> i64 {
+    states
+        .filter_map(|state| (state.cycle % 40 == 20).then_some(state.signal_strength()))
         .take(6)
         .sum()
 }
 
-pub fn level2(input: &str) -> i32 {
-    let chars = parse_input(input)
-        .unwrap()
-        .1
-        .into_iter()
-        .map(|(i, x)| {
-            if ((i as i32 - 1).rem_euclid(40) - x).abs() <= 1 {
-                '⣿'
+// The screen's pixels, leaving out any cycles after it is full
+fn render(states: impl Iterator<Item = CpuState>, glyphs: &Glyphs) -> String {
+    let chars = states
+        .take(SCREEN_CYCLES)
+        .map(|state| {
+            if ((state.cycle as i64 - 1) % 40 - i64::from(state.x)).abs() <= 1 {
+                glyphs.lit
             } else {
-                ' '
+                glyphs.empty
             }
         })
         .collect_vec();
-    for line in chars.chunks(40) {
-        let line: String = line.iter().collect();
-        println!("{line}");
-    }
+    chars
+        .chunks(40)
+        .map(|line| line.iter().collect::<String>())
+        .join("\n")
+}
+
+// Every cycle of the program up to `MAX_CYCLES`, for plotting with
+// aoc run --export-trace
+pub fn trace(input: &str) -> Vec<CpuState> {
+    Cpu::run(&parse_or_report(parse_program, input)).collect()
+}
+
+pub fn level1(input: &str) -> i64 {
+    signal_strength(Cpu::run(&parse_or_report(parse_program, input)))
+}
+
+pub fn level2(input: &str) -> i32 {
+    println!(
+        "{}",
+        render(
+            Cpu::run(&parse_or_report(parse_program, input)),
+            &glyphs::current()
+        )
+    );
     0
 }
 
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Instruction>;
+
+    fn parse(input: &str) -> Vec<Instruction> {
+        parse_or_report(parse_program, input)
+    }
+
+    fn level1(program: &Vec<Instruction>) -> String {
+        signal_strength(Cpu::run(program)).to_string()
+    }
+
+    // The answer is the letters drawn on the screen, so hand back the drawing
+    fn level2(program: &Vec<Instruction>) -> String {
+        format!("\n{}", render(Cpu::run(program), &glyphs::current()))
+    }
+
+    // One run of the program for both, which only look at the screen's cycles
+    fn both(program: &Vec<Instruction>) -> (Answer, Answer) {
+        let states = Cpu::run(program).take(SCREEN_CYCLES).collect_vec();
+        let screen = render(states.iter().copied(), &glyphs::current());
+        (
+            Answer::Number(signal_strength(states.into_iter()).into()),
+            Answer::Text(format!("\n{screen}")),
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -92,4 +260,96 @@ mod test {
         let test_input = include_str!("./test_input/day10.txt");
         assert_eq!(level2(test_input), 0)
     }
+
+    #[test]
+    #[should_panic(expected = "line 3, column 1")]
+    fn stray_lines_fail_to_parse() {
+        level1("noop\naddx 3\nstray\naddx -5\n");
+    }
+
+    #[test]
+    fn renders_in_ascii() {
+        let test_input = include_str!("./test_input/day10.txt");
+        let program = assemble(test_input).unwrap();
+        let screen = render(Cpu::run(&program), &glyphs::ASCII);
+        assert_eq!(
+            screen.lines().next().unwrap(),
+            "##..##..##..##..##..##..##..##..##..##.."
+        );
+    }
+
+    #[test]
+    fn assembles_both_ways() {
+        let source = "noop\naddx -3\njmp -2\nmulx 4";
+        let program = assemble(source).unwrap();
+        assert_eq!(
+            program,
+            [
+                Instruction::Noop,
+                Instruction::Addx(-3),
+                Instruction::Jmp(-2),
+                Instruction::Mulx(4)
+            ]
+        );
+        assert_eq!(disassemble(&program), source);
+        assert!(assemble("addx").is_err());
+    }
+
+    #[test]
+    fn runs_extended_instructions() {
+        // Jumps over the addx, then multiplies: x is 3 only after mulx ends
+        let program = assemble("jmp 2\naddx 10\nmulx 3\nnoop").unwrap();
+        let xs = Cpu::run(&program)
+            .map(|state| (state.cycle, state.x))
+            .collect_vec();
+        assert_eq!(xs, [(1, 1), (2, 1), (3, 1), (4, 3)]);
+        // Three cycles a round, so 33 rounds are over by cycle 101
+        let looping = assemble("addx 1\njmp -1").unwrap();
+        let x = Cpu::run(&looping).nth(100).unwrap().x;
+        assert_eq!(x, 34);
+        assert_eq!(Cpu::run(&assemble("jmp -5").unwrap()).count(), 1);
+    }
+
+    #[test]
+    fn endless_programs_stop() {
+        let endless = assemble("noop\njmp -1").unwrap();
+        assert_eq!(Cpu::run(&endless).count(), MAX_CYCLES);
+        let screen = render(Cpu::run(&endless), &glyphs::ASCII);
+        assert_eq!(screen.lines().count(), 6);
+        assert_eq!(trace("addx 2\njmp 0").len(), MAX_CYCLES);
+        let (level1, level2) = Solver::both(&endless);
+        assert_eq!(level1, Answer::Number(720));
+        assert_eq!(level2.to_string().lines().count(), 7);
+    }
+
+    #[test]
+    fn x_wraps_around() {
+        let program = assemble(&"mulx 1000\n".repeat(5)).unwrap();
+        let x = Cpu::run(&program).last().unwrap().x;
+        assert_eq!(x, 1000_i32.wrapping_pow(4));
+        let states = Cpu::run(&program).collect_vec();
+        assert_eq!(
+            Solver::level1(&program),
+            signal_strength(states.into_iter()).to_string()
+        );
+        // 1 + i32::MAX wraps around to i32::MIN
+        let huge = assemble(&format!("addx {}\n{}", i32::MAX, "noop\n".repeat(30))).unwrap();
+        assert_eq!(
+            Solver::level1(&huge),
+            (20 * i64::from(i32::MIN)).to_string()
+        );
+        assert_eq!(Solver::level2(&huge).lines().count(), 2);
+    }
+
+    #[test]
+    fn traces_every_cycle() {
+        let test_input = include_str!("./test_input/day10.txt");
+        let trace = trace(test_input);
+        assert_eq!(trace.len(), 240);
+        let state = trace[19];
+        assert_eq!((state.cycle, state.x), (20, 21));
+        assert_eq!(state.signal_strength(), 420);
+        let fields = state.fields().iter().map(Field::to_string).collect_vec();
+        assert_eq!(fields, ["20", "21", "addx -1", "420"]);
+    }
 }
@@ -1,15 +1,32 @@
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    ops::ControlFlow,
+    thread,
+};
+
 use itertools::Either;
 use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::{char, line_ending, u16, u64},
-    combinator::{map, map_opt, value},
-    multi::{count, separated_list0},
-    sequence::{delimited, preceded, tuple},
+    combinator::{map, value},
+    multi::{many1, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, tuple},
     IResult,
 };
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{
+        day::{Day, DiffModel, Part},
+        dot::Digraph,
+        export::{Field, Row},
+        math::gcd,
+        progress::AtomicProgress,
+    },
+};
 
 #[derive(Debug, Clone)]
 enum Var {
@@ -26,19 +43,39 @@ impl Var {
     }
 }
 
+impl Display for Var {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Var::Old => write!(f, "old"),
+            Var::Lit(y) => write!(f, "{y}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 enum Op {
     Add,
     Mul,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Formula {
     left: Var,
     right: Var,
     op: Op,
 }
 
+// As the input writes it
+impl Display for Formula {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Add => '+',
+            Op::Mul => '*',
+        };
+        write!(f, "new = {} {op} {}", self.left, self.right)
+    }
+}
+
 impl Formula {
     fn apply(&self, x: u64) -> u64 {
         let left = self.left.apply(x);
@@ -48,6 +85,40 @@ impl Formula {
             Op::Mul => left * right,
         }
     }
+
+    // The new worry level modulo `d`, for an old one that already is
+    fn apply_mod(&self, x: u64, d: u64) -> u64 {
+        let left = (self.left.apply(x) % d) as u128;
+        let right = (self.right.apply(x) % d) as u128;
+        let result = match self.op {
+            Op::Add => left + right,
+            Op::Mul => left * right,
+        };
+        (result % d as u128) as u64
+    }
+}
+
+// A worry level kept as its remainder modulo each monkey's divisor, which
+// is all the tests ever look at. Unlike one modulus for all monkeys it
+// doesn't need the lcm of the divisors, so large divisors can't overflow it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorryItem(Vec<u64>);
+
+impl WorryItem {
+    pub fn new(worry: u64, divisors: &[u64]) -> Self {
+        Self(divisors.iter().map(|d| worry % d).collect())
+    }
+
+    // The remainder modulo the divisor of the monkey at `index`
+    pub fn residue(&self, index: usize) -> u64 {
+        self.0[index]
+    }
+
+    fn apply(&mut self, formula: &Formula, divisors: &[u64]) {
+        for (residue, d) in self.0.iter_mut().zip(divisors) {
+            *residue = formula.apply_mod(*residue, *d);
+        }
+    }
 }
 
 fn var(input: &str) -> IResult<&str, Var> {
@@ -93,8 +164,8 @@ fn throw(test_success: bool) -> impl FnMut(&str) -> IResult<&str, usize> {
     }
 }
 
-#[derive(Debug)]
-struct Monkey {
+#[derive(Debug, Clone)]
+pub struct Monkey {
     items: Vec<u64>,
     formula: Formula,
     test_divisible: u64,
@@ -105,21 +176,67 @@ struct Monkey {
 
 impl Monkey {
     fn inspect_item(&self, size_bound: &SizeBound, x: u64) -> Either<u64, u64> {
-        let result = size_bound.apply(self.formula.apply(x));
-        if result % self.test_divisible == 0 {
+        let result = match size_bound {
+            SizeBound::DivideBy(d) => self.formula.apply(x) / d,
+            SizeBound::Modulus(d) => self.formula.apply_mod(x, *d),
+        };
+        if result.is_multiple_of(self.test_divisible) {
             Either::Left(result)
         } else {
             Either::Right(result)
         }
     }
-    fn inspect(&mut self, size_bound: &SizeBound) -> (Vec<u64>, Vec<u64>) {
+    fn inspect(&mut self, size_bound: &SizeBound, schedule: Schedule) -> (Vec<u64>, Vec<u64>) {
         self.inspected += self.items.len();
-        std::mem::take(&mut self.items)
-            .into_iter()
-            .partition_map(|x| self.inspect_item(size_bound, x))
+        let items = std::mem::take(&mut self.items);
+        match schedule {
+            // Empty turns have nothing to split, whatever the threshold
+            Schedule::Parallel { threads, threshold } if items.len() >= threshold.max(1) => {
+                let chunk_size = items.len().div_ceil(threads.max(1));
+                let progress = AtomicProgress::new("day11 split turns", &["items", "passed"]);
+                let (inspected, passed) = (progress.counter("items"), progress.counter("passed"));
+                let chunks: Vec<(Vec<u64>, Vec<u64>)> = thread::scope(|s| {
+                    items
+                        .chunks(chunk_size)
+                        .map(|chunk| {
+                            s.spawn(|| {
+                                inspected.add(chunk.len() as u64);
+                                chunk.iter().partition_map(|&x| {
+                                    let thrown = self.inspect_item(size_bound, x);
+                                    if thrown.is_left() {
+                                        passed.add(1);
+                                    }
+                                    thrown
+                                })
+                            })
+                        })
+                        .collect_vec()
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap())
+                        .collect()
+                });
+                // Joining the chunks in order keeps the throws in item order
+                let (passed, failed): (Vec<_>, Vec<_>) = chunks.into_iter().unzip();
+                (passed.concat(), failed.concat())
+            }
+            _ => items
+                .into_iter()
+                .partition_map(|x| self.inspect_item(size_bound, x)),
+        }
     }
 }
 
+// How a monkey works through its items during its turn. Rounds and turns
+// have to stay in order since monkeys catch items thrown earlier in the
+// same round, but within a turn every item's new worry level and target
+// depend on that item alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Sequential,
+    // Turns with at least `threshold` items are split between `threads` threads
+    Parallel { threads: usize, threshold: usize },
+}
+
 fn monkey(input: &str) -> IResult<&str, (usize, Monkey)> {
     map(
         tuple((
@@ -151,18 +268,14 @@ enum SizeBound {
     Modulus(u64),
 }
 
-impl SizeBound {
-    fn apply(&self, x: u64) -> u64 {
-        match self {
-            SizeBound::DivideBy(d) => x / d,
-            SizeBound::Modulus(d) => x % d,
-        }
-    }
-}
-
 struct MonkeyCabal {
     monkeys: Vec<Monkey>,
     size_bound: SizeBound,
+    schedule: Schedule,
+    // Items each monkey inspected, one entry per round played
+    history: Vec<Vec<usize>>,
+    // Items thrown from one monkey to another over all rounds
+    thrown: BTreeMap<(usize, usize), usize>,
 }
 
 impl MonkeyCabal {
@@ -176,88 +289,325 @@ impl MonkeyCabal {
     }
 
     fn round(&mut self) {
+        let mut inspected = Vec::with_capacity(self.monkeys.len());
         for i in 0..self.monkeys.len() {
-            let (mut passed, mut failed) = self.monkeys[i].inspect(&self.size_bound);
+            let (mut passed, mut failed) = self.monkeys[i].inspect(&self.size_bound, self.schedule);
+            inspected.push(passed.len() + failed.len());
             let Monkey {
                 throw_to_true,
                 throw_to_false,
                 ..
             } = self.monkeys[i];
+            for (target, items) in [(throw_to_true, &passed), (throw_to_false, &failed)] {
+                if !items.is_empty() {
+                    *self.thrown.entry((i, target)).or_default() += items.len();
+                }
+            }
             self.monkeys[throw_to_true].items.append(&mut passed);
             self.monkeys[throw_to_false].items.append(&mut failed);
         }
+        self.history.push(inspected);
     }
-}
 
-fn gcd(a: u64, b: u64) -> u64 {
-    let mut x = a;
-    let mut y = b;
-    while y != 0 {
-        let z = x.rem_euclid(y);
-        x = y;
-        y = z;
+    fn to_dot(&self) -> String {
+        let mut graph = Digraph::new("monkeys");
+        for (i, monkey) in self.monkeys.iter().enumerate() {
+            graph.node(i).attr(
+                "label",
+                format!("Monkey {i}\n{} inspected", monkey.inspected),
+            );
+        }
+        for ((source, target), count) in &self.thrown {
+            graph.edge(source, target).attr("label", count);
+        }
+        graph.to_dot()
     }
-    x
 }
 
-fn lcm(a: u64, b: u64) -> u64 {
-    a * (b / gcd(a, b))
+impl Simulation for MonkeyCabal {
+    type Output = Infallible;
+
+    fn step(&mut self) -> ControlFlow<Infallible> {
+        self.round();
+        ControlFlow::Continue(())
+    }
 }
 
-impl From<(Option<u64>, Vec<Monkey>)> for MonkeyCabal {
-    fn from(value: (Option<u64>, Vec<Monkey>)) -> Self {
-        let size_bound = match value.0 {
+impl MonkeyCabal {
+    fn new(size_bound: Option<u64>, monkeys: Vec<Monkey>) -> anyhow::Result<Self> {
+        let size_bound = match size_bound {
             Some(d) => SizeBound::DivideBy(d),
-            None => SizeBound::Modulus(
-                value
-                    .1
-                    .iter()
-                    .fold(1, |acc, monkey| lcm(acc, monkey.test_divisible)),
-            ),
+            None => {
+                let modulus = monkeys.iter().try_fold(1_u64, |acc, monkey| {
+                    let d = monkey.test_divisible;
+                    acc.checked_mul(d / gcd(acc, d))
+                });
+                SizeBound::Modulus(modulus.context(
+                    "the lcm of the divisors overflows u64, WorryModel::Residues can handle these",
+                )?)
+            }
         };
-        MonkeyCabal {
-            monkeys: value.1,
+        Ok(MonkeyCabal {
+            monkeys,
             size_bound,
+            schedule: Schedule::Sequential,
+            history: Vec::new(),
+            thrown: BTreeMap::new(),
+        })
+    }
+}
+
+// Blocks may be separated by any number of blank lines
+fn monkeys(input: &str) -> IResult<&str, Vec<(usize, Monkey)>> {
+    separated_list1(pair(line_ending, many1(line_ending)), monkey)(input)
+}
+
+// Monkeys may be listed in any order, but every index from 0 up has to be
+// declared exactly once and throws have to go to one of them.
+fn parse_monkeys(input: &str) -> anyhow::Result<Vec<Monkey>> {
+    let mut by_index = BTreeMap::new();
+    for (i, monkey) in parse_complete(monkeys, input.trim())? {
+        if by_index.insert(i, monkey).is_some() {
+            bail!("monkey {i} is declared twice");
         }
     }
+    if let Some(missing) = (0..by_index.len()).find(|i| !by_index.contains_key(i)) {
+        bail!("monkey {missing} is missing");
+    }
+    for (i, monkey) in &by_index {
+        for target in [monkey.throw_to_true, monkey.throw_to_false] {
+            ensure!(
+                target < by_index.len(),
+                "monkey {i} throws to monkey {target}, which does not exist"
+            );
+        }
+    }
+    Ok(by_index.into_values().collect())
 }
 
-fn parse_input(size_bound: Option<u64>) -> impl FnMut(&str) -> IResult<&str, MonkeyCabal> {
-    move |input| {
-        let mut i = 0;
-        map(
-            separated_list0(
-                count(line_ending, 2),
-                map_opt(monkey, move |(j, monkey)| {
-                    let result = (i == j).then_some(monkey);
-                    i += 1;
-                    result
-                }),
-            ),
-            |monkeys| (size_bound, monkeys).into(),
-        )(input)
+fn parse_input(input: &str, size_bound: Option<u64>) -> anyhow::Result<MonkeyCabal> {
+    MonkeyCabal::new(size_bound, parse_monkeys(input)?)
+}
+
+// Plays the rounds with every item as a WorryItem. Only for the game
+// without relief, since dividing by three has no meaning on remainders.
+fn residue_rounds(monkeys: &[Monkey], rounds: usize) -> Vec<usize> {
+    let divisors = monkeys.iter().map(|m| m.test_divisible).collect_vec();
+    let mut items = monkeys
+        .iter()
+        .map(|m| {
+            m.items
+                .iter()
+                .map(|&x| WorryItem::new(x, &divisors))
+                .collect_vec()
+        })
+        .collect_vec();
+    let mut inspected = vec![0; monkeys.len()];
+    for _ in 0..rounds {
+        for (i, monkey) in monkeys.iter().enumerate() {
+            inspected[i] += items[i].len();
+            for mut item in std::mem::take(&mut items[i]) {
+                item.apply(&monkey.formula, &divisors);
+                let target = if item.residue(i) == 0 {
+                    monkey.throw_to_true
+                } else {
+                    monkey.throw_to_false
+                };
+                items[target].push(item);
+            }
+        }
     }
+    inspected
 }
 
-pub fn level1(input: &str) -> usize {
-    let mut monkeys = parse_input(Some(3))(input).unwrap().1;
-    for _ in 0..20 {
-        monkeys.round();
+// How worry levels are kept small over the long game of level 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorryModel {
+    // One remainder modulo the lcm of all divisors
+    Lcm,
+    // A WorryItem with one remainder per divisor
+    Residues,
+}
+
+// Monkey business after `rounds` rounds without relief
+pub fn long_game(input: &str, rounds: usize, model: WorryModel) -> anyhow::Result<usize> {
+    long_game_of(&parse_monkeys(input)?, rounds, model)
+}
+
+fn long_game_of(monkeys: &[Monkey], rounds: usize, model: WorryModel) -> anyhow::Result<usize> {
+    let inspected = match model {
+        WorryModel::Lcm => {
+            let mut monkeys = MonkeyCabal::new(None, monkeys.to_vec())?;
+            run_n_steps(&mut monkeys, rounds);
+            return Ok(monkeys.monkey_business());
+        }
+        WorryModel::Residues => residue_rounds(monkeys, rounds),
+    };
+    Ok(inspected
+        .into_iter()
+        .sorted_by(|x, y| x.cmp(y).reverse())
+        .take(2)
+        .product())
+}
+
+pub fn lint(input: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut monkeys = Vec::new();
+    let mut throws = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        if let Some(rest) = line.strip_prefix("Monkey ") {
+            match rest.strip_suffix(':').and_then(|x| x.parse::<usize>().ok()) {
+                Some(j) => {
+                    if j != monkeys.len() {
+                        findings.push(Finding::new(
+                            line_no,
+                            format!("monkey {j} declared where {} was expected", monkeys.len()),
+                        ));
+                    }
+                    monkeys.push(j);
+                }
+                None => findings.push(Finding::new(line_no, format!("bad monkey header {line:?}"))),
+            }
+        } else if let Some((_, target)) = line.split_once("throw to monkey ") {
+            match target.parse::<usize>().ok() {
+                Some(target) => throws.push((line_no, monkeys.last().copied(), target)),
+                None => findings.push(Finding::new(
+                    line_no,
+                    format!("bad throw target {target:?}"),
+                )),
+            }
+        }
+    }
+    for (line_no, source, target) in throws {
+        if !monkeys.contains(&target) {
+            findings.push(Finding::new(
+                line_no,
+                format!("throws to monkey {target}, which does not exist"),
+            ));
+        } else if source == Some(target) {
+            findings.push(Finding::new(
+                line_no,
+                format!("monkey {target} throws to itself"),
+            ));
+        }
     }
-    monkeys.monkey_business()
+    findings
+}
+
+// How many items each monkey inspected in each of the first `rounds` rounds,
+// with or without worry dropping after each inspection as in level 1.
+pub fn inspection_history(
+    input: &str,
+    rounds: usize,
+    relief: bool,
+    schedule: Schedule,
+) -> Vec<Vec<usize>> {
+    let mut monkeys = or_report(parse_input(input, relief.then_some(3)));
+    monkeys.schedule = schedule;
+    run_n_steps(&mut monkeys, rounds);
+    monkeys.history
+}
+
+// Items one monkey inspected in one round, rounds counting from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inspections {
+    pub round: usize,
+    pub monkey: usize,
+    pub inspected: usize,
+}
+
+impl Row for Inspections {
+    const COLUMNS: &'static [&'static str] = &["round", "monkey", "inspected"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![self.round.into(), self.monkey.into(), self.inspected.into()]
+    }
+}
+
+// The 20 rounds of level 1, one row per monkey and round
+pub fn inspection_rows(input: &str) -> Vec<Inspections> {
+    inspection_history(input, 20, true, Schedule::Sequential)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(round, inspected)| {
+            inspected
+                .into_iter()
+                .enumerate()
+                .map(move |(monkey, inspected)| Inspections {
+                    round: round + 1,
+                    monkey,
+                    inspected,
+                })
+        })
+        .collect()
+}
+
+// Graphviz rendering of where items went during the 20 rounds of level 1,
+// with edges labelled by the number of items thrown along them.
+pub fn throw_graph(input: &str) -> String {
+    let mut monkeys = or_report(parse_input(input, Some(3)));
+    run_n_steps(&mut monkeys, 20);
+    monkeys.to_dot()
+}
+
+// Monkey business after the 20 rounds of level 1, with relief
+fn short_game(monkeys: &[Monkey]) -> anyhow::Result<usize> {
+    let mut monkeys = MonkeyCabal::new(Some(3), monkeys.to_vec())?;
+    run_n_steps(&mut monkeys, 20);
+    Ok(monkeys.monkey_business())
+}
+
+pub fn level1(input: &str) -> usize {
+    or_report(parse_monkeys(input).and_then(|monkeys| short_game(&monkeys)))
 }
 
 pub fn level2(input: &str) -> usize {
-    let mut monkeys = parse_input(None)(input).unwrap().1;
-    for _ in 0..10_000 {
-        monkeys.round();
+    or_report(long_game(input, 10_000, WorryModel::Lcm))
+}
+
+// Each level plays its own copy of the monkeys with its own worry bound
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Monkey>;
+
+    fn parse(input: &str) -> Vec<Monkey> {
+        or_report(parse_monkeys(input))
+    }
+
+    fn level1(monkeys: &Vec<Monkey>) -> String {
+        or_report(short_game(monkeys)).to_string()
+    }
+
+    fn level2(monkeys: &Vec<Monkey>) -> String {
+        or_report(long_game_of(monkeys, 10_000, WorryModel::Lcm)).to_string()
+    }
+}
+
+impl DiffModel for Solver {
+    fn parts(input: &str) -> anyhow::Result<Vec<Part>> {
+        Ok(parse_monkeys(input)?
+            .iter()
+            .enumerate()
+            .map(|(i, monkey)| Part {
+                key: format!("monkey {i}"),
+                fields: vec![
+                    ("items", monkey.items.iter().join(", ")),
+                    ("operation", monkey.formula.to_string()),
+                    ("divisor", monkey.test_divisible.to_string()),
+                    ("if true", format!("monkey {}", monkey.throw_to_true)),
+                    ("if false", format!("monkey {}", monkey.throw_to_false)),
+                ],
+            })
+            .collect())
     }
-    monkeys.monkey_business()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::progress;
 
     #[test]
     fn level1_given_example() {
@@ -270,4 +620,253 @@ mod test {
         let test_input = include_str!("./test_input/day11.txt");
         assert_eq!(level2(test_input), 2713310158)
     }
+
+    fn summed(history: Vec<Vec<usize>>) -> Vec<usize> {
+        history.into_iter().fold(vec![0; 4], |acc, round| {
+            acc.iter().zip(round).map(|(a, b)| a + b).collect()
+        })
+    }
+
+    #[test]
+    fn history_per_round() {
+        let test_input = include_str!("./test_input/day11.txt");
+        let history = inspection_history(test_input, 20, false, Schedule::Sequential);
+        assert_eq!(history[0], vec![2, 4, 3, 6]);
+        assert_eq!(summed(history), vec![99, 97, 8, 103]);
+        let history = inspection_history(test_input, 20, true, Schedule::Sequential);
+        assert_eq!(summed(history), vec![101, 95, 7, 105]);
+        let rows = inspection_rows(test_input);
+        assert_eq!(rows.len(), 80);
+        assert_eq!(
+            rows[79],
+            Inspections {
+                round: 20,
+                monkey: 3,
+                inspected: 6
+            }
+        );
+    }
+
+    // The example with every starting item list repeated `copies` times
+    fn stress_input(copies: usize) -> String {
+        include_str!("./test_input/day11.txt")
+            .lines()
+            .map(|line| match line.split_once("Starting items: ") {
+                Some((indent, items)) => {
+                    let items = [items].repeat(copies).join(", ");
+                    format!("{indent}Starting items: {items}")
+                }
+                None => line.to_string(),
+            })
+            .join("\n")
+    }
+
+    #[test]
+    fn parallel_turns_match_sequential() {
+        let input = stress_input(3000);
+        let parallel = Schedule::Parallel {
+            threads: 4,
+            threshold: 1000,
+        };
+        for (rounds, relief) in [(20, true), (100, false)] {
+            let mut sequential = parse_input(&input, relief.then_some(3)).unwrap();
+            let mut threaded = parse_input(&input, relief.then_some(3)).unwrap();
+            threaded.schedule = parallel;
+            run_n_steps(&mut sequential, rounds);
+            run_n_steps(&mut threaded, rounds);
+            assert_eq!(threaded.history, sequential.history);
+            assert_eq!(threaded.thrown, sequential.thrown);
+            for (a, b) in threaded.monkeys.iter().zip(&sequential.monkeys) {
+                assert_eq!(a.items, b.items);
+            }
+        }
+        // Odd thread counts and chunks smaller than the threshold too
+        let odd = Schedule::Parallel {
+            threads: 7,
+            threshold: 1,
+        };
+        assert_eq!(
+            inspection_history(&input, 20, true, odd),
+            inspection_history(&input, 20, true, Schedule::Sequential)
+        );
+        // Where every turn is split, the threads count every inspection
+        progress::take();
+        let mut threaded = parse_input(&input, Some(3)).unwrap();
+        threaded.schedule = odd;
+        run_n_steps(&mut threaded, 20);
+        let inspected: usize = threaded.monkeys.iter().map(|monkey| monkey.inspected).sum();
+        let split = progress::take();
+        assert_eq!(split[0].label, "day11 split turns");
+        assert_eq!(split[0].counts[0], ("items", inspected as u64));
+    }
+
+    #[test]
+    fn parallel_turns_skip_empty_monkeys() {
+        // Monkeys with nothing to inspect, which a threshold of 0 would
+        // otherwise split into chunks of size 0
+        let test_input = include_str!("./test_input/day11.txt");
+        let every_turn = Schedule::Parallel {
+            threads: 4,
+            threshold: 0,
+        };
+        assert_eq!(
+            inspection_history(test_input, 20, true, every_turn),
+            inspection_history(test_input, 20, true, Schedule::Sequential)
+        );
+    }
+
+    #[test]
+    fn worry_models_agree() {
+        let test_input = include_str!("./test_input/day11.txt");
+        for rounds in [1, 20, 1000, 10_000] {
+            assert_eq!(
+                long_game(test_input, rounds, WorryModel::Residues).unwrap(),
+                long_game(test_input, rounds, WorryModel::Lcm).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn residues_track_exact_worry() {
+        let divisors = [4_294_967_291, 4_294_967_279, 13, 1 << 40];
+        let formulas = ["old * old", "old + 6", "old * 19", "old * old", "old + 3"]
+            .map(|f| formula(&format!("  Operation: new = {f}")).unwrap().1);
+        let mut exact: u128 = 79;
+        let mut item = WorryItem::new(79, &divisors);
+        for formula in &formulas {
+            exact = match formula.op {
+                Op::Add => exact + formula.right.apply(0) as u128,
+                Op::Mul => match formula.right {
+                    Var::Old => exact * exact,
+                    Var::Lit(y) => exact * y as u128,
+                },
+            };
+            item.apply(formula, &divisors);
+            for (i, d) in divisors.iter().enumerate() {
+                assert_eq!(item.residue(i) as u128, exact % *d as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn residues_survive_huge_divisors() {
+        let test_input = include_str!("./test_input/day11.txt")
+            .replace("divisible by 23", "divisible by 4294967291")
+            .replace("divisible by 19", "divisible by 4294967279")
+            .replace("divisible by 13", "divisible by 4294967231")
+            .replace("divisible by 17", "divisible by 4294967197");
+        assert!(long_game(&test_input, 20, WorryModel::Lcm)
+            .unwrap_err()
+            .to_string()
+            .contains("overflows u64"));
+        assert!(long_game(&test_input, 1000, WorryModel::Residues).is_ok());
+    }
+
+    #[test]
+    fn throw_graph_given_example() {
+        let test_input = include_str!("./test_input/day11.txt");
+        assert_eq!(
+            throw_graph(test_input),
+            r#"digraph monkeys {
+    0 [label="Monkey 0\n101 inspected"];
+    1 [label="Monkey 1\n95 inspected"];
+    2 [label="Monkey 2\n7 inspected"];
+    3 [label="Monkey 3\n105 inspected"];
+    0 -> 3 [label="101"];
+    1 -> 0 [label="91"];
+    1 -> 2 [label="4"];
+    2 -> 1 [label="4"];
+    2 -> 3 [label="3"];
+    3 -> 0 [label="13"];
+    3 -> 1 [label="92"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn monkeys_in_any_order() {
+        let test_input = include_str!("./test_input/day11.txt");
+        let blocks = test_input.trim().split("\n\n").collect_vec();
+        let shuffled = [blocks[2], blocks[0], blocks[3], blocks[1]].join("\n\n\n\n");
+        assert_eq!(level1(&shuffled), 10605);
+    }
+
+    #[test]
+    fn incomplete_monkeys_are_an_error() {
+        let test_input = include_str!("./test_input/day11.txt");
+        let blocks = test_input.trim().split("\n\n").collect_vec();
+        let twice = [blocks[0], blocks[1], blocks[1], blocks[2], blocks[3]].join("\n\n");
+        assert_eq!(
+            parse_input(&twice, None).err().unwrap().to_string(),
+            "monkey 1 is declared twice"
+        );
+        let gap = [blocks[0], blocks[1], blocks[3]].join("\n\n");
+        assert_eq!(
+            parse_input(&gap, None).err().unwrap().to_string(),
+            "monkey 2 is missing"
+        );
+        let truncated = [blocks[0], blocks[1], blocks[2]].join("\n\n");
+        assert_eq!(
+            parse_input(&truncated, None).err().unwrap().to_string(),
+            "monkey 0 throws to monkey 3, which does not exist"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "monkey 2 is missing")]
+    fn levels_report_incomplete_monkeys() {
+        let test_input = include_str!("./test_input/day11.txt");
+        let blocks = test_input.trim().split("\n\n").collect_vec();
+        level2(&[blocks[0], blocks[1], blocks[3]].join("\n\n"));
+    }
+
+    #[test]
+    fn lint_given_example() {
+        let test_input = include_str!("./test_input/day11.txt");
+        assert_eq!(lint(test_input), vec![])
+    }
+
+    #[test]
+    fn lint_finds_gaps_and_bad_targets() {
+        let test_input = include_str!("./test_input/day11.txt")
+            .replace("Monkey 2:", "Monkey 5:")
+            .replace("If true: throw to monkey 0", "If true: throw to monkey 7");
+        assert_eq!(
+            lint(&test_input)
+                .into_iter()
+                .map(|finding| finding.to_string())
+                .collect_vec(),
+            vec![
+                "line 15: monkey 5 declared where 2 was expected",
+                "line 5: throws to monkey 2, which does not exist",
+                "line 12: throws to monkey 2, which does not exist",
+                "line 26: throws to monkey 7, which does not exist",
+            ]
+        )
+    }
+
+    #[test]
+    fn diffs_monkeys() {
+        let test_input = include_str!("./test_input/day11.txt");
+        let changed = test_input
+            .replace("Starting items: 79, 98", "Starting items: 79, 99")
+            .replace("new = old * old", "new = old + old");
+        assert_eq!(
+            Solver::diff(test_input, &changed).unwrap(),
+            [
+                "~ monkey 0 items: 79, 98 -> 79, 99",
+                "~ monkey 2 operation: new = old * old -> new = old + old"
+            ]
+        );
+        let (first_three, _) = test_input.split_once("\n\nMonkey 3").unwrap();
+        let fewer = first_three.replace("throw to monkey 3", "throw to monkey 0");
+        let diff = Solver::diff(test_input, &fewer).unwrap();
+        assert_eq!(diff[0], "~ monkey 0 if false: monkey 3 -> monkey 0");
+        assert_eq!(
+            diff.last().unwrap(),
+            "- monkey 3: items 74, operation new = old + 3, divisor 17, \
+             if true monkey 0, if false monkey 1"
+        );
+    }
 }
@@ -1,336 +1,307 @@
-use std::{cell::Cell, collections::BinaryHeap, ops::ControlFlow};
-
-use nom::{
-    bytes::complete::{take_until, take_while},
-    character::complete::{anychar, line_ending, satisfy},
-    combinator::map_res,
-    multi::many0,
-    IResult, Parser,
+use crate::{
+    prelude::*,
+    util::{
+        day::Day,
+        input::byte_grid,
+        search::{a_star, bfs},
+    },
 };
 
-use crate::util::prelude::*;
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct GridPos {
-    pub x: usize,
-    pub y: usize,
+// How the characters of a map translate to heights. S and E mark the start
+// and the end, which stand on the lowest and highest ground respectively.
+pub trait Elevation {
+    fn height(&self, c: char) -> Option<i64>;
+    fn lowest(&self) -> i64;
+    fn highest(&self) -> i64;
 }
 
-impl GridPos {
-    pub fn dist(&self, other: &Self) -> usize {
-        ((self.x as isize - other.x as isize).abs() + (self.y as isize - other.y as isize).abs())
-            as usize
-    }
-
-    pub fn neighbors(&self, max_x: usize, max_y: usize) -> impl Iterator<Item = GridPos> + '_ {
-        let x = self.x as isize;
-        let y = self.y as isize;
-        [(-1, 0), (1, 0), (0, -1), (0, 1)]
-            .into_iter()
-            .map(move |(dx, dy)| (x + dx, y + dy))
-            .filter_map(move |(x2, y2)| {
-                (((0..(max_x as isize)).contains(&x2)) && (0..(max_y as isize)).contains(&y2))
-                    .then_some(Self {
-                        x: x2 as usize,
-                        y: y2 as usize,
-                    })
-            })
+// The puzzle's encoding, a through z
+pub struct Lowercase;
+
+impl Elevation for Lowercase {
+    fn height(&self, c: char) -> Option<i64> {
+        c.is_ascii_lowercase()
+            .then(|| ascii_code(c) - LOWER_A_ASCII)
+    }
+
+    fn lowest(&self) -> i64 {
+        0
+    }
+
+    fn highest(&self) -> i64 {
+        25
     }
 }
 
-impl From<(usize, usize)> for GridPos {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+// Whether a step from one height to another is allowed while climbing
+pub trait Climb {
+    fn passable(&self, from: i64, to: i64) -> bool;
+}
+
+// The puzzle's rule: up at most one, down any distance
+pub struct AtMostOneUp;
+
+impl Climb for AtMostOneUp {
+    fn passable(&self, from: i64, to: i64) -> bool {
+        to <= from + 1
     }
 }
 
-#[derive(Debug)]
-pub struct Grid<T> {
-    inner: Vec<T>,
-    pub length: usize,
-    pub height: usize,
+impl<F: Fn(i64, i64) -> bool> Climb for F {
+    fn passable(&self, from: i64, to: i64) -> bool {
+        self(from, to)
+    }
 }
 
-impl<T> Grid<T> {
-    pub fn contains(&self, pos: &GridPos) -> bool {
-        (0..self.length).contains(&pos.x) && (0..self.height).contains(&pos.y)
+// What a step from one height to another costs, None if it isn't allowed.
+// `cheapest` bounds every step's cost from below; it scales the distance
+// estimate that guides the search, so it must never be too high.
+// Costs that are one for every allowed step are searched breadth first,
+// without the priority queue.
+pub trait MoveCost {
+    fn cost(&self, from: i64, to: i64) -> Option<usize>;
+    fn cheapest(&self) -> usize;
+
+    fn is_unit(&self) -> bool {
+        false
     }
-    pub fn get(&self, pos: &GridPos) -> Option<&T> {
-        if self.contains(pos) {
-            self.inner.get(pos.x + self.length * pos.y)
-        } else {
-            None
-        }
+}
+
+// Every passable step counts as one
+impl<C: Climb> MoveCost for C {
+    fn cost(&self, from: i64, to: i64) -> Option<usize> {
+        self.passable(from, to).then_some(1)
     }
-    pub fn get_mut(&mut self, pos: &GridPos) -> Option<&mut T> {
-        if self.contains(pos) {
-            self.inner.get_mut(pos.x + self.length * pos.y)
-        } else {
-            None
-        }
+
+    fn cheapest(&self) -> usize {
+        1
     }
 
-    pub fn iter_rows(&self) -> impl Iterator<Item = std::slice::Iter<T>> {
-        self.inner.chunks(self.length).map(|s| s.iter())
-    }
-
-    pub fn iter_rows_mut(&mut self) -> impl Iterator<Item = std::slice::IterMut<T>> {
-        self.inner.chunks_mut(self.length).map(|s| s.iter_mut())
-    }
-
-    pub fn neighbors<'a, 'b: 'a>(
-        &'a self,
-        pos: &'b GridPos,
-    ) -> impl Iterator<Item = (GridPos, &'a T)> + 'a {
-        self.contains(pos).then_some(()).into_iter().flat_map(|_| {
-            pos.neighbors(self.length, self.height)
-                .filter_map(|new_pos| self.get(&new_pos).map(|t| (new_pos, t)))
-        })
-    }
-
-    pub fn parse<'a, F: Parser<&'a str, Vec<T>, nom::error::Error<&'a str>>>(
-        mut line_parser: F,
-    ) -> impl Parser<&'a str, Self, nom::error::Error<&'a str>> {
-        move |input| match line_parser.parse(input) {
-            IResult::Ok((input, first_line)) => {
-                let length = first_line.len();
-                let mut acc = first_line;
-                let mut height = 1;
-                let mut rest = input;
-                loop {
-                    match line_ending::<_, nom::error::Error<&'a str>>(rest) {
-                        IResult::Ok((new_rest, _)) => rest = new_rest,
-                        Err(_) => {
-                            return IResult::Ok((
-                                input,
-                                Self {
-                                    inner: acc,
-                                    length,
-                                    height,
-                                },
-                            ))
-                        }
-                    }
-                    match line_parser.parse(rest) {
-                        IResult::Ok((new_rest, mut row)) if row.len() == length => {
-                            acc.append(&mut row);
-                            height += 1;
-                            rest = new_rest;
-                        }
-                        _ => {
-                            return IResult::Ok((
-                                input,
-                                Self {
-                                    inner: acc,
-                                    length,
-                                    height,
-                                },
-                            ))
-                        }
-                    }
-                }
-            }
-            Err(e) => Err(e),
-        }
+    fn is_unit(&self) -> bool {
+        true
     }
 }
 
-impl<J, T> FromIterator<J> for Grid<T>
-where
-    J: Iterator<Item = T>,
-{
-    fn from_iter<I: IntoIterator<Item = J>>(iter: I) -> Self {
-        let mut iter = iter.into_iter();
-        let mut height = 0;
-        if let Some(first) = iter.next() {
-            let mut acc = first.collect_vec();
-            let length = acc.len();
-            for row in iter {
-                acc.extend(row);
-                if acc.len() - height * length != length {
-                    panic!("Expected {length} elements")
-                }
-                height += 1;
-            }
-            Self {
-                inner: acc,
-                height,
-                length,
-            }
-        } else {
-            Self {
-                inner: Vec::new(),
-                length: 0,
-                height: 0,
-            }
-        }
+// Steps costing whatever the function says, zero included
+pub struct Weighted<F>(pub F);
+
+impl<F: Fn(i64, i64) -> Option<usize>> MoveCost for Weighted<F> {
+    fn cost(&self, from: i64, to: i64) -> Option<usize> {
+        (self.0)(from, to)
+    }
+
+    fn cheapest(&self) -> usize {
+        0
     }
 }
 
 #[derive(Debug)]
 enum Tree {
-    Tree(i64),
-    Start,
-    End,
+    Height(i64),
+    Start(i64),
+    End(i64),
 }
 
 impl Tree {
+    fn from_char(c: char, elevation: &impl Elevation) -> anyhow::Result<Self> {
+        match c {
+            'S' => Ok(Tree::Start(elevation.lowest())),
+            'E' => Ok(Tree::End(elevation.highest())),
+            _ => elevation
+                .height(c)
+                .map(Tree::Height)
+                .ok_or_else(|| anyhow!("Expected S, E, or a height, found {c:?}")),
+        }
+    }
+
     fn height(&self) -> i64 {
         match *self {
-            Tree::Tree(height) => height,
-            Tree::Start => 0,
-            Tree::End => 25,
+            Tree::Height(height) | Tree::Start(height) | Tree::End(height) => height,
         }
     }
 }
 
-impl TryFrom<char> for Tree {
-    type Error = anyhow::Error;
+// A parsed map, searchable any number of times. Hand made maps sometimes
+// mark several starts or ends, so all of them are kept.
+#[derive(Debug)]
+pub struct HeightMap {
+    grid: Grid<Tree>,
+    starts: Vec<GridPos>,
+    ends: Vec<GridPos>,
+    lowest: i64,
+}
 
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            'a'..='z' => Ok(Tree::Tree(ascii_code(value) - LOWER_A_ASCII)),
-            'S' => Ok(Tree::Start),
-            'E' => Ok(Tree::End),
-            _ => Err(anyhow!("Expected S, E, or lowercase ASCII character")),
-        }
+// The one cell with `marker`, an error if the map has several
+fn single(positions: &[GridPos], marker: char) -> anyhow::Result<GridPos> {
+    match positions {
+        [pos] => Ok(*pos),
+        _ => bail!(
+            "Ambiguous map, {} {marker} markers at {}",
+            positions.len(),
+            positions
+                .iter()
+                .map(|pos| format!("({}, {})", pos.x, pos.y))
+                .join(", ")
+        ),
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct SearchEntry {
-    priority: usize,
-    depth: usize,
-    position: GridPos,
+fn parse_map(input: &str, elevation: &impl Elevation) -> anyhow::Result<HeightMap> {
+    parse_map_bytes(input.as_bytes(), elevation)
 }
 
-impl From<(usize, usize, GridPos)> for SearchEntry {
-    fn from((priority, depth, position): (usize, usize, GridPos)) -> Self {
-        Self {
-            priority,
-            depth,
-            position,
+// Bytes rather than a str, so that a memory mapped input parses in place.
+// A character that is no height fails with what it was.
+pub fn parse_map_bytes(input: &[u8], elevation: &impl Elevation) -> anyhow::Result<HeightMap> {
+    let mut starts = Vec::new();
+    let mut ends = Vec::new();
+    let mut bad_char = None;
+    let grid = byte_grid(input, |pos, b| {
+        let tree = Tree::from_char(char::from(b), elevation)
+            .map_err(|e| bad_char = Some(e))
+            .ok()?;
+        match tree {
+            Tree::Start(_) => starts.push(pos),
+            Tree::End(_) => ends.push(pos),
+            _ => {}
         }
+        Some(tree)
+    });
+    if let Some(e) = bad_char {
+        return Err(e);
     }
+    let grid = grid.context("Expected every row of the map to be as long as the first")?;
+    ensure!(
+        !starts.is_empty() && !ends.is_empty(),
+        "Expected a map of heights with S and E"
+    );
+    Ok(HeightMap {
+        grid,
+        starts,
+        ends,
+        lowest: elevation.lowest(),
+    })
 }
 
-impl PartialOrd for SearchEntry {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl HeightMap {
+    // The cheapest way from `start` to a cell `is_end` accepts, with the
+    // estimate `remaining` never more than what is left to pay. Breadth
+    // first when every step costs one.
+    fn search(
+        &self,
+        start: &GridPos,
+        is_end: impl Fn(&Tree) -> bool,
+        remaining: impl Fn(&GridPos) -> usize,
+        cost: impl Fn(&Tree, &Tree) -> Option<usize>,
+        unit: bool,
+    ) -> Option<usize> {
+        let neighbors = |pos: &GridPos| {
+            let tree = self.grid.get(pos).unwrap();
+            self.grid
+                .neighbors(pos)
+                .filter_map(|(new_pos, new_tree)| Some((new_pos, cost(tree, new_tree)?)))
+                .collect_vec()
+        };
+        let is_end = |pos: &GridPos| self.grid.get(pos).is_some_and(&is_end);
+        let found = if unit {
+            let steps = |pos: &GridPos| neighbors(pos).into_iter().map(|(pos, _)| pos);
+            bfs(*start, steps, is_end)
+        } else {
+            a_star(*start, neighbors, remaining, is_end)
+        };
+        found.map(|(_, cost)| cost)
+    }
+
+    // From the start to the end, which there must be one of each of
+    pub fn climb(&self, climb: &impl MoveCost) -> anyhow::Result<Option<usize>> {
+        let start = single(&self.starts, 'S')?;
+        let end = single(&self.ends, 'E')?;
+        Ok(self.search(
+            &start,
+            |tree| matches!(tree, Tree::End(_)),
+            |pos| pos.dist(&end) * climb.cheapest(),
+            |start, end| climb.cost(start.height(), end.height()),
+            climb.is_unit(),
+        ))
     }
-}
 
-impl Ord for SearchEntry {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.priority
-            .cmp(&other.priority)
-            .then(self.depth.cmp(&other.depth))
-            .reverse()
+    // To the end from the nearest cell at the lowest height, every S among
+    // them, so only the end has to be unique
+    pub fn trail(&self, climb: &impl MoveCost) -> anyhow::Result<Option<usize>> {
+        let end = single(&self.ends, 'E')?;
+        Ok(self.search(
+            &end,
+            |tree| tree.height() == self.lowest,
+            |_| 0,
+            |start, end| climb.cost(end.height(), start.height()),
+            climb.is_unit(),
+        ))
     }
 }
 
-fn parse_grid(input: &str) -> Option<(Grid<(Tree, Cell<bool>)>, GridPos, GridPos)> {
-    let mut y = 0;
-    let mut start_pos: Option<GridPos> = None;
-    let mut end_pos: Option<GridPos> = None;
-    let (_, grid) = Grid::parse(|line| {
-        map_res(take_while(|c: char| c.is_alphabetic()), |line: &str| {
-            let result = line
-                .chars()
-                .enumerate()
-                .map(|(x, c)| {
-                    c.try_into().map(|t: Tree| {
-                        match t {
-                            Tree::Start => start_pos = Some(GridPos { x, y }),
-                            Tree::End => end_pos = Some(GridPos { x, y }),
-                            _ => {}
-                        };
-                        (t, false.into())
-                    })
-                })
-                .collect();
-            y += 1;
-            result
-        })(line)
-    })
-    .parse(input)
-    .ok()?;
-    Some((grid, start_pos?, end_pos?))
+// Cheapest way from S to E, or None if E can't be reached. With a `Climb`
+// that is the fewest steps. An error for maps without a single S and E.
+pub fn shortest_climb(
+    input: &str,
+    elevation: &impl Elevation,
+    climb: &impl MoveCost,
+) -> anyhow::Result<Option<usize>> {
+    parse_map(input, elevation)?.climb(climb)
 }
 
-fn a_star(
-    grid: Grid<(Tree, Cell<bool>)>,
-    start_pos: GridPos,
-    is_end: impl Fn(&Tree) -> bool,
-    priority: impl Fn(usize, &GridPos) -> usize,
-    cost: impl Fn(&Tree, &Tree) -> Option<usize>,
-) -> Option<usize> {
-    grid.get(&start_pos).map(|(_, visited)| visited.set(true));
-    let mut queue: BinaryHeap<SearchEntry> = BinaryHeap::new();
-    queue.push((priority(0, &start_pos), 0, start_pos).into());
-    while let Some(SearchEntry {
-        depth, position, ..
-    }) = queue.pop()
-    {
-        if let ControlFlow::Break(result) = match grid.get(&position) {
-            Some((tree, _)) => grid
-                .neighbors(&position)
-                .try_for_each(|(new_pos, t)| match t {
-                    (new_tree, new_visited) => match cost(&tree, &new_tree) {
-                        Some(move_cost) if !new_visited.get() => {
-                            if is_end(new_tree) {
-                                return ControlFlow::Break(depth + move_cost);
-                            }
-                            let priority = priority(depth + move_cost, &new_pos);
-                            new_visited.set(true);
-                            queue.push(SearchEntry {
-                                priority,
-                                depth: depth + move_cost,
-                                position: new_pos,
-                            });
-                            ControlFlow::Continue(())
-                        }
-                        _ => ControlFlow::Continue(()),
-                    },
-                }),
-            _ => ControlFlow::Continue(()),
-        } {
-            return Some(result);
-        }
-    }
-    None
+// Cheapest way to E from any square at the lowest height, searched
+// backwards from E so every step is costed in its climbing direction
+pub fn shortest_trail(
+    input: &str,
+    elevation: &impl Elevation,
+    climb: &impl MoveCost,
+) -> anyhow::Result<Option<usize>> {
+    parse_map(input, elevation)?.trail(climb)
+}
+
+// The puzzle's answers, which there is only an answer to if E can be reached
+fn steps(found: anyhow::Result<Option<usize>>) -> usize {
+    or_report(found.and_then(|steps| steps.context("No path reaches E")))
 }
 
 pub fn level1(input: &str) -> usize {
-    let (grid, start_pos, end_pos) = parse_grid(input).unwrap();
-    a_star(
-        grid,
-        start_pos,
-        |tree| matches!(tree, Tree::End),
-        |depth, pos| depth + pos.dist(&end_pos),
-        |start, end| (end.height() <= start.height() + 1).then_some(1),
-    )
-    .unwrap()
+    steps(shortest_climb(input, &Lowercase, &AtMostOneUp))
 }
 
 pub fn level2(input: &str) -> usize {
-    let (grid, _, end_pos) = parse_grid(input).unwrap();
-    a_star(
-        grid,
-        end_pos,
-        |tree| tree.height() == 0,
-        |depth, _| depth,
-        |start, end| (start.height() <= end.height() + 1).then_some(1),
-    )
-    .unwrap()
+    steps(shortest_trail(input, &Lowercase, &AtMostOneUp))
+}
+
+pub fn level1_bytes(input: &[u8]) -> usize {
+    steps(parse_map_bytes(input, &Lowercase).and_then(|map| map.climb(&AtMostOneUp)))
+}
+
+pub fn level2_bytes(input: &[u8]) -> usize {
+    steps(parse_map_bytes(input, &Lowercase).and_then(|map| map.trail(&AtMostOneUp)))
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = HeightMap;
+
+    fn parse(input: &str) -> HeightMap {
+        or_report(parse_map(input, &Lowercase))
+    }
+
+    fn level1(map: &HeightMap) -> String {
+        steps(map.climb(&AtMostOneUp)).to_string()
+    }
+
+    fn level2(map: &HeightMap) -> String {
+        steps(map.trail(&AtMostOneUp)).to_string()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::random::Rng;
 
     #[test]
     fn level1_given_example() {
@@ -343,4 +314,175 @@ mod test {
         let test_input = include_str!("./test_input/day12.txt");
         assert_eq!(level2(test_input), 29)
     }
+
+    #[test]
+    fn custom_climb_rules() {
+        let test_input = include_str!("./test_input/day12.txt");
+        let gentle = |from: i64, to: i64| (from - 2..=from + 1).contains(&to);
+        assert_eq!(
+            shortest_climb(test_input, &Lowercase, &gentle).unwrap(),
+            Some(31)
+        );
+        let flat = |from: i64, to: i64| from == to;
+        assert_eq!(shortest_climb(test_input, &Lowercase, &flat).unwrap(), None);
+    }
+
+    struct Digits;
+
+    impl Elevation for Digits {
+        fn height(&self, c: char) -> Option<i64> {
+            c.to_digit(10).map(i64::from)
+        }
+
+        fn lowest(&self) -> i64 {
+            0
+        }
+
+        fn highest(&self) -> i64 {
+            9
+        }
+    }
+
+    #[test]
+    fn other_elevation_alphabets() {
+        let input = "S2468\n01357\n9999E";
+        let steps_of_two = |from: i64, to: i64| to <= from + 2;
+        assert_eq!(
+            shortest_climb(input, &Digits, &steps_of_two).unwrap(),
+            Some(6)
+        );
+        assert_eq!(
+            shortest_trail(input, &Digits, &steps_of_two).unwrap(),
+            Some(5)
+        );
+        assert_eq!(shortest_climb(input, &Digits, &AtMostOneUp).unwrap(), None);
+    }
+
+    #[test]
+    fn weighted_moves_take_the_cheapest_path() {
+        // S reaches 1 first, but the way through 2 into 3 is cheaper
+        let input = "S18\n23E";
+        let costs = Weighted(|from, to| match (from, to) {
+            (0, 1) => Some(1),
+            (0, 2) => Some(2),
+            (1, 3) => Some(10),
+            (2, 3) => Some(1),
+            (3, 9) => Some(1),
+            _ => Some(100),
+        });
+        assert_eq!(shortest_climb(input, &Digits, &costs).unwrap(), Some(4));
+        let free = Weighted(|_, _| Some(0));
+        assert_eq!(shortest_climb(input, &Digits, &free).unwrap(), Some(0));
+        assert_eq!(shortest_trail(input, &Digits, &free).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn level1_needs_one_start() {
+        let input = "SbcdE\nabcSz";
+        let error = shortest_climb(input, &Lowercase, &AtMostOneUp).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Ambiguous map, 2 S markers at (0, 0), (3, 1)"
+        );
+        assert!(shortest_climb("SbE\nEcz", &Lowercase, &AtMostOneUp).is_err());
+        assert!(shortest_climb("abcdE", &Lowercase, &AtMostOneUp).is_err());
+    }
+
+    #[test]
+    fn reports_what_went_wrong() {
+        let error = shortest_climb("SbX\nabE", &Lowercase, &AtMostOneUp).unwrap_err();
+        assert_eq!(error.to_string(), "Expected S, E, or a height, found 'X'");
+        let error = parse_map_bytes(b"Sbc\nabE", &Digits).unwrap_err();
+        assert_eq!(error.to_string(), "Expected S, E, or a height, found 'b'");
+        let error = shortest_climb("Sbc\naE", &Lowercase, &AtMostOneUp).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected every row of the map to be as long as the first"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "No path reaches E")]
+    fn unreachable_end_is_reported() {
+        level1("SbcE");
+    }
+
+    #[test]
+    fn level2_starts_from_every_s_and_a() {
+        // The S next to E wins, where an a would have been three steps off
+        let input = "abcdSE\nSzzzzz";
+        assert_eq!(
+            shortest_trail(input, &Lowercase, &|_, _| true).unwrap(),
+            Some(1)
+        );
+        let example = include_str!("./test_input/day12.txt").replace('a', "S");
+        assert!(shortest_climb(&example, &Lowercase, &AtMostOneUp).is_err());
+        assert_eq!(
+            shortest_trail(&example, &Lowercase, &AtMostOneUp).unwrap(),
+            Some(29)
+        );
+        assert!(shortest_trail("SbE\nEcz", &Lowercase, &AtMostOneUp).is_err());
+    }
+
+    // Relaxes every step until nothing improves, for checking the search
+    fn bellman_ford(input: &str, costs: &impl MoveCost) -> Option<usize> {
+        let HeightMap {
+            grid, starts, ends, ..
+        } = parse_map(input, &Digits).unwrap();
+        let (start, end) = (starts[0], ends[0]);
+        let index = |pos: &GridPos| pos.x + pos.y * grid.length;
+        let mut best = vec![None; grid.length * grid.height];
+        best[index(&start)] = Some(0);
+        let positions = (0..grid.height)
+            .flat_map(|y| (0..grid.length).map(move |x| GridPos { x, y }))
+            .collect_vec();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for pos in &positions {
+                let (Some(depth), Some(tree)) = (best[index(pos)], grid.get(pos)) else {
+                    continue;
+                };
+                for (new_pos, new_tree) in grid.neighbors(pos) {
+                    if let Some(cost) = costs.cost(tree.height(), new_tree.height()) {
+                        let new = &mut best[index(&new_pos)];
+                        if new.is_none_or(|known| depth + cost < known) {
+                            *new = Some(depth + cost);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        best[index(&end)]
+    }
+
+    #[test]
+    fn weighted_search_agrees_with_relaxation() {
+        let mut rng = Rng::new(12);
+        let costs = Weighted(|from: i64, to: i64| {
+            (to <= from + 3).then(|| (to - from).unsigned_abs() as usize * 3 % 7)
+        });
+        for _ in 0..200 {
+            let (length, height) = (2 + rng.below(6), 1 + rng.below(5));
+            let mut cells = (0..length * height)
+                .map(|_| char::from_digit(rng.below(10) as u32, 10).unwrap())
+                .collect_vec();
+            let start = rng.below(cells.len());
+            let end = (start + 1 + rng.below(cells.len() - 1)) % cells.len();
+            cells[start] = 'S';
+            cells[end] = 'E';
+            let input = cells.chunks(length).map(String::from_iter).join("\n");
+            assert_eq!(
+                shortest_climb(&input, &Digits, &costs).unwrap(),
+                bellman_ford(&input, &costs),
+                "{input}"
+            );
+            assert_eq!(
+                shortest_climb(&input, &Digits, &AtMostOneUp).unwrap(),
+                bellman_ford(&input, &AtMostOneUp),
+                "{input}"
+            );
+        }
+    }
 }
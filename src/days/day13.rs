@@ -1,110 +1,57 @@
 use std::cmp::Ordering;
-use std::ops::ControlFlow;
 
-use nom::branch::alt;
-use nom::character::complete::{char, i32, line_ending};
+use nom::character::complete::{i32, line_ending, multispace0};
 use nom::combinator::map;
-use nom::multi::{count, fold_many0, many_m_n, separated_list0};
-use nom::sequence::{delimited, separated_pair};
+use nom::multi::{count, many_m_n, separated_list0};
+use nom::sequence::{separated_pair, terminated};
 use nom::IResult;
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{
+        day::Day,
+        nested::{nested_list, Nested},
+    },
+};
 
-#[derive(Debug, PartialEq, Eq)]
-enum PacketData {
-    Data(i32),
-    List(Vec<PacketData>),
-}
-
-impl PacketData {
-    fn divider(i: i32) -> Self {
-        PacketData::List(vec![PacketData::List(vec![PacketData::Data(i)])])
-    }
-}
-
-impl PartialOrd for PacketData {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for PacketData {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (PacketData::Data(x), PacketData::Data(y)) => x.cmp(y),
-            (PacketData::Data(x), PacketData::List(ys)) => match ys.get(0) {
-                Some(y) => PacketData::Data(*x).cmp(y).then(if ys.len() > 1 {
-                    Ordering::Less
-                } else {
-                    Ordering::Equal
-                }),
-                None => Ordering::Greater,
-            },
-            (PacketData::List(xs), PacketData::Data(y)) => match xs.get(0) {
-                Some(x) => PacketData::Data(*y).cmp(x).reverse().then(if xs.len() > 1 {
-                    Ordering::Greater
-                } else {
-                    Ordering::Equal
-                }),
-                None => Ordering::Less,
-            },
-            (PacketData::List(xs), PacketData::List(ys)) => {
-                if let ControlFlow::Break(result) =
-                    xs.iter()
-                        .zip_longest(ys.iter())
-                        .try_for_each(|xy| match xy {
-                            itertools::EitherOrBoth::Both(x, y) => match x.cmp(y) {
-                                Ordering::Equal => ControlFlow::Continue(()),
-                                result => ControlFlow::Break(result),
-                            },
-                            itertools::EitherOrBoth::Left(_) => {
-                                ControlFlow::Break(Ordering::Greater)
-                            }
-                            itertools::EitherOrBoth::Right(_) => ControlFlow::Break(Ordering::Less),
-                        })
-                {
-                    result
-                } else {
-                    Ordering::Equal
-                }
-            }
-        }
-    }
-}
+pub type Packet = Nested<i32>;
 
-fn packet_data_list(input: &str) -> IResult<&str, Vec<PacketData>> {
-    delimited(
-        char('['),
-        separated_list0(
-            char(','),
-            alt((
-                map(packet_data_list, PacketData::List),
-                map(i32, PacketData::Data),
-            )),
-        ),
-        char(']'),
-    )(input)
+fn divider(i: i32) -> Packet {
+    Nested::List(vec![Nested::List(vec![Nested::Leaf(i)])])
 }
 
-fn parse_line(input: &str) -> IResult<&str, PacketData> {
-    map(packet_data_list, PacketData::List)(input)
+fn parse_line(input: &str) -> IResult<&str, Packet> {
+    nested_list(i32)(input)
 }
 
 fn parse_pair(input: &str) -> IResult<&str, Ordering> {
     map(
         separated_pair(parse_line, line_ending, parse_line),
-        |(x, y)| x.cmp(&y),
+        |(x, y)| x.packet_cmp(&y),
+    )(input)
+}
+
+fn parse_all_lines(input: &str) -> IResult<&str, Vec<Packet>> {
+    terminated(
+        separated_list0(many_m_n(1, 2, line_ending), parse_line),
+        multispace0,
     )(input)
 }
 
-fn parse_all_lines(input: &str) -> IResult<&str, Vec<PacketData>> {
-    separated_list0(many_m_n(1, 2, line_ending), parse_line)(input)
+fn decoder_key(packets: &[Packet]) -> usize {
+    let divider_two = divider(2);
+    let divider_six = divider(6);
+    let mut packets = packets.iter().collect_vec();
+    packets.push(&divider_two);
+    packets.push(&divider_six);
+    packets.sort_by(|a, b| a.packet_cmp(b));
+    let two_pos = packets.iter().position(|p| *p == &divider_two).unwrap() + 1;
+    let six_pos = packets.iter().position(|p| *p == &divider_six).unwrap() + 1;
+    two_pos * six_pos
 }
 
 pub fn level1(input: &str) -> usize {
-    separated_list0(count(line_ending, 2), parse_pair)(input)
-        .unwrap()
-        .1
+    parse_or_report(separated_list0(count(line_ending, 2), parse_pair), input)
         .into_iter()
         .enumerate()
         .filter_map(|(i, c)| (c != Ordering::Greater).then_some(i + 1))
@@ -112,15 +59,32 @@ pub fn level1(input: &str) -> usize {
 }
 
 pub fn level2(input: &str) -> usize {
-    let divider_two = PacketData::divider(2);
-    let divider_six = PacketData::divider(6);
-    let mut packets = parse_all_lines(input).unwrap().1;
-    packets.push(PacketData::divider(2));
-    packets.push(PacketData::divider(6));
-    packets.sort();
-    let two_pos = packets.iter().position(|p| p == &divider_two).unwrap() + 1;
-    let six_pos = packets.iter().position(|p| p == &divider_six).unwrap() + 1;
-    two_pos * six_pos
+    decoder_key(&parse_or_report(parse_all_lines, input))
+}
+
+// Parses every packet once, level 1 then compares them two by two
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Packet>;
+
+    fn parse(input: &str) -> Vec<Packet> {
+        parse_or_report(parse_all_lines, input)
+    }
+
+    fn level1(packets: &Vec<Packet>) -> String {
+        packets
+            .iter()
+            .tuples()
+            .enumerate()
+            .filter_map(|(i, (a, b))| a.packet_cmp(b).is_le().then_some(i + 1))
+            .sum::<usize>()
+            .to_string()
+    }
+
+    fn level2(packets: &Vec<Packet>) -> String {
+        decoder_key(packets).to_string()
+    }
 }
 
 #[cfg(test)]
@@ -138,4 +102,61 @@ mod test {
         let test_input = include_str!("./test_input/day13.txt");
         assert_eq!(level2(test_input), 140)
     }
+
+    fn parse(input: &str) -> Result<Vec<Packet>, (usize, usize)> {
+        parse_complete(parse_all_lines, input).map_err(|e| e.line_col())
+    }
+
+    #[test]
+    fn lenient_packets() {
+        let strict = parse("[1,[2,3],[]]").unwrap();
+        for lenient in ["[1, [2, 3], []]", "[ 1 ,[2,3,], [ ] ]", "[1,[2,3,],[],]\n"] {
+            assert_eq!(parse(lenient).unwrap(), strict, "{lenient}");
+        }
+        let test_input = include_str!("./test_input/day13.txt");
+        let spaced = test_input
+            .replace(',', ", ")
+            .replace(']', ",]")
+            .replace("[,]", "[]");
+        assert_eq!(decoder_key(&parse(&spaced).unwrap()), 140);
+    }
+
+    #[test]
+    fn errors_point_at_the_mistake() {
+        // Small mutations of valid packets, which used to be accepted by
+        // stopping early or reported where the list ended
+        assert_eq!(parse("[1,,2]"), Err((1, 4)));
+        assert_eq!(parse("[1 2]"), Err((1, 4)));
+        assert_eq!(parse("[-]"), Err((1, 2)));
+        assert_eq!(parse("[,]"), Err((1, 2)));
+        assert_eq!(parse("[[1],[2]"), Err((1, 9)));
+        assert_eq!(parse("[1]\n[2,x]"), Err((2, 4)));
+        // Too many blank lines leave the next packet unread
+        assert_eq!(parse("[1]\n\n\n[2]"), Err((4, 1)));
+        assert_eq!(parse("[1]]"), Err((1, 4)));
+    }
+
+    #[test]
+    fn deep_packets_fail_instead_of_overflowing() {
+        let deep = "[".repeat(100_000) + &"]".repeat(100_000);
+        assert_eq!(parse(&deep), Err((1, 257)));
+    }
+
+    #[test]
+    fn mutated_packets_parse_or_fail_in_bounds() {
+        let mut rng = crate::util::random::Rng::new(13);
+        let test_input = include_str!("./test_input/day13.txt");
+        let alphabet = b"[], 0123456789-\n";
+        for _ in 0..2000 {
+            let mut bytes = test_input.as_bytes().to_vec();
+            for _ in 0..1 + rng.below(3) {
+                let i = rng.below(bytes.len());
+                bytes[i] = alphabet[rng.below(alphabet.len())];
+            }
+            let input = String::from_utf8(bytes).unwrap();
+            if let Err((line, _)) = parse(&input) {
+                assert!(line <= input.lines().count() + 1, "{input}");
+            }
+        }
+    }
 }
@@ -1,53 +1,28 @@
-use std::{fmt::Display, iter::once, ops::ControlFlow};
+use std::{collections::HashSet, fmt::Display, ops::ControlFlow};
+
+use bitvec::prelude::*;
 
-use itertools::izip;
 use nom::{
     bytes::complete::{tag, take_while},
     character::complete::{char, digit1, line_ending},
-    combinator::{all_consuming, map, map_res},
+    combinator::{map, map_res},
     multi::separated_list0,
     sequence::separated_pair,
-    Finish, IResult,
+    IResult,
 };
 
-use crate::util::prelude::*;
-
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-struct GridPos {
-    x: usize,
-    y: usize,
-}
-
-impl GridPos {
-    fn children(
-        self,
-        min_x: usize,
-        max_x: usize,
-        max_y: usize,
-    ) -> impl Iterator<Item = Option<GridPos>> {
-        let x = self.x as isize;
-        let y = self.y as isize;
-        [(0, 1), (-1, 1), (1, 1)].into_iter().map(move |(dx, dy)| {
-            let x2 = x + dx;
-            let y2 = y + dy;
-            ((((min_x as isize)..(max_x as isize)).contains(&x2))
-                && (0..(max_y as isize)).contains(&y2))
-            .then_some(Self {
-                x: x2 as usize,
-                y: y2 as usize,
-            })
-        })
-    }
-}
-
-impl From<(usize, usize)> for GridPos {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
+use crate::{
+    prelude::*,
+    util::{
+        day::Day,
+        export::{Field, Row},
+        glyphs::{self, Glyphs},
+        grid,
+        infra::Level,
+        neighbors::{Bounds, SAND3},
+        random::Rng,
+    },
+};
 
 fn usize(input: &str) -> IResult<&str, usize> {
     map_res(digit1, |s: &str| s.parse())(input)
@@ -89,31 +64,41 @@ enum Location {
 
 impl Display for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Location::Rock => '█',
-            Location::Sand => '⣿',
-            Location::Air => ' ',
-        }
-        .fmt(f)
+        self.glyph(&glyphs::current()).fmt(f)
     }
 }
 
 impl Location {
+    fn glyph(&self, glyphs: &Glyphs) -> char {
+        match self {
+            Location::Rock => glyphs.solid,
+            Location::Sand => glyphs.grain,
+            Location::Air => glyphs.empty,
+        }
+    }
+
     fn is_free(&self) -> bool {
         matches!(*self, Location::Air)
     }
 }
 
-#[derive(Debug)]
-struct Path(Vec<GridPos>);
+#[derive(Debug, Clone)]
+pub struct Path(Vec<GridPos>);
 
 fn path(input: &str) -> IResult<&str, Path> {
     map(separated_list0(tag(" -> "), grid_pos), Path)(input)
 }
 
+pub fn parse_paths(input: &str) -> Vec<Path> {
+    parse_or_report(separated_list0(line_ending, path), input)
+}
+
+// Rock and sand as one bit per cell each, so that the level 2 scan can
+// combine whole rows at once.
 #[derive(Debug)]
 struct Grid {
-    inner: Vec<Location>,
+    rock: BitVec,
+    sand: BitVec,
     x_offset: usize,
     length: usize,
     height: usize,
@@ -121,36 +106,54 @@ struct Grid {
 
 impl Display for Grid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for line in self.inner.chunks(self.length) {
-            for pos in line.iter() {
-                pos.fmt(f)?
-            }
-            writeln!(f)?
-        }
-        std::fmt::Result::Ok(())
+        self.render(&glyphs::current()).fmt(f)
     }
 }
 
 impl Grid {
+    // Rendered with row numbers and the column numbers of the cave, which
+    // start at `x_offset`
+    fn labeled(&self, glyphs: &Glyphs) -> String {
+        let rows = self.render(glyphs).lines().map(String::from).collect_vec();
+        grid::with_axes(&rows, (self.x_offset as i64, 0))
+    }
+
+    fn render(&self, glyphs: &Glyphs) -> String {
+        let mut out = String::new();
+        for y in 0..self.height {
+            for x in self.x_offset..self.x_offset + self.length {
+                out.push(self.get(GridPos { x, y }).unwrap().glyph(glyphs));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
     fn contains(&self, pos: GridPos) -> bool {
         (self.x_offset..(self.x_offset + self.length)).contains(&pos.x)
             && (0..self.height).contains(&pos.y)
     }
 
-    fn get(&self, pos: GridPos) -> Option<&Location> {
-        if self.contains(pos) {
-            self.inner.get(pos.x - self.x_offset + self.length * pos.y)
-        } else {
-            None
-        }
+    fn index(&self, pos: GridPos) -> Option<usize> {
+        self.contains(pos)
+            .then(|| pos.x - self.x_offset + self.length * pos.y)
     }
 
-    fn get_mut(&mut self, pos: GridPos) -> Option<&mut Location> {
-        if self.contains(pos) {
-            self.inner
-                .get_mut(pos.x - self.x_offset + self.length * pos.y)
+    fn get(&self, pos: GridPos) -> Option<Location> {
+        let i = self.index(pos)?;
+        Some(if self.rock[i] {
+            Location::Rock
+        } else if self.sand[i] {
+            Location::Sand
         } else {
-            None
+            Location::Air
+        })
+    }
+
+    fn set(&mut self, pos: GridPos, location: Location) {
+        if let Some(i) = self.index(pos) {
+            self.rock.set(i, matches!(location, Location::Rock));
+            self.sand.set(i, matches!(location, Location::Sand));
         }
     }
 
@@ -169,7 +172,8 @@ impl Grid {
             let length = right - left + 1;
             let height = top + 1;
             let mut result = Self {
-                inner: vec![Location::Air; length * height],
+                rock: bitvec![0; length * height],
+                sand: bitvec![0; length * height],
                 x_offset: left,
                 length,
                 height,
@@ -177,67 +181,39 @@ impl Grid {
             paths.into_iter().for_each(|path| result.add_path(path));
             result
         } else {
-            return Self {
-                inner: Vec::new(),
+            Self {
+                rock: BitVec::new(),
+                sand: BitVec::new(),
                 x_offset: 0,
                 length: 0,
                 height: 0,
-            };
+            }
         }
     }
 
     fn add_path(&mut self, Path(nodes): Path) {
         let mut nodes = nodes.iter();
-        let Some(mut start_pos) = nodes.next() else {return};
+        let Some(mut start_pos) = nodes.next() else {
+            return;
+        };
         for end_pos in nodes {
             if start_pos.x == end_pos.x {
                 let start_y = start_pos.y.min(end_pos.y);
                 let end_y = start_pos.y.max(end_pos.y);
                 for y in start_y..=end_y {
-                    self.get_mut(GridPos { x: start_pos.x, y })
-                        .map(|loc| *loc = Location::Rock);
+                    self.set(GridPos { x: start_pos.x, y }, Location::Rock)
                 }
             } else if start_pos.y == end_pos.y {
                 let start_x = start_pos.x.min(end_pos.x);
                 let end_x = start_pos.x.max(end_pos.x);
                 for x in start_x..=end_x {
-                    self.get_mut(GridPos { x, y: start_pos.y })
-                        .map(|loc| *loc = Location::Rock);
+                    self.set(GridPos { x, y: start_pos.y }, Location::Rock)
                 }
             }
             start_pos = end_pos;
         }
     }
 
-    // Continue: Sand dropped from this location gets dropped
-    // Break: Sand dropped from this location escapes
-    fn get_descendant_count(&mut self, start_pos: Option<GridPos>) -> ControlFlow<usize, usize> {
-        if let Some(start_pos) = start_pos {
-            match self.get(start_pos) {
-                None => ControlFlow::Break(0),
-                Some(Location::Air) => start_pos
-                    .children(self.x_offset, self.x_offset + self.length, self.height)
-                    .try_fold(0, |count, child| {
-                        self.get_descendant_count(child)
-                            .map_break(|child_count| child_count + count)
-                            .map_continue(|child_count| child_count + count)
-                    })
-                    .map_continue(|count| {
-                        self.get_mut(start_pos).map(|loc| *loc = Location::Sand);
-                        count + 1
-                    }),
-                _ => ControlFlow::Continue(0),
-            }
-        } else {
-            return ControlFlow::Break(0);
-        }
-    }
-
-    fn drop_sand(&mut self, start_x: usize) -> Option<usize> {
-        self.get_descendant_count(Some(GridPos { x: start_x, y: 0 }))
-            .break_value()
-    }
-
     fn get_sandy_count(&self, start_x: usize) -> usize {
         // In the end, exactly those locations which can be reached from the
         // start position by going down one and at most one to a side
@@ -245,35 +221,33 @@ impl Grid {
         // triangles whose area can be calculated from the height.
         // This leaves the interior, which can be calculated by scanning
         // through the rows in O(length * height).
+        // Bit j + 1 of `sandy` stands for column j, with one extra bit on
+        // either side that stays set once sand escapes on that side.
         let mut left_escape: Option<usize> = None;
         let mut right_escape: Option<usize> = None;
-        let mut sandy = vec![false; self.length + 2];
+        let mut sandy = bitvec![0; self.length + 2];
         let mut sandy_count = 1;
-        sandy[start_x - self.x_offset + 1] = true;
-        let mut stash: Vec<bool> = vec![false; self.length + 2];
-        let last_row: &[Location] = &vec![Location::Air; self.length];
-        for (i, row) in self
-            .inner
-            .chunks(self.length)
-            .chain(once(last_row))
-            .enumerate()
-            .skip(1)
-        {
-            for (is_sandy, parents, loc) in izip!(stash[1..].iter_mut(), sandy.windows(3), row) {
-                *is_sandy = parents.iter().any(Clone::clone) && matches!(loc, Location::Air);
-                if *is_sandy {
-                    sandy_count += 1
-                }
-            }
-            stash[0] = left_escape.is_some();
-            stash[self.length + 1] = right_escape.is_some();
+        sandy.set(start_x - self.x_offset + 1, true);
+        let mut open = !self.rock.clone();
+        open.extend(std::iter::repeat_n(true, self.length));
+        for i in 1..=self.height {
+            let open_row = &open[i * self.length..(i + 1) * self.length];
+            let mut next = sandy.clone();
+            next[1..] |= &sandy[..self.length + 1];
+            next[..self.length + 1] |= &sandy[1..];
+            let mut interior = next[1..=self.length].to_bitvec();
+            interior &= open_row;
+            sandy_count += interior.count_ones();
             if left_escape.is_none() && sandy[1] {
                 left_escape.replace(i);
             }
             if right_escape.is_none() && sandy[self.length] {
                 right_escape.replace(i);
             }
-            std::mem::swap(&mut sandy, &mut stash);
+            next[1..=self.length].copy_from_bitslice(&interior);
+            next.set(0, left_escape.is_some());
+            next.set(self.length + 1, right_escape.is_some());
+            sandy = next;
         }
         let left_height = left_escape.map(|h| self.height + 1 - h).unwrap_or_default();
         let right_height = right_escape
@@ -283,20 +257,349 @@ impl Grid {
     }
 }
 
+// Why a sand simulation stopped, and how many grains came to rest before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandOutcome {
+    // A grain fell out of the grid, there being no floor
+    Abyss { settled: usize },
+    // A grain came to rest on the source itself
+    SourceBlocked { settled: usize },
+}
+
+impl SandOutcome {
+    pub fn settled(&self) -> usize {
+        match self {
+            SandOutcome::Abyss { settled } | SandOutcome::SourceBlocked { settled } => *settled,
+        }
+    }
+}
+
+// Drops one grain per step until one falls out of the grid or the source is
+// covered. Each grain starts from where the previous one was last still
+// moving, so no cell is fallen through twice.
+struct SandFall {
+    grid: Grid,
+    path: Vec<GridPos>,
+    settled: usize,
+    // Where the last grain came to rest
+    last: Option<GridPos>,
+}
+
+impl SandFall {
+    fn new(grid: Grid, start_x: usize) -> Self {
+        let start = GridPos { x: start_x, y: 0 };
+        Self {
+            path: grid.get(start).into_iter().map(|_| start).collect(),
+            grid,
+            settled: 0,
+            last: None,
+        }
+    }
+}
+
+impl Simulation for SandFall {
+    type Output = SandOutcome;
+
+    fn step(&mut self) -> ControlFlow<SandOutcome> {
+        let Grid {
+            x_offset,
+            length,
+            height,
+            ..
+        } = self.grid;
+        while let Some(&pos) = self.path.last() {
+            let mut next = None;
+            for child in SAND3.around(
+                pos,
+                Bounds {
+                    xs: x_offset..x_offset + length,
+                    ys: 0..height,
+                },
+            ) {
+                match child {
+                    None => {
+                        return ControlFlow::Break(SandOutcome::Abyss {
+                            settled: self.settled,
+                        })
+                    }
+                    Some(child) if self.grid.get(child).is_some_and(|loc| loc.is_free()) => {
+                        next = Some(child);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            match next {
+                Some(child) => self.path.push(child),
+                None => {
+                    self.grid.set(pos, Location::Sand);
+                    self.path.pop();
+                    self.settled += 1;
+                    self.last = Some(pos);
+                    return ControlFlow::Continue(());
+                }
+            }
+        }
+        ControlFlow::Break(SandOutcome::SourceBlocked {
+            settled: self.settled,
+        })
+    }
+}
+
+// The rock with the floor two below the lowest rock added as one more path,
+// wide enough that no grain can fall past its ends
+fn with_floor(mut paths: Vec<Path>, start_x: usize) -> Vec<Path> {
+    let floor = paths
+        .iter()
+        .flat_map(|path| path.0.iter())
+        .map(|pos| pos.y + 2)
+        .max()
+        .unwrap_or(2);
+    paths.push(Path(vec![
+        GridPos {
+            x: start_x.saturating_sub(floor),
+            y: floor,
+        },
+        GridPos {
+            x: start_x + floor,
+            y: floor,
+        },
+    ]));
+    paths
+}
+
+// Where the sand comes from, and whether level 2's floor catches it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandConfig {
+    pub source_x: usize,
+    pub floor: bool,
+}
+
+impl Default for SandConfig {
+    fn default() -> Self {
+        Self {
+            source_x: 500,
+            floor: false,
+        }
+    }
+}
+
+fn sand_fall(paths: Vec<Path>, config: SandConfig) -> SandFall {
+    let paths = if config.floor {
+        with_floor(paths, config.source_x)
+    } else {
+        paths
+    };
+    SandFall::new(Grid::new(paths), config.source_x)
+}
+
+// Where each grain comes to rest, in the order they fall
+pub fn settled_positions(paths: Vec<Path>, config: SandConfig) -> impl Iterator<Item = GridPos> {
+    let mut sim = sand_fall(paths, config);
+    std::iter::from_fn(move || match sim.step() {
+        ControlFlow::Continue(()) => sim.last,
+        ControlFlow::Break(_) => None,
+    })
+}
+
+// How many grains came to rest in a row of the cave
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandRow {
+    pub y: usize,
+    pub settled: usize,
+}
+
+impl Row for SandRow {
+    const COLUMNS: &'static [&'static str] = &["y", "settled"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![self.y.into(), self.settled.into()]
+    }
+}
+
+// The rows sand settled in, top first
+pub fn sand_per_row(input: &str, floor: bool) -> Vec<SandRow> {
+    let config = SandConfig {
+        floor,
+        ..SandConfig::default()
+    };
+    settled_positions(parse_paths(input), config)
+        .counts_by(|pos| pos.y)
+        .into_iter()
+        .map(|(y, settled)| SandRow { y, settled })
+        .sorted_by_key(|row| row.y)
+        .collect()
+}
+
+// The labeled cave as the sand piles up, from empty to the last grain in at
+// most `FRAMES` more frames
+pub fn visualize(input: &str, level: Level) -> Vec<String> {
+    const FRAMES: usize = 50;
+    let config = SandConfig {
+        floor: level == Level::Two,
+        ..SandConfig::default()
+    };
+    let paths = parse_paths(input);
+    let settled = settled_positions(paths.clone(), config).collect_vec();
+    let mut grid = sand_fall(paths, config).grid;
+    let glyphs = glyphs::current();
+    let mut frames = vec![grid.labeled(&glyphs)];
+    for grains in settled.chunks(settled.len().div_ceil(FRAMES).max(1)) {
+        for &pos in grains {
+            grid.set(pos, Location::Sand);
+        }
+        frames.push(grid.labeled(&glyphs));
+    }
+    frames
+}
+
+// Drops grains one at a time from x = 500, onto a floor or into the abyss
+pub fn simulate(input: &str, floor: bool) -> SandOutcome {
+    let config = SandConfig {
+        floor,
+        ..SandConfig::default()
+    };
+    run_until_break(&mut sand_fall(parse_paths(input), config))
+}
+
 pub fn level1(input: &str) -> usize {
-    let paths = all_consuming(separated_list0(line_ending, path))(input)
-        .finish()
-        .unwrap()
-        .1;
-    Grid::new(paths).drop_sand(500).unwrap()
+    settled_positions(parse_paths(input), SandConfig::default()).count()
+}
+
+// Sand resting on the floor two below the lowest rock, spreading one column
+// further to each side per row except where rock blocks it. The intervals
+// never build a grid, which pays off when the rock is sparse compared to
+// the width of the pile.
+fn interval_sandy_count(paths: &[Path], start_x: usize) -> usize {
+    let height = paths
+        .iter()
+        .flat_map(|path| path.0.iter())
+        .map(|pos| pos.y + 1)
+        .max()
+        .unwrap_or_default();
+    let mut rock = vec![DisjointIntervals::new(); height + 1];
+    for Path(nodes) in paths {
+        for (a, b) in nodes.iter().tuple_windows() {
+            for row in &mut rock[a.y.min(b.y)..=a.y.max(b.y)] {
+                row.insert(Interval::new(a.x as i64, b.x as i64));
+            }
+        }
+    }
+    let mut sandy: DisjointIntervals = [Interval::new(start_x as i64, start_x as i64)]
+        .into_iter()
+        .collect();
+    let mut sandy_count = sandy.len();
+    for rock_row in &rock[1..] {
+        sandy = sandy.widened(1).difference(rock_row);
+        sandy_count += sandy.len();
+    }
+    sandy_count
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloorScan {
+    // Row by row over the rendered grid, a word of cells at a time
+    Bitset,
+    // Row by row over intervals of reachable columns
+    Intervals,
+    // Grain by grain, like level 1
+    Simulated,
+}
+
+pub fn floor_sand_count(input: &str, scan: FloorScan) -> usize {
+    let paths = parse_paths(input);
+    match scan {
+        FloorScan::Bitset => Grid::new(paths).get_sandy_count(500),
+        FloorScan::Intervals => interval_sandy_count(&paths, 500),
+        FloorScan::Simulated => {
+            let config = SandConfig {
+                floor: true,
+                ..SandConfig::default()
+            };
+            settled_positions(paths, config).count()
+        }
+    }
+}
+
+// Small random rock structures around the sand source, for checking the
+// floor scans against the brute force below. One segment always crosses
+// x = 500 since the bitset grid has to contain the source column.
+pub fn random_input(rng: &mut Rng) -> String {
+    let mut lines = vec![format!(
+        "{},{y} -> {},{y}",
+        rng.range(490..=500),
+        rng.range(500..=510),
+        y = rng.range(2..=12)
+    )];
+    for _ in 0..rng.below(4) {
+        let (mut x, mut y) = (rng.range(490..=510), rng.range(1..=12));
+        let mut points = vec![format!("{x},{y}")];
+        for i in 0..1 + rng.below(3) {
+            if i % 2 == 0 {
+                x = rng.range(490..=510);
+            } else {
+                y = rng.range(1..=12);
+            }
+            points.push(format!("{x},{y}"));
+        }
+        lines.push(points.join(" -> "));
+    }
+    lines.join("\n")
+}
+
+// Drops every grain of level 2 one at a time, for random inputs only
+pub fn level2_brute_force(input: &str) -> usize {
+    let paths = parse_paths(input);
+    let mut blocked = HashSet::new();
+    for Path(nodes) in &paths {
+        for (a, b) in nodes.iter().tuple_windows() {
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                for y in a.y.min(b.y)..=a.y.max(b.y) {
+                    blocked.insert((x as i64, y as i64));
+                }
+            }
+        }
+    }
+    let floor = blocked.iter().map(|(_, y)| y + 2).max().unwrap_or(2);
+    let mut grains = 0;
+    while !blocked.contains(&(500, 0)) {
+        let (mut x, mut y) = (500, 0);
+        while let Some(dx) = [0, -1, 1]
+            .into_iter()
+            .find(|dx| y + 1 < floor && !blocked.contains(&(x + dx, y + 1)))
+        {
+            x += dx;
+            y += 1;
+        }
+        blocked.insert((x, y));
+        grains += 1;
+    }
+    grains
 }
 
 pub fn level2(input: &str) -> usize {
-    let paths = all_consuming(separated_list0(line_ending, path))(input)
-        .finish()
-        .unwrap()
-        .1;
-    Grid::new(paths).get_sandy_count(500)
+    floor_sand_count(input, FloorScan::Bitset)
+}
+
+// The grid is built anew per level since level 1 fills it with sand
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Path>;
+
+    fn parse(input: &str) -> Vec<Path> {
+        parse_paths(input)
+    }
+
+    fn level1(paths: &Vec<Path>) -> String {
+        settled_positions(paths.clone(), SandConfig::default())
+            .count()
+            .to_string()
+    }
+
+    fn level2(paths: &Vec<Path>) -> String {
+        Grid::new(paths.clone()).get_sandy_count(500).to_string()
+    }
 }
 
 #[cfg(test)]
@@ -309,9 +612,140 @@ mod test {
         assert_eq!(level1(test_input), 24)
     }
 
+    #[test]
+    fn counts_sand_per_row() {
+        let test_input = include_str!("./test_input/day14.txt");
+        let rows = sand_per_row(test_input, false);
+        assert_eq!(rows.iter().map(|row| row.settled).sum::<usize>(), 24);
+        assert_eq!(rows.last(), Some(&SandRow { y: 8, settled: 6 }));
+    }
+
     #[test]
     fn level2_given_example() {
         let test_input = include_str!("./test_input/day14.txt");
         assert_eq!(level2(test_input), 93)
     }
+
+    #[test]
+    fn sand_falls_grain_by_grain() {
+        let test_input = include_str!("./test_input/day14.txt");
+        let paths = parse_or_report(separated_list0(line_ending, path), test_input);
+        let mut sim = SandFall::new(Grid::new(paths), 500);
+        assert_eq!(run_n_steps(&mut sim, 5), None);
+        assert_eq!(
+            sim.grid.to_string().lines().rev().nth(1).unwrap(),
+            "    ⣿⣿⣿⣿█ "
+        );
+        assert_eq!(
+            sim.grid
+                .render(&glyphs::ASCII)
+                .lines()
+                .rev()
+                .nth(1)
+                .unwrap(),
+            "....oooo#."
+        );
+        assert_eq!(
+            run_until_break(&mut sim),
+            SandOutcome::Abyss { settled: 24 }
+        );
+    }
+
+    #[test]
+    fn labels_cave_coordinates() {
+        let test_input = include_str!("./test_input/day14.txt");
+        let paths = parse_or_report(separated_list0(line_ending, path), test_input);
+        let labeled = Grid::new(paths).labeled(&glyphs::ASCII);
+        assert_eq!(
+            labeled,
+            "   4    5
+   9    0
+   5    0
+0 ..........
+1 ..........
+2 ..........
+3 ..........
+4 ....#...##
+5 ....#...#.
+6 ..###...#.
+7 ........#.
+8 ........#.
+9 #########.
+"
+        );
+    }
+
+    #[test]
+    fn visualizes_the_pile() {
+        let test_input = include_str!("./test_input/day14.txt");
+        let glyphs = glyphs::current();
+        // One frame per grain, then two grains a frame for level 2's 93
+        let frames = visualize(test_input, Level::One);
+        assert_eq!(frames.len(), 25);
+        assert_eq!(
+            frames[0],
+            Grid::new(parse_paths(test_input)).labeled(&glyphs)
+        );
+        let mut sim = SandFall::new(Grid::new(parse_paths(test_input)), 500);
+        run_until_break(&mut sim);
+        assert_eq!(frames[24], sim.grid.labeled(&glyphs));
+        assert_eq!(visualize(test_input, Level::Two).len(), 48);
+    }
+
+    #[test]
+    fn grains_settle_in_order() {
+        let test_input = include_str!("./test_input/day14.txt");
+        let first = settled_positions(parse_paths(test_input), SandConfig::default())
+            .take(5)
+            .map(|pos| (pos.x, pos.y))
+            .collect_vec();
+        assert_eq!(first, [(500, 8), (499, 8), (501, 8), (500, 7), (498, 8)]);
+        let config = SandConfig {
+            floor: true,
+            ..SandConfig::default()
+        };
+        let last = settled_positions(parse_paths(test_input), config).last();
+        assert_eq!(last, Some(GridPos { x: 500, y: 0 }));
+    }
+
+    #[test]
+    fn simulations_say_why_they_stopped() {
+        let test_input = include_str!("./test_input/day14.txt");
+        assert_eq!(
+            simulate(test_input, false),
+            SandOutcome::Abyss { settled: 24 }
+        );
+        assert_eq!(
+            simulate(test_input, true),
+            SandOutcome::SourceBlocked { settled: 93 }
+        );
+        // A cup around the source fills up without a floor
+        let cup = "498,0 -> 498,2 -> 502,2 -> 502,0";
+        assert_eq!(
+            simulate(cup, false),
+            SandOutcome::SourceBlocked { settled: 4 }
+        );
+    }
+
+    #[test]
+    fn brute_force_given_example() {
+        let test_input = include_str!("./test_input/day14.txt");
+        assert_eq!(level2_brute_force(test_input), 93)
+    }
+
+    #[test]
+    fn floor_scans_agree() {
+        let test_input = include_str!("./test_input/day14.txt");
+        assert_eq!(floor_sand_count(test_input, FloorScan::Intervals), 93);
+        assert_eq!(floor_sand_count(test_input, FloorScan::Simulated), 93);
+        let sparse = "400,5 -> 400,5\n600,5 -> 600,5\n498,2 -> 502,2";
+        assert_eq!(
+            floor_sand_count(sparse, FloorScan::Intervals),
+            floor_sand_count(sparse, FloorScan::Bitset)
+        );
+        assert_eq!(
+            floor_sand_count(sparse, FloorScan::Simulated),
+            floor_sand_count(sparse, FloorScan::Bitset)
+        );
+    }
 }
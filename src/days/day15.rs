@@ -1,207 +1,412 @@
-use std::{cmp::Ordering, collections::HashSet, iter::once};
+use std::collections::{BTreeSet, HashMap};
 
 use nom::{
+    branch::alt,
     bytes::complete::tag,
     character::complete::{char, digit1, i64, line_ending},
-    combinator::{all_consuming, map, map_res},
+    combinator::{all_consuming, map, map_opt, map_res},
     multi::separated_list1,
     sequence::{pair, preceded, separated_pair},
     IResult,
 };
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{
+        day::{Day, DiffModel, Explain, Param, Part, Perturb},
+        diamond::{unrotate, Diamond, RotatedSquare},
+        infra::Level,
+        random::Rng,
+    },
+};
 
-#[derive(Debug)]
-struct Square {
-    x: i64,
-    y: i64,
-    r: u64,
-}
+type Beacon = (i64, i64);
 
-impl Square {
-    fn interval(&self, y: i64) -> Option<Interval> {
-        let dx = self.r.checked_sub(self.y.abs_diff(y))?;
-        Some(Interval {
-            start: self.x.saturating_sub_unsigned(dx),
-            end: self.x.saturating_add_unsigned(dx),
-        })
-    }
-}
+// A sensor's range reaches exactly up to its closest beacon
+type Sensor = (Diamond, Beacon);
 
 fn pos(input: &str) -> IResult<&str, (i64, i64)> {
     pair(preceded(tag("x="), i64), preceded(tag(", y="), i64))(input)
 }
 
-fn parse_line(input: &str) -> IResult<&str, (Square, (i64, i64))> {
-    map(
-        pair(
-            preceded(tag("Sensor at "), pos),
-            preceded(tag(": closest beacon is at "), pos),
-        ),
-        |((x, y), (x2, y2))| {
-            (
-                Square {
-                    x,
-                    y,
-                    r: x.abs_diff(x2) + y.abs_diff(y2),
-                },
-                (x2, y2),
-            )
-        },
-    )(input)
+fn sensor(input: &str) -> IResult<&str, (i64, i64)> {
+    preceded(alt((tag("Sensor at "), tag("sensor at "))), pos)(input)
 }
 
-fn parse_input(input: &str) -> IResult<&str, Vec<(Square, (i64, i64))>> {
-    all_consuming(separated_list1(line_ending, parse_line))(input)
+fn closest_beacon(input: &str) -> IResult<&str, (i64, i64)> {
+    preceded(
+        alt((tag("closest beacon is at "), tag("Closest beacon is at "))),
+        pos,
+    )(input)
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Interval {
-    start: i64,
-    end: i64,
+// The sensor first as in the puzzle, or the closest beacon first. A sensor
+// can sit on its own beacon, with a range of 0, but sensors and beacons at
+// opposite ends of the i64 range are further apart than a u64 holds.
+fn parse_line(input: &str) -> IResult<&str, (Diamond, Beacon)> {
+    map_opt(
+        alt((
+            separated_pair(sensor, tag(": "), closest_beacon),
+            map(
+                separated_pair(closest_beacon, tag(": "), sensor),
+                |(beacon, sensor)| (sensor, beacon),
+            ),
+        )),
+        |(sensor, beacon)| Some((Diamond::reaching(sensor, beacon)?, beacon)),
+    )(input)
 }
 
-impl Interval {
-    fn contains(&self, x: i64) -> bool {
-        (self.start..=self.end).contains(&x)
-    }
-
-    fn merge(&self, other: Self) -> Option<Self> {
-        (self.start <= other.end + 1 && other.start <= self.end + 1).then_some(Interval {
-            start: { self.start.min(other.start) },
-            end: self.end.max(other.end),
-        })
-    }
+fn parse_input(input: &str) -> IResult<&str, Vec<Sensor>> {
+    separated_list1(line_ending, parse_line)(input)
+}
 
-    fn len(&self) -> i64 {
-        self.end + 1 - self.start
-    }
-}
-
-#[derive(Debug, Default)]
-struct DisjointIntervals(Vec<Interval>);
-
-impl FromIterator<Interval> for DisjointIntervals {
-    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
-        let mut acc = Self::default();
-        acc.extend(iter);
-        acc
-    }
-}
-
-impl Extend<Interval> for DisjointIntervals {
-    fn extend<T: IntoIterator<Item = Interval>>(&mut self, iter: T) {
-        for i @ Interval { start, end } in iter {
-            let start_pos = self.search(start - 1);
-            let end_pos = self.search(end + 1);
-            let mut to_insert = i;
-            if let Some(new) = start_pos
-                .ok()
-                .and_then(|i| self.0.get(i))
-                .and_then(|start_interval| to_insert.merge(*start_interval))
-            {
-                to_insert = new
-            };
-            if let Some(new) = end_pos
-                .ok()
-                .and_then(|i| self.0.get(i))
-                .and_then(|end_interval| to_insert.merge(*end_interval))
-            {
-                to_insert = new
-            };
-            let to_remove_start = start_pos.unwrap_or_else(|i| i);
-            let to_remove_end = match end_pos {
-                Result::Ok(i) => i + 1,
-                Err(i) => i,
-            };
-            self.0
-                .splice(to_remove_start..to_remove_end, once(to_insert));
+pub fn lint(input: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut sensors: HashMap<(i64, i64), usize> = HashMap::new();
+    for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        match all_consuming(parse_line)(line) {
+            Ok((_, (Diamond { center: (x, y), .. }, _))) => {
+                if let Some(first) = sensors.insert((x, y), line_no) {
+                    findings.push(Finding::new(
+                        line_no,
+                        format!("sensor at x={x}, y={y} already listed on line {first}"),
+                    ));
+                }
+            }
+            Err(_) => findings.push(Finding::new(
+                line_no,
+                format!("not a sensor line: {line:?}"),
+            )),
         }
     }
+    findings
 }
 
-impl DisjointIntervals {
-    fn new(i: Interval) -> Self {
-        Self(vec![i])
-    }
-
-    fn search(&self, x: i64) -> Result<usize, usize> {
-        self.0.binary_search_by(|i| {
-            if x < i.start {
-                Ordering::Greater
-            } else if x > i.end {
-                Ordering::Less
-            } else {
-                Ordering::Equal
-            }
+// Shifts every sensor and beacon sideways by the same random amount. The
+// coverage of each row moves along unchanged, so level 1 keeps its answer,
+// while level 2's search area no longer fits.
+pub fn anonymize(input: &str, rng: &mut Rng) -> String {
+    let dx = rng.range(-1_000_000..=1_000_000);
+    input
+        .lines()
+        .map(|line| match all_consuming(parse_line)(line) {
+            Ok((_, (Diamond { center: (x, y), .. }, (x2, y2)))) => format!(
+                "Sensor at x={}, y={y}: closest beacon is at x={}, y={y2}",
+                x.saturating_add(dx),
+                x2.saturating_add(dx)
+            ),
+            Err(_) => line.to_string(),
         })
-    }
+        .join("\n")
 }
 
-pub fn level1(input: &str, y: i64) -> i64 {
-    let (squares, mut beacons): (Vec<_>, Vec<_>) =
-        parse_input(input).unwrap().1.into_iter().unzip();
-    let intervals: DisjointIntervals = squares
+fn row_coverage(sensors: &[Sensor], y: i64) -> u64 {
+    let (diamonds, mut beacons): (Vec<_>, Vec<_>) = sensors.iter().cloned().unzip();
+    let intervals: DisjointIntervals = diamonds
         .iter()
-        .filter_map(|square| square.interval(y))
+        .filter_map(|diamond| diamond.row(y))
         .collect();
     beacons.sort();
     let beacons_in_row = beacons
         .into_iter()
         .dedup()
-        .filter(|(x_beacon, y_beacon)| y == *y_beacon && intervals.search(*x_beacon).is_ok())
-        .count() as i64;
-    intervals.0.iter().map(|i| i.len()).sum::<i64>() - beacons_in_row
-}
-
-// This isn't correct for all inputs since the empty field could also lie on the boundary,
-// where it wouldn't need to be sandwiched between two sum = constant lines (candidate check).
-// Since that case can be treated with the method from level one and did not occur for test
-// or real input, it's left out for now
-pub fn level2(input: &str, max: i64) -> i64 {
-    let squares = parse_input(input)
-        .unwrap()
-        .1
-        .into_iter()
-        .map(|(square, _)| square)
-        .collect_vec();
-    let candidates_above = squares
+        .filter(|(x_beacon, y_beacon)| y == *y_beacon && intervals.contains(*x_beacon))
+        .count();
+    let row = Interval::new(i64::MIN, i64::MAX);
+    (intervals.covered_len_within(row) - beacons_in_row) as u64
+}
+
+pub fn level1(input: &str, y: i64) -> u64 {
+    row_coverage(&parse_or_report(parse_input, input), y)
+}
+
+// A handful of sensors in a 20x20 square, to be checked at y = 10
+pub fn random_input(rng: &mut Rng) -> String {
+    (0..1 + rng.below(6))
+        .map(|_| {
+            let [x, y, x2, y2] = [(); 4].map(|_| rng.range(0..=20));
+            format!("Sensor at x={x}, y={y}: closest beacon is at x={x2}, y={y2}")
+        })
+        .join("\n")
+}
+
+// Checks every x that any sensor could reach, for random inputs only
+pub fn level1_brute_force(input: &str, y: i64) -> i64 {
+    let sensors = parse_or_report(parse_input, input);
+    let reach = |x: i64| sensors.iter().any(|(diamond, _)| diamond.contains((x, y)));
+    let beacon = |x: i64| sensors.iter().any(|(_, beacon)| *beacon == (x, y));
+    let (min, max) = sensors
         .iter()
-        .map(|Square { x, y, r }| (x + y).saturating_add_unsigned(*r) + 1)
-        .collect::<HashSet<_>>();
-    let candidates_below = squares
+        .map(|(diamond, _)| {
+            let (x, _) = diamond.center;
+            (x - diamond.r as i64, x + diamond.r as i64)
+        })
+        .fold((i64::MAX, i64::MIN), |(min, max), (a, b)| {
+            (min.min(a), max.max(b))
+        });
+    (min..=max).filter(|x| reach(*x) && !beacon(*x)).count() as i64
+}
+
+// The distress beacon's x * 4_000_000 + y, which only fits the answer's
+// u64 for coordinates that are not negative and not absurdly large.
+pub fn tuning_frequency(x: i64, y: i64) -> anyhow::Result<u64> {
+    let x = u64::try_from(x).map_err(|_| anyhow!("x={x} is negative"))?;
+    let y = u64::try_from(y).map_err(|_| anyhow!("y={y} is negative"))?;
+    x.checked_mul(4_000_000)
+        .and_then(|scaled| scaled.checked_add(y))
+        .ok_or_else(|| anyhow!("tuning frequency of x={x}, y={y} overflows"))
+}
+
+// The distress beacon is the one cell in the search area no sensor reaches,
+// so unless it sits on the area's edge, which neither the example nor the
+// real input does, it lies on a diagonal x + y = u left free between two
+// sensors' ranges. In rotated coordinates u = x + y,
+// v = x - y each such diagonal is a row, on which the sensors reaching it
+// cover intervals of v, and the beacon sits in the gap between them.
+fn distress_beacon(sensors: &[Sensor], max: i64) -> (i64, i64) {
+    let diamonds = sensors.iter().map(|(diamond, _)| diamond).collect_vec();
+    let diagonals = crate::time!(
+        "day15 candidate diagonals",
+        candidate_diagonals(&diamonds, max)
+    );
+    let _timer = ScopedTimer::new("day15 diagonal scan");
+    let squares = diamonds
         .iter()
-        .map(|Square { x, y, r }| (x + y).saturating_sub_unsigned(*r) - 1)
-        .collect::<HashSet<_>>();
-    let candidates = candidates_above
-        .intersection(&candidates_below)
-        .filter(|c| (0..2 * max).contains(c))
+        .map(|diamond| diamond.to_rotated_square())
         .collect_vec();
-    for sum in candidates.into_iter() {
-        let intervals = squares
+    for u in diagonals {
+        if let Some(point) = diagonal_gaps(&squares, u, max).find_beacon(u) {
+            return point;
+        }
+    }
+    panic!("Nothing found, are you sure there is a unique solution?")
+}
+
+// The diagonals u = x + y in the search area that run in a one cell gap
+// between two sensors' ranges
+fn candidate_diagonals(diamonds: &[&Diamond], max: i64) -> BTreeSet<i64> {
+    diamonds
+        .iter()
+        .tuple_combinations()
+        .filter_map(|(a, b)| a.gap_diagonal(b))
+        .filter(|u| (0..2 * max).contains(u))
+        .collect()
+}
+
+// What the sensors cover of a diagonal within the search area, in v = x - y
+struct DiagonalCover {
+    covered: DisjointIntervals,
+    gaps: DisjointIntervals,
+}
+
+impl DiagonalCover {
+    // Only every other v of a diagonal is a cell
+    fn find_beacon(&self, u: i64) -> Option<(i64, i64)> {
+        self.gaps
             .iter()
-            .filter(|s| sum.abs_diff(s.x + s.y) <= s.r)
-            .map(|s| {
-                let v = s.x - s.y;
-                let cutoff = 2 * (2 * max - sum).min(*sum);
-                Interval {
-                    start: v.saturating_sub_unsigned(s.r).max(-cutoff),
-                    end: v.saturating_add_unsigned(s.r).min(cutoff),
-                }
+            .flat_map(|gap| gap.start..=gap.end)
+            .find_map(|v| unrotate(u, v))
+    }
+}
+
+fn diagonal_gaps(squares: &[RotatedSquare], u: i64, max: i64) -> DiagonalCover {
+    // Where the diagonal crosses the search area
+    let reach = (2 * max - u).min(u);
+    let area = Interval::new(-reach, reach);
+    let covered: DisjointIntervals = squares
+        .iter()
+        .filter(|square| square.u.contains(u))
+        .filter_map(|square| square.v.intersection(&area))
+        .collect();
+    let gaps = DisjointIntervals::from_iter([area]).difference(&covered);
+    DiagonalCover { covered, gaps }
+}
+
+fn distress_frequency(sensors: &[Sensor], max: i64) -> u64 {
+    let (x, y) = distress_beacon(sensors, max);
+    tuning_frequency(x, y).unwrap()
+}
+
+pub fn level2(input: &str, max: i64) -> u64 {
+    distress_frequency(&parse_or_report(parse_input, input), max)
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Sensor>;
+
+    fn parse(input: &str) -> Vec<Sensor> {
+        parse_or_report(parse_input, input)
+    }
+
+    fn level1(sensors: &Vec<Sensor>) -> String {
+        row_coverage(sensors, 2_000_000).to_string()
+    }
+
+    fn level2(sensors: &Vec<Sensor>) -> String {
+        distress_frequency(sensors, 4_000_000).to_string()
+    }
+}
+
+impl Explain for Solver {
+    fn explain(input: &str, level: Level) -> Vec<String> {
+        explain(input, level, 2_000_000, 4_000_000)
+    }
+}
+
+// Sensors keyed by where they are, so a moved sensor shows as one removed
+// and one added
+impl DiffModel for Solver {
+    fn parts(input: &str) -> anyhow::Result<Vec<Part>> {
+        let sensors = parse_complete(parse_input, input)?;
+        Ok(sensors
+            .iter()
+            .map(|(diamond, (x, y))| Part {
+                key: format!("sensor at x={}, y={}", diamond.center.0, diamond.center.1),
+                fields: vec![
+                    ("beacon", format!("x={x}, y={y}")),
+                    ("range", diamond.r.to_string()),
+                ],
             })
-            .collect::<DisjointIntervals>();
-        if let Some(Interval { start: diff, .. }) = intervals.0.get(1) {
-            let x = (sum + diff - 1) / 2;
-            let y = (sum - diff + 1) / 2;
-            return x * 4_000_000 + y;
+            .collect())
+    }
+}
+
+// Moving sensors together with their beacons keeps the ranges, so only `r`
+// changes which cells the sensors reach
+impl Perturb for Solver {
+    const PARAMS: &'static [Param<Vec<Sensor>>] = &[
+        Param {
+            name: "r",
+            about: "every sensor's range, past its beacon",
+            apply: |sensors, delta| {
+                for (diamond, _) in sensors {
+                    diamond.r = diamond.r.saturating_add_signed(delta);
+                }
+            },
+        },
+        Param {
+            name: "x",
+            about: "every sensor and beacon, to the right",
+            apply: |sensors, delta| {
+                for (diamond, beacon) in sensors {
+                    diamond.center.0 = diamond.center.0.saturating_add(delta);
+                    beacon.0 = beacon.0.saturating_add(delta);
+                }
+            },
+        },
+        Param {
+            name: "y",
+            about: "every sensor and beacon, down",
+            apply: |sensors, delta| {
+                for (diamond, beacon) in sensors {
+                    diamond.center.1 = diamond.center.1.saturating_add(delta);
+                    beacon.1 = beacon.1.saturating_add(delta);
+                }
+            },
+        },
+    ];
+}
+
+// The steps of level 1 on row `y`, or of level 2 in the square up to `max`
+pub fn explain(input: &str, level: Level, y: i64, max: i64) -> Vec<String> {
+    let sensors = parse_or_report(parse_input, input);
+    let intervals = |intervals: &DisjointIntervals| intervals.iter().join(", ");
+    let mut steps = Vec::new();
+    match level {
+        Level::One => {
+            let rows: DisjointIntervals = sensors
+                .iter()
+                .filter_map(|(diamond, _)| diamond.row(y))
+                .collect();
+            let reaching = sensors
+                .iter()
+                .filter(|(diamond, _)| diamond.row(y).is_some())
+                .count();
+            steps.push(format!(
+                "{reaching} of the {} sensors reach row y={y}",
+                sensors.len()
+            ));
+            steps.push(format!("Their ranges merge into {}", intervals(&rows)));
+            let covered = rows.covered_len_within(Interval::new(i64::MIN, i64::MAX));
+            let answer = row_coverage(&sensors, y);
+            steps.push(format!(
+                "{covered} cells are covered, {} of them by a beacon, leaving {answer}, the answer",
+                covered as u64 - answer
+            ));
+        }
+        Level::Two => {
+            let diamonds = sensors.iter().map(|(diamond, _)| diamond).collect_vec();
+            let diagonals = candidate_diagonals(&diamonds, max);
+            steps.push(format!(
+                "{} diagonals x + y = u run in a one cell gap between two sensors: u = {}",
+                diagonals.len(),
+                diagonals.iter().join(", ")
+            ));
+            let squares = diamonds
+                .iter()
+                .map(|diamond| diamond.to_rotated_square())
+                .collect_vec();
+            for u in diagonals {
+                let cover = diagonal_gaps(&squares, u, max);
+                steps.push(format!(
+                    "On u = {u} the sensors cover x - y in {}",
+                    intervals(&cover.covered)
+                ));
+                if let Some((x, y)) = cover.find_beacon(u) {
+                    steps.push(format!(
+                        "That leaves x - y = {} free, the distress beacon at x={x}, y={y}",
+                        x - y
+                    ));
+                    steps.push(format!(
+                        "Its tuning frequency is {x} * 4000000 + {y} = {}, the answer",
+                        tuning_frequency(x, y).unwrap()
+                    ));
+                    break;
+                }
+                steps.push("Nothing is left free there".to_string());
+            }
         }
     }
-    panic!("Nothing found, are you sure there is a unique solution?")
+    steps
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::day;
+
+    #[test]
+    fn perturbing_saturates_at_the_bounds() {
+        let test_input = include_str!("./test_input/day15.txt");
+        let mut sensors = Solver::parse(test_input);
+        let x = Solver::PARAMS
+            .iter()
+            .find(|param| param.name == "x")
+            .unwrap();
+        // Twice, past the bound even for the example's negative coordinates
+        (x.apply)(&mut sensors, i64::MAX);
+        (x.apply)(&mut sensors, i64::MAX);
+        assert!(sensors
+            .iter()
+            .all(|(diamond, beacon)| diamond.center.0 == i64::MAX && beacon.0 == i64::MAX));
+        for name in ["x", "y"] {
+            for delta in [i64::MIN, i64::MAX] {
+                day::perturbed::<Solver>(test_input, name, delta, Level::One).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn anonymized_input_keeps_row_coverage() {
+        let test_input = include_str!("./test_input/day15.txt");
+        let anonymous = anonymize(test_input, &mut Rng::new(15));
+        assert_ne!(anonymous, test_input.trim_end());
+        assert_eq!(level1(&anonymous, 10), level1(test_input, 10));
+        assert_eq!(level1(&anonymous, 11), level1(test_input, 11));
+    }
 
     #[test]
     fn level1_given_example() {
@@ -209,9 +414,144 @@ mod test {
         assert_eq!(level1(test_input, 10), 26)
     }
 
+    #[test]
+    fn explains_the_example() {
+        let test_input = include_str!("./test_input/day15.txt");
+        let steps = explain(test_input, Level::One, 10, 20);
+        assert_eq!(
+            steps.last().unwrap(),
+            "27 cells are covered, 1 of them by a beacon, leaving 26, the answer"
+        );
+        let steps = explain(test_input, Level::Two, 10, 20);
+        assert_eq!(
+            steps[steps.len() - 2],
+            "That leaves x - y = 3 free, the distress beacon at x=14, y=11"
+        );
+        assert!(steps.last().unwrap().contains("= 56000011"));
+    }
+
+    #[test]
+    fn brute_force_given_example() {
+        let test_input = include_str!("./test_input/day15.txt");
+        assert_eq!(level1_brute_force(test_input, 10), 26)
+    }
+
     #[test]
     fn level2_given_example() {
         let test_input = include_str!("./test_input/day15.txt");
         assert_eq!(level2(test_input, 20), 56000011)
     }
+
+    #[test]
+    fn tuning_frequency_is_checked() {
+        assert_eq!(tuning_frequency(14, 11).unwrap(), 56000011);
+        assert_eq!(
+            tuning_frequency(4_000_000, 4_000_000).unwrap(),
+            16_000_004_000_000
+        );
+        let max_x = (u64::MAX / 4_000_000) as i64;
+        assert!(tuning_frequency(max_x, 0).is_ok());
+        assert_eq!(
+            tuning_frequency(max_x + 1, 0).unwrap_err().to_string(),
+            format!("tuning frequency of x={}, y=0 overflows", max_x + 1)
+        );
+        assert!(tuning_frequency(max_x, i64::MAX).is_err());
+        assert_eq!(
+            tuning_frequency(-1, 3).unwrap_err().to_string(),
+            "x=-1 is negative"
+        );
+        assert_eq!(
+            tuning_frequency(3, -1).unwrap_err().to_string(),
+            "y=-1 is negative"
+        );
+    }
+
+    #[test]
+    fn accepts_beacon_first_lines() {
+        let sensor_first = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15";
+        let beacon_first = "Closest beacon is at x=-2, y=15: sensor at x=2, y=18";
+        let (_, (diamond, beacon)) = all_consuming(parse_line)(beacon_first).unwrap();
+        assert_eq!((diamond, beacon), (Diamond::new((2, 18), 7), (-2, 15)));
+        assert_eq!(level1(beacon_first, 16), level1(sensor_first, 16));
+        assert_eq!(lint(beacon_first), vec![]);
+    }
+
+    #[test]
+    fn sensor_on_its_own_beacon() {
+        let input = "Sensor at x=5, y=5: closest beacon is at x=5, y=5";
+        assert_eq!(level1(input, 5), 0);
+        assert_eq!(level1(input, 4), 0);
+        let test_input = include_str!("./test_input/day15.txt");
+        let input = format!(
+            "{}\nSensor at x=0, y=0: closest beacon is at x=0, y=0",
+            test_input.trim_end()
+        );
+        assert_eq!(level1(&input, 10), 26);
+        assert_eq!(level2(&input, 20), 56000011);
+    }
+
+    #[test]
+    fn coordinates_near_i64_bounds() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        let input = format!(
+            "Sensor at x={}, y=0: closest beacon is at x={max}, y=0\n\
+             Sensor at x={}, y=0: closest beacon is at x={min}, y=0",
+            max - 1,
+            min + 1
+        );
+        assert_eq!(level1(&input, 0), 4);
+        assert_eq!(level1(&input, 1), 2);
+        let test_input = include_str!("./test_input/day15.txt");
+        let input = format!("{}\n{input}", test_input.trim_end());
+        assert_eq!(level2(&input, 20), 56000011);
+        let input = format!("Sensor at x={min}, y={min}: closest beacon is at x={max}, y={max}");
+        assert_eq!(
+            lint(&input)
+                .into_iter()
+                .map(|finding| finding.to_string())
+                .collect_vec(),
+            vec![format!("line 1: not a sensor line: {input:?}")]
+        );
+    }
+
+    #[test]
+    fn lint_given_example() {
+        let test_input = include_str!("./test_input/day15.txt");
+        assert_eq!(lint(test_input), vec![])
+    }
+
+    #[test]
+    fn lint_finds_duplicate_sensors() {
+        let test_input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15\n\
+            Sensor at x=9, y=16: closest beacon is at x=10, y=16\n\
+            Sensor at x=2, y=18: closest beacon is at x=-2, y=15";
+        assert_eq!(
+            lint(test_input)
+                .into_iter()
+                .map(|finding| finding.to_string())
+                .collect_vec(),
+            vec!["line 3: sensor at x=2, y=18 already listed on line 1"]
+        )
+    }
+
+    #[test]
+    fn diffs_sensors() {
+        let test_input = include_str!("./test_input/day15.txt");
+        let changed = test_input
+            .replace(
+                "closest beacon is at x=-2, y=15",
+                "closest beacon is at x=-2, y=16",
+            )
+            .replace("Sensor at x=9, y=16", "Sensor at x=9, y=17");
+        assert_eq!(
+            Solver::diff(test_input, &changed).unwrap(),
+            [
+                "~ sensor at x=2, y=18 beacon: x=-2, y=15 -> x=-2, y=16",
+                "~ sensor at x=2, y=18 range: 7 -> 6",
+                "- sensor at x=9, y=16: beacon x=10, y=16, range 1",
+                "+ sensor at x=9, y=17: beacon x=10, y=16, range 2"
+            ]
+        );
+        assert!(Solver::diff(test_input, "Sensor at x=1").is_err());
+    }
 }
@@ -1,38 +1,217 @@
-use itertools::Itertools;
+use nom::{
+    branch::alt,
+    character::complete::{char, line_ending},
+    combinator::{cut, map, opt, value},
+    multi::separated_list1,
+    sequence::{pair, separated_pair, terminated},
+    IResult,
+};
 
-use crate::util::*;
+use crate::{prelude::*, util::day::Day};
 
-fn parse_line_score_level_1(line: &str) -> i64 {
-    let shapes = line
-        .split(' ')
-        .map(|w| w.bytes().next().unwrap() as i64)
-        .collect_vec();
-    let x_ascii: i64 = "X".bytes().next().unwrap().into();
-    let a_ascii: i64 = "A".bytes().next().unwrap().into();
-    let shape_score = 1 + shapes[1] - x_ascii;
-    let win_score = 3 * (shapes[1] - shapes[0] - 2 - x_ascii + a_ascii).rem_euclid(3);
-    shape_score + win_score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Rock,
+    Paper,
+    Scissors,
 }
 
-fn parse_line_score_level_2(line: &str) -> i64 {
-    let shapes = line
-        .split(' ')
-        .map(|w| w.bytes().next().unwrap() as i64)
-        .collect_vec();
-    let x_ascii: i64 = "X".bytes().next().unwrap().into();
-    let a_ascii: i64 = "A".bytes().next().unwrap().into();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+// The guide's second column, which level 1 reads as the response and
+// level 2 as the outcome to aim for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    X,
+    Y,
+    Z,
+}
+
+impl Column {
+    pub fn shape(self) -> Shape {
+        Shape::ALL[self as usize]
+    }
+
+    pub fn outcome(self) -> Outcome {
+        [Outcome::Loss, Outcome::Draw, Outcome::Win][self as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    pub opponent: Shape,
+    pub column: Column,
+}
+
+impl Shape {
+    const ALL: [Shape; 3] = [Shape::Rock, Shape::Paper, Shape::Scissors];
+
+    fn opponent_letter(self) -> char {
+        ['A', 'B', 'C'][self as usize]
+    }
+
+    fn response_letter(self) -> char {
+        ['X', 'Y', 'Z'][self as usize]
+    }
+
+    pub fn score(self) -> i64 {
+        self as i64 + 1
+    }
+
+    // Each shape beats the one before it, wrapping around
+    pub fn against(self, opponent: Shape) -> Outcome {
+        match (self as i64 - opponent as i64).rem_euclid(3) {
+            0 => Outcome::Draw,
+            1 => Outcome::Win,
+            _ => Outcome::Loss,
+        }
+    }
+
+    // The response to `self` that ends in `outcome`
+    pub fn for_outcome(self, outcome: Outcome) -> Shape {
+        *Shape::ALL
+            .iter()
+            .find(|shape| shape.against(self) == outcome)
+            .unwrap()
+    }
+}
+
+impl Outcome {
+    pub fn score(self) -> i64 {
+        3 * self as i64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundResult {
+    pub opponent: Shape,
+    pub response: Shape,
+    pub outcome: Outcome,
+    pub score: i64,
+}
+
+impl RoundResult {
+    fn new(opponent: Shape, response: Shape) -> Self {
+        let outcome = response.against(opponent);
+        Self {
+            opponent,
+            response,
+            outcome,
+            score: response.score() + outcome.score(),
+        }
+    }
+}
+
+fn opponent(input: &str) -> IResult<&str, Shape> {
+    alt((
+        value(Shape::Rock, char('A')),
+        value(Shape::Paper, char('B')),
+        value(Shape::Scissors, char('C')),
+    ))(input)
+}
+
+fn column(input: &str) -> IResult<&str, Column> {
+    alt((
+        value(Column::X, char('X')),
+        value(Column::Y, char('Y')),
+        value(Column::Z, char('Z')),
+    ))(input)
+}
+
+fn guide(input: &str) -> IResult<&str, Vec<Round>> {
+    separated_list1(
+        line_ending,
+        cut(map(
+            separated_pair(opponent, char(' '), column),
+            |(opponent, column)| Round { opponent, column },
+        )),
+    )(input)
+}
+
+pub fn parse_guide(input: &str) -> Result<Vec<Round>, ParseError> {
+    parse_complete(guide, input)
+}
+
+// The second column as the response
+fn play_level1(rounds: &[Round]) -> Vec<RoundResult> {
+    rounds
+        .iter()
+        .map(|round| RoundResult::new(round.opponent, round.column.shape()))
+        .collect()
+}
+
+// The second column as the outcome, playing whatever gets it
+fn play_level2(rounds: &[Round]) -> Vec<RoundResult> {
+    rounds
+        .iter()
+        .map(|round| {
+            let response = round.opponent.for_outcome(round.column.outcome());
+            RoundResult::new(round.opponent, response)
+        })
+        .collect()
+}
 
-    let shape_score = 1 + (shapes[0] + shapes[1] - x_ascii - a_ascii - 1).rem_euclid(3);
-    let win_score = 3 * (shapes[1] - x_ascii);
-    shape_score + win_score
+fn total(results: &[RoundResult]) -> i64 {
+    results.iter().map(|result| result.score).sum()
+}
+
+// Plays a guide read the level 1 way, with the second column the response
+pub fn simulate(input: &str) -> Result<Vec<RoundResult>, ParseError> {
+    Ok(play_level1(&parse_guide(input)?))
+}
+
+// A guide winning every round against the opponent's moves, given one
+// per line or as the first column of a guide
+pub fn best_response_guide(opponent_moves: &str) -> Result<String, ParseError> {
+    let opponents = parse_complete(
+        separated_list1(
+            line_ending,
+            cut(terminated(opponent, opt(pair(char(' '), column)))),
+        ),
+        opponent_moves,
+    )?;
+    Ok(opponents
+        .into_iter()
+        .map(|opponent| {
+            let response = opponent.for_outcome(Outcome::Win);
+            format!(
+                "{} {}",
+                opponent.opponent_letter(),
+                response.response_letter()
+            )
+        })
+        .join("\n"))
 }
 
 pub fn level1(input: &str) -> i64 {
-    input.lines().map(parse_line_score_level_1).sum()
+    total(&play_level1(&parse_or_report(guide, input)))
 }
 
 pub fn level2(input: &str) -> i64 {
-    input.lines().map(parse_line_score_level_2).sum()
+    total(&play_level2(&parse_or_report(guide, input)))
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Round>;
+
+    fn parse(input: &str) -> Vec<Round> {
+        parse_or_report(guide, input)
+    }
+
+    fn level1(rounds: &Vec<Round>) -> String {
+        total(&play_level1(rounds)).to_string()
+    }
+
+    fn level2(rounds: &Vec<Round>) -> String {
+        total(&play_level2(rounds)).to_string()
+    }
 }
 
 #[cfg(test)]
@@ -50,4 +229,40 @@ mod test {
         let test_input = include_str!("./test_input/day2.txt");
         assert_eq!(level2(test_input), 12)
     }
+
+    #[test]
+    fn simulated_rounds() {
+        let test_input = include_str!("./test_input/day2.txt");
+        let rounds = simulate(test_input).unwrap();
+        let outcomes = rounds.iter().map(|round| round.outcome).collect_vec();
+        assert_eq!(outcomes, [Outcome::Win, Outcome::Loss, Outcome::Draw]);
+        let total: i64 = rounds.iter().map(|round| round.score).sum();
+        assert_eq!(total, level1(test_input));
+    }
+
+    #[test]
+    fn always_winning_guide() {
+        let test_input = include_str!("./test_input/day2.txt");
+        let guide = best_response_guide(test_input).unwrap();
+        assert_eq!(guide, "A Y\nB Z\nC X");
+        assert_eq!(best_response_guide("A\nB\nC").unwrap(), guide);
+        let rounds = simulate(&guide).unwrap();
+        assert!(rounds.iter().all(|round| round.outcome == Outcome::Win));
+        assert_eq!(level1(&guide), 24);
+        for opponent in Shape::ALL {
+            for outcome in [Outcome::Loss, Outcome::Draw, Outcome::Win] {
+                assert_eq!(opponent.for_outcome(outcome).against(opponent), outcome);
+            }
+        }
+    }
+
+    #[test]
+    fn bad_lines_are_errors() {
+        let error = simulate("A Y\n\nC Z").unwrap_err();
+        assert_eq!(error.line_col(), (2, 1));
+        let error = simulate("A Y\nB W").unwrap_err();
+        assert_eq!(error.line_col(), (2, 3));
+        let error = best_response_guide("A\nD").unwrap_err();
+        assert_eq!(error.line_col(), (2, 1));
+    }
 }
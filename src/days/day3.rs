@@ -1,46 +1,131 @@
-use std::collections::HashSet;
+use nom::{
+    character::complete::{alpha1, line_ending},
+    combinator::{cut, map},
+    multi::separated_list1,
+    IResult,
+};
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{counter::Counter, day::Day},
+};
 
 fn priority(c: char) -> i64 {
-    if ('a'..='z').contains(&c) {
+    if c.is_ascii_lowercase() {
         ascii_code(c) - LOWER_A_ASCII + 1
-    } else if ('A'..='Z').contains(&c) {
+    } else if c.is_ascii_uppercase() {
         ascii_code(c) - UPPER_A_ASCII + 27
     } else {
         panic!("Can only handle non-extended alphabet characters")
     }
 }
 
-fn parse_line(line: &str) -> i64 {
-    let (first, second) = line.split_at(line.len() / 2);
-    let first_set: HashSet<_> = first.chars().collect();
-    let second_set: HashSet<_> = second.chars().collect();
-    priority(*first_set.intersection(&second_set).next().unwrap())
+// The item in every one of `parts`, which is counted once per part when
+// each part counts its items once. An error unless there is exactly one.
+fn common_item(parts: &[&str]) -> anyhow::Result<char> {
+    let counter: Counter<char> = parts
+        .iter()
+        .flat_map(|part| part.chars().unique())
+        .collect();
+    let common = counter
+        .iter()
+        .filter(|&(_, count)| count == parts.len())
+        .map(|(item, _)| *item)
+        .sorted()
+        .collect_vec();
+    match common[..] {
+        [item] => Ok(item),
+        [] => bail!("No item in all of {}", parts.join(", ")),
+        _ => bail!(
+            "Items {} are all in each of {}",
+            common.iter().join(", "),
+            parts.join(", ")
+        ),
+    }
+}
+
+// One rucksack of letters per line, so that every item has a priority
+fn rucksacks(input: &str) -> IResult<&str, Vec<String>> {
+    separated_list1(line_ending, cut(map(alpha1, str::to_string)))(input)
+}
+
+pub fn parse_rucksacks(input: &str) -> Result<Vec<String>, ParseError> {
+    parse_complete(rucksacks, input)
+}
+
+// The item in both halves of each rucksack, by line number from 1, with its
+// priority
+pub fn duplicates(rucksacks: &[String]) -> anyhow::Result<Vec<(usize, char, i64)>> {
+    rucksacks
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let (first, second) = line.split_at(line.len() / 2);
+            let item = common_item(&[first, second]).with_context(|| format!("line {}", i + 1))?;
+            Ok((i + 1, item, priority(item)))
+        })
+        .collect()
+}
+
+// The item each group of `group_size` rucksacks has in common, by group
+// number from 1
+pub fn badges(rucksacks: &[String], group_size: usize) -> anyhow::Result<Vec<(usize, char)>> {
+    rucksacks
+        .chunks(group_size)
+        .enumerate()
+        .map(|(i, group)| {
+            let group = group.iter().map(String::as_str).collect_vec();
+            if group.len() < group_size {
+                bail!(
+                    "group {} has {} rucksacks instead of {group_size}",
+                    i + 1,
+                    group.len()
+                );
+            }
+            let badge = common_item(&group).with_context(|| format!("group {}", i + 1))?;
+            Ok((i + 1, badge))
+        })
+        .collect()
 }
 
-fn parse_line_group<'a>(lines: impl Iterator<Item = &'a str>) -> i64 {
-    let char_in_all = lines
-        .map(|line| line.chars().collect::<HashSet<_>>())
-        .reduce(|a, b| a.intersection(&b).copied().collect())
-        .unwrap()
+fn duplicate_priorities(rucksacks: &[String]) -> i64 {
+    or_report(duplicates(rucksacks))
         .into_iter()
-        .next()
-        .unwrap();
-    priority(char_in_all)
+        .map(|(_, _, priority)| priority)
+        .sum()
+}
+
+fn badge_priorities(rucksacks: &[String]) -> i64 {
+    or_report(badges(rucksacks, 3))
+        .into_iter()
+        .map(|(_, badge)| priority(badge))
+        .sum()
 }
 
 pub fn level1(input: &str) -> i64 {
-    input.lines().map(parse_line).sum()
+    duplicate_priorities(&parse_or_report(rucksacks, input))
 }
 
 pub fn level2(input: &str) -> i64 {
-    input
-        .lines()
-        .chunks(3)
-        .into_iter()
-        .map(parse_line_group)
-        .sum()
+    badge_priorities(&parse_or_report(rucksacks, input))
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<String>;
+
+    fn parse(input: &str) -> Vec<String> {
+        parse_or_report(rucksacks, input)
+    }
+
+    fn level1(rucksacks: &Vec<String>) -> String {
+        duplicate_priorities(rucksacks).to_string()
+    }
+
+    fn level2(rucksacks: &Vec<String>) -> String {
+        badge_priorities(rucksacks).to_string()
+    }
 }
 
 #[cfg(test)]
@@ -58,4 +143,45 @@ mod test {
         let test_input = include_str!("./test_input/day3.txt");
         assert_eq!(level2(test_input), 70)
     }
+
+    #[test]
+    fn reports_the_items() {
+        let test_input = include_str!("./test_input/day3.txt");
+        let rucksacks = parse_rucksacks(test_input).unwrap();
+        let found = duplicates(&rucksacks).unwrap();
+        assert_eq!(found[..2], [(1, 'p', 16), (2, 'L', 38)]);
+        assert_eq!(badges(&rucksacks, 3).unwrap(), [(1, 'r'), (2, 'Z')]);
+    }
+
+    #[test]
+    fn rucksacks_hold_letters() {
+        let error = parse_rucksacks(
+            "abAB
+ab1B
+",
+        )
+        .unwrap_err();
+        assert_eq!(error.line_col(), (2, 3));
+        let error = parse_rucksacks(
+            "abAB
+
+abAB",
+        )
+        .unwrap_err();
+        assert_eq!(error.line_col(), (2, 1));
+    }
+
+    #[test]
+    fn needs_exactly_one_common_item() {
+        let parse = |input| parse_rucksacks(input).unwrap();
+        let error = duplicates(&parse("abcd\nabab")).unwrap_err();
+        assert_eq!(format!("{error:#}"), "line 1: No item in all of ab, cd");
+        let error = badges(&parse("abc\nabd\nabe"), 3).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "group 1: Items a, b are all in each of abc, abd, abe"
+        );
+        let error = badges(&parse("abc\nade\nafg\nab"), 3).unwrap_err();
+        assert_eq!(format!("{error:#}"), "group 2 has 1 rucksacks instead of 3");
+    }
 }
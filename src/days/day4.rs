@@ -1,10 +1,32 @@
-use std::ops::{Range, RangeInclusive};
+use std::ops::RangeInclusive;
 
-use crate::util::prelude::*;
+use nom::{
+    character::complete::{char, i64, line_ending},
+    combinator::{cut, map},
+    multi::separated_list1,
+    sequence::separated_pair,
+    IResult,
+};
 
-fn parse_range(input: &str) -> RangeInclusive<i64> {
-    let numbers = input.split('-').map(|x| x.parse().unwrap()).collect_vec();
-    numbers[0]..=numbers[1]
+use crate::{prelude::*, util::day::Day};
+
+type Pair = (RangeInclusive<i64>, RangeInclusive<i64>);
+
+// Ranges given backwards like 7-3 mean the same sections as 3-7
+fn range(input: &str) -> IResult<&str, RangeInclusive<i64>> {
+    map(separated_pair(i64, char('-'), i64), |(a, b)| {
+        a.min(b)..=a.max(b)
+    })(input)
+}
+
+// Cutting keeps the error at the offending character instead of
+// backtracking to the start of the line.
+fn parse_input(input: &str) -> IResult<&str, Vec<Pair>> {
+    separated_list1(line_ending, cut(separated_pair(range, char(','), range)))(input)
+}
+
+pub fn parse(input: &str) -> Result<Vec<Pair>, ParseError> {
+    parse_complete(parse_input, input)
 }
 
 fn range_contains<T: PartialOrd>(a: &RangeInclusive<T>, b: &RangeInclusive<T>) -> bool {
@@ -15,32 +37,47 @@ fn overlaps<T: PartialOrd>(a: &RangeInclusive<T>, b: &RangeInclusive<T>) -> bool
     a.contains(b.start()) || a.contains(b.end())
 }
 
-fn line_one_contains_other(line: &str) -> bool {
-    let chunks = line.split(',').map(parse_range).collect_vec();
-    range_contains(&chunks[0], &chunks[1]) || range_contains(&chunks[1], &chunks[0])
+fn one_contains_other((a, b): &Pair) -> bool {
+    range_contains(a, b) || range_contains(b, a)
 }
 
-fn line_overlaps(line: &str) -> bool {
-    let chunks = line.split(',').map(parse_range).collect_vec();
-    overlaps(&chunks[0], &chunks[1]) || range_contains(&chunks[1], &chunks[0])
+fn pair_overlaps((a, b): &Pair) -> bool {
+    overlaps(a, b) || range_contains(b, a)
 }
 
-pub fn level1(input: &str) -> i64 {
-    input
-        .lines()
-        .filter(|s| line_one_contains_other(s))
+fn count_pairs(pairs: &[Pair], condition: fn(&Pair) -> bool) -> i64 {
+    pairs
+        .iter()
+        .filter(|pair| condition(pair))
         .count()
         .try_into()
         .unwrap()
 }
 
+pub fn level1(input: &str) -> i64 {
+    count_pairs(&parse_or_report(parse_input, input), one_contains_other)
+}
+
 pub fn level2(input: &str) -> i64 {
-    input
-        .lines()
-        .filter(|s| line_overlaps(s))
-        .count()
-        .try_into()
-        .unwrap()
+    count_pairs(&parse_or_report(parse_input, input), pair_overlaps)
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Pair>;
+
+    fn parse(input: &str) -> Vec<Pair> {
+        parse_or_report(parse_input, input)
+    }
+
+    fn level1(pairs: &Vec<Pair>) -> String {
+        count_pairs(pairs, one_contains_other).to_string()
+    }
+
+    fn level2(pairs: &Vec<Pair>) -> String {
+        count_pairs(pairs, pair_overlaps).to_string()
+    }
 }
 
 #[cfg(test)]
@@ -58,4 +95,17 @@ mod test {
         let test_input = include_str!("./test_input/day4.txt");
         assert_eq!(level2(test_input), 4)
     }
+
+    #[test]
+    fn reversed_ranges_are_normalized() {
+        assert_eq!(parse("7-3,4-4\n").unwrap(), vec![(3..=7, 4..=4)]);
+        assert_eq!(level1("7-3,4-4"), 1);
+        assert_eq!(level2("9-8,1-8"), 1);
+    }
+
+    #[test]
+    fn malformed_line_reports_position() {
+        let error = parse("2-4,6-8\n2-3,4_5\n5-7,7-9").unwrap_err();
+        assert_eq!(error.line_col(), (2, 6));
+    }
 }
@@ -1,17 +1,17 @@
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::{
-        complete::{char, newline, satisfy, u32},
-        is_alphabetic,
-    },
-    combinator::{map, value},
-    multi::{count, many1, separated_list1},
-    sequence::{delimited, pair, preceded},
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, newline, satisfy, space0, space1, u32},
+    combinator::{map, map_opt, value},
+    multi::{count, separated_list1},
+    sequence::{delimited, preceded, tuple},
     IResult, ToUsize,
 };
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{day::Day, span::Spanned},
+};
 
 #[derive(Debug)]
 struct Move {
@@ -19,8 +19,10 @@ struct Move {
     source: usize,
     target: usize,
 }
+// The drawing's rows of crates, top first, and the moves after it
 #[derive(Debug)]
-struct CrateMoves {
+pub struct CrateMoves {
+    stack_count: usize,
     crate_lines: Vec<Vec<Option<char>>>,
     moves: Vec<Move>,
 }
@@ -37,50 +39,83 @@ fn parse_crate_chunk(chunk: &str) -> IResult<&str, Option<char>> {
 fn parse_crate_line(line: &str) -> IResult<&str, Vec<Option<char>>> {
     separated_list1(char(' '), parse_crate_chunk)(line)
 }
-fn parse_digit_line(line: &str) -> IResult<&str, ()> {
-    value(
-        (),
-        delimited(
-            char(' '),
-            separated_list1(tag("   "), satisfy(|c| c.is_ascii_digit())),
-            char(' '),
-        ),
+// Stack labels are separated by at least one space, so stacks past 9 with
+// wider labels like "10" work too.
+fn parse_label_line(line: &str) -> IResult<&str, Vec<&str>> {
+    delimited(
+        space0,
+        separated_list1(space1, take_while1(|c: char| !c.is_whitespace())),
+        space0,
     )(line)
 }
 fn parse_usize(input: &str) -> IResult<&str, usize> {
     map(u32, |x| x.to_usize())(input)
 }
-fn parse_crate_move_line(line: &str) -> IResult<&str, Move> {
-    let (line, _) = tag("move ")(line)?;
-    let (line, count) = parse_usize(line)?;
-    let (line, _) = tag(" from ")(line)?;
-    let (line, source) = parse_usize(line)?;
-    let (line, _) = tag(" to ")(line)?;
-    let (output, target) = parse_usize(line)?;
-    IResult::Ok((
-        output,
-        Move {
+fn parse_stack<'a, 'b>(
+    labels: &'b [&'a str],
+) -> impl FnMut(&'a str) -> IResult<&'a str, usize> + 'b {
+    map_opt(take_while1(|c: char| !c.is_whitespace()), |label| {
+        labels.iter().position(|l| *l == label)
+    })
+}
+fn parse_crate_move_line<'a, 'b>(
+    labels: &'b [&'a str],
+) -> impl FnMut(&'a str) -> IResult<&'a str, Move> + 'b {
+    map(
+        tuple((
+            preceded(tag("move "), parse_usize),
+            preceded(tag(" from "), parse_stack(labels)),
+            preceded(tag(" to "), parse_stack(labels)),
+        )),
+        |(count, source, target)| Move {
             count,
-            source: source - 1,
-            target: target - 1,
+            source,
+            target,
         },
-    ))
+    )
 }
 
 fn parse_input(input: &str) -> IResult<&str, CrateMoves> {
     let (input, crate_lines) = separated_list1(newline, parse_crate_line)(input)?;
     let (input, _) = newline(input)?;
-    let (input, _) = parse_digit_line(input)?;
+    let (input, labels) = parse_label_line(input)?;
     let (input, _) = count(newline, 2)(input)?;
-    let (output, moves) = separated_list1(newline, parse_crate_move_line)(input)?;
-    IResult::Ok((output, CrateMoves { crate_lines, moves }))
+    let (output, moves) = separated_list1(newline, parse_crate_move_line(&labels))(input)?;
+    Ok((
+        output,
+        CrateMoves {
+            stack_count: labels.len(),
+            crate_lines,
+            moves,
+        },
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CraneModel {
+    // Moves one crate at a time, so a moved group ends up reversed
+    CrateMover9000,
+    // Moves the whole group at once, keeping its order
+    CrateMover9001,
 }
 
-fn move_crates(crate_moves: CrateMoves, reverse: bool) -> String {
+// Mutable access to two different stacks at once, so crates can move
+// between them without collecting into an intermediate Vec first.
+fn stack_pair<T>(stacks: &mut [T], source: usize, target: usize) -> (&mut T, &mut T) {
+    if source < target {
+        let (low, high) = stacks.split_at_mut(target);
+        (&mut low[source], &mut high[0])
+    } else {
+        let (low, high) = stacks.split_at_mut(source);
+        (&mut high[0], &mut low[target])
+    }
+}
+
+fn move_crates(crate_moves: &CrateMoves, model: CraneModel) -> String {
     let mut reversed_stacks: Vec<Vec<char>> =
-        vec![Vec::with_capacity(crate_moves.crate_lines.len()); crate_moves.crate_lines[0].len()];
+        vec![Vec::with_capacity(crate_moves.crate_lines.len()); crate_moves.stack_count];
 
-    for line in crate_moves.crate_lines {
+    for line in &crate_moves.crate_lines {
         for (i, maybe_c) in line.iter().enumerate() {
             if let Some(c) = maybe_c {
                 reversed_stacks[i].push(*c)
@@ -92,30 +127,138 @@ fn move_crates(crate_moves: CrateMoves, reverse: bool) -> String {
         .into_iter()
         .map(|v| v.into_iter().rev().collect_vec())
         .collect_vec();
-    for Move {
+    for &Move {
         count,
         source,
         target,
-    } in crate_moves.moves
+    } in &crate_moves.moves
     {
+        // Putting crates back where they came from leaves the stack as it was,
+        // whichever the model
+        if source == target {
+            continue;
+        }
         let offset = stacks[source].len() - count;
-        let mut crates = stacks[source].split_off(offset);
-        if reverse {
-            crates = crates.into_iter().rev().collect();
+        let (source, target) = stack_pair(&mut stacks, source, target);
+        match model {
+            CraneModel::CrateMover9000 => target.extend(source.drain(offset..).rev()),
+            CraneModel::CrateMover9001 => target.extend(source.drain(offset..)),
         }
-        stacks[target].append(&mut crates);
     }
     stacks.into_iter().map(|v| v[v.len() - 1]).collect()
 }
 
+pub fn top_crates(input: &str, model: CraneModel) -> String {
+    move_crates(&parse_or_report(parse_input, input), model)
+}
+
+pub fn lint(input: &str) -> Vec<Finding> {
+    let lines = input.lines().collect_vec();
+    let Some(blank) = lines.iter().position(|line| line.trim().is_empty()) else {
+        return vec![Finding::new(
+            lines.len(),
+            "missing blank line between drawing and moves",
+        )];
+    };
+    if blank == 0 {
+        return vec![Finding::new(1, "missing crate drawing")];
+    }
+    let mut findings = Vec::new();
+    let label_line = lines[blank - 1];
+    let width = label_line.len();
+    for (i, line) in lines[..blank - 1].iter().enumerate() {
+        if line.len() != width {
+            findings.push(Finding::new(
+                i + 1,
+                format!("drawing row has width {}, expected {width}", line.len()),
+            ));
+        }
+    }
+    let labels = label_line.split_whitespace().collect_vec();
+    for (i, label) in labels.iter().enumerate() {
+        if labels[..i].contains(label) {
+            findings.push(Finding::new(
+                blank,
+                format!("stack label {label:?} appears twice"),
+            ));
+        }
+    }
+    let mut heights = vec![0_usize; labels.len()];
+    for line in &lines[..blank - 1] {
+        for (height, chunk) in heights.iter_mut().zip(line.as_bytes().chunks(4)) {
+            if chunk.first() == Some(&b'[') {
+                *height += 1;
+            }
+        }
+    }
+    for (i, line) in lines.iter().enumerate().skip(blank + 1) {
+        let line_no = i + 1;
+        let Some((count, source, target)) = (match line.split_whitespace().collect_vec()[..] {
+            ["move", count, "from", source, "to", target] => count
+                .parse::<usize>()
+                .ok()
+                .map(|count| (count, source, target)),
+            _ => None,
+        }) else {
+            findings.push(Finding::new(line_no, format!("not a move: {line:?}")));
+            continue;
+        };
+        let (Some(source), Some(target)) = (
+            labels.iter().position(|l| *l == source),
+            labels.iter().position(|l| *l == target),
+        ) else {
+            let unknown = [source, target]
+                .into_iter()
+                .find(|label| !labels.contains(label))
+                .unwrap();
+            findings.push(Finding::at(
+                input,
+                Spanned::of(input, unknown, ()).start,
+                format!("unknown stack label in {line:?}"),
+            ));
+            continue;
+        };
+        if count > heights[source] {
+            findings.push(Finding::new(
+                line_no,
+                format!(
+                    "moves {count} crates from stack {}, which only holds {}",
+                    labels[source], heights[source]
+                ),
+            ));
+        }
+        let moved = count.min(heights[source]);
+        heights[source] -= moved;
+        heights[target] += moved;
+    }
+    findings
+}
+
 pub fn level1(input: &str) -> String {
-    let (_, crate_moves) = parse_input(input).unwrap();
-    move_crates(crate_moves, true)
+    top_crates(input, CraneModel::CrateMover9000)
 }
 
 pub fn level2(input: &str) -> String {
-    let (_, crate_moves) = parse_input(input).unwrap();
-    move_crates(crate_moves, false)
+    top_crates(input, CraneModel::CrateMover9001)
+}
+
+// Each level stacks up its own copy of the crates to move them around
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = CrateMoves;
+
+    fn parse(input: &str) -> CrateMoves {
+        parse_or_report(parse_input, input)
+    }
+
+    fn level1(crate_moves: &CrateMoves) -> String {
+        move_crates(crate_moves, CraneModel::CrateMover9000)
+    }
+
+    fn level2(crate_moves: &CrateMoves) -> String {
+        move_crates(crate_moves, CraneModel::CrateMover9001)
+    }
 }
 
 #[cfg(test)]
@@ -128,9 +271,60 @@ mod test {
         assert_eq!(level1(test_input), "CMZ")
     }
 
+    #[test]
+    #[should_panic(expected = "line 7, column 1")]
+    fn stray_lines_fail_to_parse() {
+        let test_input = include_str!("./test_input/day5.txt");
+        level1(&test_input.replacen("move 3", "stray\nmove 3", 1));
+    }
+
     #[test]
     fn level2_given_example() {
         let test_input = include_str!("./test_input/day5.txt");
         assert_eq!(level2(test_input), "MCD")
     }
+
+    const TWELVE_STACKS: &str = "                                    [Y]     [X]
+[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [K] [L]
+ 1   2   3   4   5   6   7   8   9  10  11  12 
+
+move 1 from 12 to 10
+move 2 from 10 to 11";
+
+    #[test]
+    fn more_than_nine_stacks() {
+        assert_eq!(level1(TWELVE_STACKS), "ABCDEFGHIJYL");
+        assert_eq!(level2(TWELVE_STACKS), "ABCDEFGHIJXL");
+        assert_eq!(lint(TWELVE_STACKS), vec![]);
+    }
+
+    #[test]
+    fn move_onto_same_stack() {
+        let test_input = "[A]\n[B]\n[C]\n 1 \n\nmove 2 from 1 to 1";
+        assert_eq!(top_crates(test_input, CraneModel::CrateMover9000), "A");
+        assert_eq!(top_crates(test_input, CraneModel::CrateMover9001), "A");
+    }
+
+    #[test]
+    fn lint_given_example() {
+        let test_input = include_str!("./test_input/day5.txt");
+        assert_eq!(lint(test_input), vec![])
+    }
+
+    #[test]
+    fn lint_finds_broken_drawing_and_moves() {
+        let test_input = "[D]\n[N] [C]\n 1   1 \n\nmove 2 from 1 to 4\nmove 3 from 1 to 1";
+        assert_eq!(
+            lint(test_input)
+                .into_iter()
+                .map(|finding| finding.to_string())
+                .collect_vec(),
+            vec![
+                "line 1: drawing row has width 3, expected 7",
+                "line 3: stack label \"1\" appears twice",
+                "line 5, column 18: unknown stack label in \"move 2 from 1 to 4\"",
+                "line 6: moves 3 crates from stack 1, which only holds 2",
+            ]
+        )
+    }
 }
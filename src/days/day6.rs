@@ -1,6 +1,7 @@
-use std::collections::HashSet;
-
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{counter::Counter, day::Day, infra::Level, random::Rng},
+};
 use bitvec::prelude::*;
 
 type CharMask = BitArr!(for 26, in u32);
@@ -10,11 +11,12 @@ enum CharCounter {
     Seen(CharMask),
 }
 
-impl FromIterator<char> for CharCounter {
-    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+// Only for lowercase ASCII, anything else is out of the mask's range
+impl FromIterator<u8> for CharCounter {
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
         let mut char_mask: CharMask = BitArray::ZERO;
         for c in iter {
-            let i = (ascii_code(c) - LOWER_A_ASCII) as usize;
+            let i = (c - b'a') as usize;
             if char_mask[i] {
                 return CharCounter::FoundDuplicate;
             }
@@ -34,27 +36,185 @@ impl CharCounter {
     }
 }
 
-pub fn first_distinct_chunk(input: &str, size: usize) -> usize {
+// Slides a window over the input counting its bytes, so every window costs
+// one increment and one decrement whatever the alphabet
+struct DistinctWindows<'a> {
+    input: &'a [u8],
+    size: usize,
+    end: usize,
+    counts: Counter<u8>,
+    // Byte values occurring more than once in the current window
+    duplicates: usize,
+}
+
+impl Iterator for DistinctWindows<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.end < self.input.len() {
+            if self.counts.add(self.input[self.end]) == 2 {
+                self.duplicates += 1;
+            }
+            self.end += 1;
+            if self.end > self.size {
+                let removed = self.input[self.end - self.size - 1];
+                if self.counts.remove(&removed) == 1 {
+                    self.duplicates -= 1;
+                }
+            }
+            if self.end >= self.size && self.duplicates == 0 {
+                return Some(self.end);
+            }
+        }
+        None
+    }
+}
+
+// End positions (one past the last byte) of every window of `size` bytes
+// that are all different
+pub fn all_distinct_windows(input: &[u8], size: usize) -> impl Iterator<Item = usize> + '_ {
+    assert!(size > 0, "windows need at least one byte");
+    DistinctWindows {
+        input,
+        size,
+        end: 0,
+        counts: Counter::new(),
+        duplicates: 0,
+    }
+}
+
+pub fn first_distinct_chunk(input: &[u8], size: usize) -> Option<usize> {
+    if input.iter().all(u8::is_ascii_lowercase) {
+        input
+            .windows(size)
+            .position(|w| w.iter().copied().collect::<CharCounter>().result())
+            .map(|start| start + size)
+    } else {
+        all_distinct_windows(input, size).next()
+    }
+}
+
+// Every window checked on the way to the marker, for explaining the scan
+#[derive(Debug, PartialEq, Eq)]
+pub struct MarkerScan {
+    pub size: usize,
+    // For the window ending at `size + i`, a byte it contains twice, or None
+    // if all its bytes differ. Stops at the marker.
+    pub duplicates: Vec<Option<u8>>,
+    // End position and contents of the first window without duplicates
+    pub marker: Option<(usize, Vec<u8>)>,
+}
+
+impl MarkerScan {
+    pub fn is_unique(&self, end: usize) -> bool {
+        end >= self.size && self.duplicates.get(end - self.size) == Some(&None)
+    }
+}
+
+pub fn scan_marker(input: &[u8], size: usize) -> MarkerScan {
+    let mut duplicates = Vec::new();
+    let mut marker = None;
+    for (start, window) in input.windows(size).enumerate() {
+        let mut seen = [false; 256];
+        let duplicate = window
+            .iter()
+            .copied()
+            .find(|&c| std::mem::replace(&mut seen[c as usize], true));
+        duplicates.push(duplicate);
+        if duplicate.is_none() {
+            marker = Some((start + size, window.to_vec()));
+            break;
+        }
+    }
+    MarkerScan {
+        size,
+        duplicates,
+        marker,
+    }
+}
+
+// One frame per window: the input around it with the window in brackets,
+// and the byte that rules it out
+pub fn visualize(input: &str, level: Level) -> Vec<String> {
+    const CONTEXT: usize = 24;
+    let input = signal(input);
+    let size = match level {
+        Level::One => 4,
+        Level::Two => 14,
+    };
+    let scan = scan_marker(input, size);
+    let show = |bytes: &[u8]| bytes.escape_ascii().to_string();
+    let mut frames = scan
+        .duplicates
+        .iter()
+        .enumerate()
+        .map(|(start, duplicate)| {
+            let end = start + size;
+            let before = &input[start.saturating_sub(CONTEXT)..start];
+            let after = &input[end..(end + CONTEXT).min(input.len())];
+            let verdict = match duplicate {
+                Some(c) => format!("{:?} appears more than once", *c as char),
+                None => "all different, this is the marker".to_string(),
+            };
+            format!(
+                "window of {size} ending at {end}\n{}[{}]{}\n{verdict}",
+                show(before),
+                show(&input[start..end]),
+                show(after)
+            )
+        })
+        .collect_vec();
+    if scan.marker.is_none() {
+        frames.push(format!("no window of {size} has all different bytes"));
+    }
+    frames
+}
+
+// The worst case for both levels: `size` bytes from only three letters, so
+// no window of four is distinct, then the only marker right at the end
+pub fn scaled_input(rng: &mut Rng, size: usize) -> String {
+    let mut input: String = (0..size)
+        .map(|_| (b'a' + rng.below(3) as u8) as char)
+        .collect();
+    input += "defghijklmnopq";
     input
-        .chars()
-        .collect_vec()
-        .windows(size)
-        .position(|w| w.iter().copied().collect::<CharCounter>().result())
-        .unwrap()
-        + size
+}
+
+// The datastream's bytes, without the final line break
+fn signal(input: &str) -> &[u8] {
+    input.trim_end().as_bytes()
 }
 
 pub fn level1(input: &str) -> usize {
-    first_distinct_chunk(input, 4)
+    first_distinct_chunk(signal(input), 4).unwrap()
 }
 
 pub fn level2(input: &str) -> usize {
-    first_distinct_chunk(input, 14)
+    first_distinct_chunk(signal(input), 14).unwrap()
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<u8>;
+
+    fn parse(input: &str) -> Vec<u8> {
+        signal(input).to_vec()
+    }
+
+    fn level1(signal: &Vec<u8>) -> String {
+        first_distinct_chunk(signal, 4).unwrap().to_string()
+    }
+
+    fn level2(signal: &Vec<u8>) -> String {
+        first_distinct_chunk(signal, 14).unwrap().to_string()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::complexity::assert_scales_subquadratically;
 
     #[test]
     fn level1_given_example() {
@@ -67,4 +227,51 @@ mod test {
         let test_input = include_str!("./test_input/day6.txt");
         assert_eq!(level2(test_input), 19)
     }
+
+    #[test]
+    fn windows_agree_with_bitmask() {
+        let test_input = include_str!("./test_input/day6.txt").as_bytes();
+        let windows = all_distinct_windows(test_input, 4).collect_vec();
+        assert_eq!(windows[..8], [7, 8, 9, 10, 11, 12, 13, 14]);
+        for size in 1..=14 {
+            assert_eq!(
+                all_distinct_windows(test_input, size).next(),
+                first_distinct_chunk(test_input, size)
+            );
+        }
+    }
+
+    #[test]
+    fn scan_explains_the_marker() {
+        let scan = scan_marker(b"mjqjpqmgb", 4);
+        assert_eq!(
+            scan.duplicates,
+            vec![Some(b'j'), Some(b'j'), Some(b'q'), None]
+        );
+        assert_eq!(scan.marker, Some((7, b"jpqm".to_vec())));
+        assert!(!scan.is_unique(6));
+        assert!(scan.is_unique(7));
+        assert!(!scan.is_unique(3));
+        assert_eq!(scan_marker(b"aab", 3).marker, None);
+    }
+
+    #[test]
+    fn any_bytes() {
+        let input = b"AAbB12\xff\xff1";
+        assert_eq!(first_distinct_chunk(input, 3), Some(4));
+        assert_eq!(all_distinct_windows(input, 3).collect_vec(), [4, 5, 6, 7]);
+        assert_eq!(first_distinct_chunk(input, 6), Some(7));
+        assert_eq!(first_distinct_chunk(input, 7), None);
+        assert_eq!(first_distinct_chunk(b"aab", 3), None);
+    }
+
+    #[test]
+    #[ignore = "timing based and flaky under load, run alone with --ignored"]
+    fn scales_linearly() {
+        assert_scales_subquadratically(
+            |input: &String| level2(input),
+            scaled_input,
+            &[10_000, 40_000, 160_000],
+        );
+    }
 }
@@ -1,75 +1,328 @@
-use std::str::Lines;
+use std::collections::{HashMap, HashSet};
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{
+        day::{Day, Explain},
+        dot::Digraph,
+        infra::Level,
+        random::Rng,
+    },
+};
 
-struct Sizes<'a> {
-    stack: Vec<i64>,
-    lines: Lines<'a>,
+#[derive(Debug)]
+enum Kind<'a> {
+    File(i64),
+    Dir(HashMap<&'a str, usize>),
 }
 
-impl<'a> Iterator for Sizes<'a> {
-    type Item = i64;
+#[derive(Debug)]
+struct Node<'a> {
+    name: &'a str,
+    parent: Option<usize>,
+    kind: Kind<'a>,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        for line in self.lines.by_ref() {
-            if line == "$ cd .." {
-                if let Some(size) = self.stack.pop() {
-                    if let Some(acc) = self.stack.last_mut() {
-                        *acc += size;
-                        return Some(size);
-                    } else {
-                        return None;
-                    }
-                }
+// All nodes live in one Vec and refer to each other by index. Children are
+// always pushed after their parent, so walking the Vec backwards visits
+// every node before its parent.
+#[derive(Debug)]
+struct Tree<'a> {
+    nodes: Vec<Node<'a>>,
+}
+
+const ROOT: usize = 0;
+
+impl<'a> Tree<'a> {
+    fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "",
+                parent: None,
+                kind: Kind::Dir(HashMap::new()),
+            }],
+        }
+    }
+
+    fn parent(&self, id: usize) -> Option<usize> {
+        self.nodes[id].parent
+    }
+
+    fn path(&self, id: usize) -> String {
+        let mut names = std::iter::successors(Some(id), |id| self.parent(*id))
+            .map(|id| self.nodes[id].name)
+            .collect_vec();
+        names.reverse();
+        if names.len() == 1 {
+            "/".to_string()
+        } else {
+            names.join("/")
+        }
+    }
+
+    // Listing the same entry again is fine as long as it agrees with before.
+    // Returns the id of the (possibly existing) entry.
+    fn add(&mut self, dir: usize, name: &'a str, kind: Kind<'a>) -> anyhow::Result<usize> {
+        let Kind::Dir(children) = &self.nodes[dir].kind else {
+            bail!("{} is not a directory", self.path(dir));
+        };
+        let Some(&id) = children.get(name) else {
+            let id = self.nodes.len();
+            self.nodes.push(Node {
+                name,
+                parent: Some(dir),
+                kind,
+            });
+            if let Kind::Dir(children) = &mut self.nodes[dir].kind {
+                children.insert(name, id);
             }
-            if line.starts_with("$ cd ") {
-                self.stack.push(0);
-                continue;
+            return Ok(id);
+        };
+        match (&self.nodes[id].kind, &kind) {
+            (Kind::File(old), Kind::File(new)) if old != new => {
+                bail!("file {name} listed with size {new}, but was {old} before")
             }
-            if let Some(size) = line
-                .split_once(' ')
-                .and_then(|(s, _)| s.parse::<i64>().ok())
-            {
-                if let Some(acc) = self.stack.last_mut() {
-                    *acc += size;
-                }
+            (Kind::File(_), Kind::Dir(_)) => {
+                bail!("{name} listed as a directory, but was a file before")
             }
+            (Kind::Dir(_), Kind::File(_)) => {
+                bail!("{name} listed as a file, but was a directory before")
+            }
+            _ => Ok(id),
         }
-        if let Some(size) = self.stack.pop() {
-            if let Some(acc) = self.stack.last_mut() {
-                *acc += size;
+    }
+
+    // Total size of every node, by id
+    fn totals(&self) -> Vec<i64> {
+        let mut totals = vec![0; self.nodes.len()];
+        for (id, node) in self.nodes.iter().enumerate().rev() {
+            if let Kind::File(size) = node.kind {
+                totals[id] += size;
+            }
+            if let Some(parent) = node.parent {
+                totals[parent] += totals[id];
             }
-            return Some(size);
         }
-        None
+        totals
+    }
+
+    fn dir_sizes(&self) -> Vec<i64> {
+        self.nodes
+            .iter()
+            .zip(self.totals())
+            .filter(|(node, _)| matches!(node.kind, Kind::Dir(_)))
+            .map(|(_, total)| total)
+            .collect()
     }
 }
 
-trait Sizeable {
-    fn sizes(&self) -> Sizes;
+fn parse_tree(input: &str) -> anyhow::Result<Tree<'_>> {
+    let mut tree = Tree::new();
+    let mut cwd = ROOT;
+    for (i, line) in input.lines().enumerate() {
+        let result = match line.split(' ').collect_vec()[..] {
+            ["$", "cd", "/"] => {
+                cwd = ROOT;
+                Ok(())
+            }
+            ["$", "cd", ".."] => {
+                cwd = tree.parent(cwd).unwrap_or(ROOT);
+                Ok(())
+            }
+            ["$", "cd", name] => tree
+                .add(cwd, name, Kind::Dir(HashMap::new()))
+                .map(|id| cwd = id),
+            ["$", "ls"] => Ok(()),
+            ["dir", name] => tree.add(cwd, name, Kind::Dir(HashMap::new())).map(|_| ()),
+            [size, name] => size
+                .parse()
+                .context("expected a file size")
+                .and_then(|size| tree.add(cwd, name, Kind::File(size)))
+                .map(|_| ()),
+            _ => Err(anyhow!("unexpected line {line:?}")),
+        };
+        result.with_context(|| format!("line {}", i + 1))?;
+    }
+    Ok(tree)
 }
 
-impl Sizeable for str {
-    fn sizes(&self) -> Sizes {
-        Sizes {
-            stack: Vec::new(),
-            lines: self.lines(),
+// Graphviz rendering of the directory tree, with every entry labelled by
+// its name and total size
+pub fn tree_graph(input: &str) -> String {
+    let tree = parse_tree(input).unwrap();
+    let totals = tree.totals();
+    let mut graph = Digraph::new("filesystem");
+    for (id, node) in tree.nodes.iter().enumerate() {
+        let name = if id == ROOT { "/" } else { node.name };
+        let shape = match node.kind {
+            Kind::File(_) => "note",
+            Kind::Dir(_) => "folder",
+        };
+        graph
+            .node(id)
+            .attr("label", format!("{name}\n{}", totals[id]))
+            .attr("shape", shape);
+        if let Some(parent) = node.parent {
+            graph.edge(parent, id);
         }
     }
+    graph.to_dot()
+}
+
+// Renames every file and directory, the same name always to the same new
+// one, keeping the lengths and dots of names. Sizes stay, so both answers do.
+pub fn anonymize(input: &str, rng: &mut Rng) -> String {
+    let mut names: HashMap<&str, String> = HashMap::new();
+    let mut taken = HashSet::new();
+    let mut rename = |name| {
+        names
+            .entry(name)
+            .or_insert_with(|| loop {
+                let new: String = name
+                    .chars()
+                    .map(|c| match c {
+                        '.' => '.',
+                        _ => (b'a' + rng.below(26) as u8) as char,
+                    })
+                    .collect();
+                // Names of one or two letters quickly run out of new ones
+                let exhausted = 26usize
+                    .checked_pow(name.len() as u32)
+                    .is_some_and(|count| taken.len() >= count);
+                if taken.insert(new.clone()) || exhausted {
+                    break new;
+                }
+            })
+            .clone()
+    };
+    input
+        .lines()
+        .map(|line| {
+            let (prefix, name) = match line.rsplit_once(' ') {
+                Some(("$ cd", "/" | "..")) | Some(("$", _)) | None => return line.to_string(),
+                Some(split) => split,
+            };
+            format!("{prefix} {}", rename(name))
+        })
+        .join("\n")
+}
+
+fn dir_sizes(input: &str) -> Vec<i64> {
+    parse_tree(input).unwrap().dir_sizes()
+}
+
+fn small_dirs_total(sizes: &[i64]) -> i64 {
+    sizes.iter().filter(|x| **x <= 100_000).sum()
+}
+
+fn smallest_above_cutoff(sizes: &[i64], disk_size: i64, needed: i64) -> Option<i64> {
+    let cutoff = sizes.first()? - (disk_size - needed);
+    sizes.iter().copied().filter(|size| *size >= cutoff).min()
+}
+
+fn smallest_needed_dir(sizes: &[i64], disk_size: i64, needed: i64) -> i64 {
+    or_report(
+        smallest_above_cutoff(sizes, disk_size, needed)
+            .with_context(|| format!("Not even deleting / leaves {needed} free")),
+    )
 }
 
 pub fn level1(input: &str) -> i64 {
-    input.sizes().filter(|x| *x <= 100_000).sum()
+    small_dirs_total(&dir_sizes(input))
+}
+
+// Size of the smallest directory whose deletion leaves at least `needed` free
+// on a disk of `disk_size`, None if not even the root frees that much
+pub fn smallest_deletable_dir(input: &str, disk_size: i64, needed: i64) -> Option<i64> {
+    smallest_above_cutoff(&dir_sizes(input), disk_size, needed)
 }
 
 pub fn level2(input: &str) -> i64 {
-    let sizes = input.sizes().collect_vec();
-    let cutoff = sizes.last().unwrap() - 40_000_000;
-    sizes
-        .into_iter()
-        .filter(|size| *size >= cutoff)
-        .min()
-        .unwrap()
+    smallest_needed_dir(&dir_sizes(input), 70_000_000, 30_000_000)
+}
+
+// Both levels only need the directory sizes, root first
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<i64>;
+
+    fn parse(input: &str) -> Vec<i64> {
+        dir_sizes(input)
+    }
+
+    fn level1(sizes: &Vec<i64>) -> String {
+        small_dirs_total(sizes).to_string()
+    }
+
+    fn level2(sizes: &Vec<i64>) -> String {
+        smallest_needed_dir(sizes, 70_000_000, 30_000_000).to_string()
+    }
+}
+
+impl Explain for Solver {
+    fn explain(input: &str, level: Level) -> Vec<String> {
+        let tree = parse_tree(input).unwrap();
+        let totals = tree.totals();
+        let dirs = tree
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches!(node.kind, Kind::Dir(_)))
+            .map(|(id, _)| (tree.path(id), totals[id]))
+            .collect_vec();
+        let mut steps = Vec::new();
+        match level {
+            Level::One => {
+                let small = dirs
+                    .iter()
+                    .filter(|(_, size)| *size <= 100_000)
+                    .collect_vec();
+                steps.push(format!(
+                    "{} directories, {} of them holding at most 100000:",
+                    dirs.len(),
+                    small.len()
+                ));
+                steps.extend(
+                    small
+                        .iter()
+                        .map(|(path, size)| format!("  {path} holds {size}")),
+                );
+                let total: i64 = small.iter().map(|(_, size)| size).sum();
+                steps.push(format!("Together they hold {total}, the answer"));
+            }
+            Level::Two => {
+                let (disk_size, needed) = (70_000_000, 30_000_000);
+                let used = totals[ROOT];
+                let free = disk_size - used;
+                steps.push(format!(
+                    "/ holds {used} of the disk's {disk_size}, leaving {free} free"
+                ));
+                let cutoff = needed - free;
+                if cutoff <= 0 {
+                    steps.push(format!(
+                        "That is already the {needed} the update needs, so any directory will do"
+                    ));
+                } else {
+                    steps.push(format!(
+                        "The update needs {needed}, so a directory of at least {cutoff} has to go"
+                    ));
+                }
+                let big_enough = dirs
+                    .iter()
+                    .filter(|(_, size)| *size >= cutoff)
+                    .collect_vec();
+                match big_enough.iter().min_by_key(|(_, size)| size) {
+                    Some((path, size)) => steps.push(format!(
+                        "{} directories are that big, the smallest being {path} with {size}, the answer",
+                        big_enough.len()
+                    )),
+                    None => steps.push("Not even / is that big, so there is no answer".to_string()),
+                }
+            }
+        }
+        steps
+    }
 }
 
 #[cfg(test)]
@@ -77,6 +330,33 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn explains_the_example() {
+        let test_input = include_str!("./test_input/day7.txt");
+        let steps = Solver::explain(test_input, Level::One);
+        assert_eq!(steps[0], "4 directories, 2 of them holding at most 100000:");
+        assert_eq!(steps[1..3], ["  /a holds 94853", "  /a/e holds 584"]);
+        assert_eq!(steps[3], "Together they hold 95437, the answer");
+        let steps = Solver::explain(test_input, Level::Two);
+        assert_eq!(
+            steps.last().unwrap(),
+            "2 directories are that big, the smallest being /d with 24933642, the answer"
+        );
+    }
+
+    #[test]
+    fn anonymized_input_keeps_answers() {
+        let test_input = include_str!("./test_input/day7.txt");
+        let anonymous = anonymize(test_input, &mut Rng::new(7));
+        assert_eq!(anonymous.lines().count(), test_input.lines().count());
+        assert_eq!(level1(&anonymous), level1(test_input));
+        assert_eq!(level2(&anonymous), level2(test_input));
+        for name in ["b.txt", "c.dat", "dir a", "cd e", "k.log"] {
+            assert!(!anonymous.contains(name), "{name} is still in\n{anonymous}");
+        }
+        assert!(anonymous.starts_with("$ cd /\n$ ls\ndir "));
+    }
+
     #[test]
     fn level1_given_example() {
         let test_input = include_str!("./test_input/day7.txt");
@@ -88,4 +368,97 @@ mod test {
         let test_input = include_str!("./test_input/day7.txt");
         assert_eq!(level2(test_input), 24933642)
     }
+
+    #[test]
+    fn other_disk_sizes() {
+        let test_input = include_str!("./test_input/day7.txt");
+        // 48381165 used, directory sizes 584, 94853, 24933642 and the root
+        assert_eq!(
+            smallest_deletable_dir(test_input, 48_381_165, 500),
+            Some(584)
+        );
+        assert_eq!(
+            smallest_deletable_dir(test_input, 48_400_000, 100_000),
+            Some(94853)
+        );
+        assert_eq!(
+            smallest_deletable_dir(test_input, 50_000_000, 30_000_000),
+            Some(48381165)
+        );
+        // Needing more than the whole disk
+        assert_eq!(
+            smallest_deletable_dir(test_input, 50_000_000, 60_000_000),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Not even deleting / leaves 60000000 free")]
+    fn needing_more_than_the_disk_is_reported() {
+        let test_input = include_str!("./test_input/day7.txt");
+        smallest_needed_dir(&dir_sizes(test_input), 50_000_000, 60_000_000);
+    }
+
+    #[test]
+    fn repeated_listing_is_counted_once() {
+        let test_input = include_str!("./test_input/day7.txt");
+        let relisted =
+            format!("{test_input}\n$ cd /\n$ ls\ndir a\n14848514 b.txt\n$ cd a\n$ ls\n29116 f");
+        assert_eq!(level1(&relisted), 95437);
+        assert_eq!(level2(&relisted), 24933642);
+    }
+
+    #[test]
+    fn deep_trees_need_no_recursion() {
+        // Directories nest by index, not by recursion, so unlike day 13's
+        // packets there is no depth to guard
+        let deep = "$ cd d\n1 f\n".repeat(100_000);
+        // The root holds as much as the outermost d
+        assert_eq!(level1(&deep), 100_000 + 100_000 * 100_001 / 2);
+        // Nothing needs deleting on a nearly empty disk
+        assert_eq!(level2(&deep), 1);
+    }
+
+    #[test]
+    fn parent_links_give_paths() {
+        let test_input = include_str!("./test_input/day7.txt");
+        let tree = parse_tree(test_input).unwrap();
+        let paths = (0..tree.nodes.len())
+            .filter(|id| tree.nodes[*id].name == "e" || *id == ROOT)
+            .map(|id| tree.path(id))
+            .collect_vec();
+        assert_eq!(paths, vec!["/", "/a/e"]);
+    }
+
+    #[test]
+    fn tree_graph_small() {
+        let dot = tree_graph("$ cd /\n$ ls\ndir a\n10 b\n$ cd a\n$ ls\n5 c");
+        assert_eq!(
+            dot,
+            r#"digraph filesystem {
+    0 [label="/\n15", shape="folder"];
+    1 [label="a\n5", shape="folder"];
+    2 [label="b\n10", shape="note"];
+    3 [label="c\n5", shape="note"];
+    0 -> 1;
+    0 -> 2;
+    1 -> 3;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn conflicting_listing_is_an_error() {
+        let error = parse_tree("$ cd /\n$ ls\n10 a\n$ ls\n20 a").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 5: file a listed with size 20, but was 10 before"
+        );
+        let error = parse_tree("$ cd /\n$ ls\n10 a\n$ cd a").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 4: a listed as a directory, but was a file before"
+        );
+    }
 }
@@ -1,61 +1,171 @@
 use std::cmp::Ordering;
 
-use crate::util::prelude::*;
-
-fn parse_with_default<T: Clone>(input: &str, default: &T) -> Vec<Vec<(u32, T)>> {
-    input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .filter_map(|c| c.to_digit(10).map(|x| (x, default.clone())))
-                .collect_vec()
-        })
+use crate::{
+    prelude::*,
+    util::{
+        day::Day,
+        infra::Level,
+        input::byte_lines,
+        random::Rng,
+        viz::{compose, paint, Paint, Panel},
+    },
+};
+
+// Bytes rather than a str, so that a memory mapped input parses in place
+pub fn parse_heights(input: &[u8]) -> Grid<u32> {
+    byte_lines(input)
+        .map(|line| line.iter().filter_map(|&b| char::from(b).to_digit(10)))
         .collect()
 }
 
-fn transpose<T>(table: Vec<Vec<T>>) -> Vec<Vec<T>> {
-    let row_len = if let Some(row) = table.get(0) {
-        row.len()
-    } else {
-        return Vec::new();
-    };
-    let col_len = table.len();
-    let mut acc = Vec::with_capacity(row_len);
-    for _ in 0..row_len {
-        acc.push(Vec::with_capacity(col_len));
-    }
-    table.into_iter().for_each(|row| {
-        acc.iter_mut()
-            .zip(row)
-            .for_each(|(col, entry)| col.push(entry))
-    });
-    acc
+// Each height next to some per-tree state starting at `default`
+fn with_default<T: Clone>(heights: &Grid<u32>, default: &T) -> Grid<(u32, T)> {
+    heights
+        .iter_rows()
+        .map(|row| row.map(|&height| (height, default.clone())))
+        .collect()
 }
 
-fn set_visible<'a, T, I>(row: I)
+// The edges a tree can be seen from, one bit each
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirMask(u8);
+
+impl DirMask {
+    pub const LEFT: Self = Self(1);
+    pub const RIGHT: Self = Self(2);
+    pub const TOP: Self = Self(4);
+    pub const BOTTOM: Self = Self(8);
+    pub const ALL: [Self; 4] = [Self::LEFT, Self::RIGHT, Self::TOP, Self::BOTTOM];
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    fn insert(&mut self, other: Self) {
+        self.0 |= other.0
+    }
+}
+
+// Marks the trees taller than everything before them in `row` as visible
+// from the edge `row` starts at
+fn set_visible<'a, T, I>(row: I, from: DirMask)
 where
     T: Ord + 'a,
-    I: Iterator<Item = &'a mut (T, bool)>,
+    I: Iterator<Item = &'a mut (T, DirMask)>,
 {
     let mut acc: Option<&T> = None;
     for (t, visible) in row {
-        if let Some(max_so_far) = acc {
-            if *t > *max_so_far {
-                *visible = true;
-                acc = Some(t)
-            }
-        } else {
-            *visible = true;
+        if acc.is_none_or(|max_so_far| *t > *max_so_far) {
+            visible.insert(from);
             acc = Some(t)
         }
     }
 }
 
-fn set_row_visible(table: &mut [Vec<(impl Ord, bool)>]) {
-    table.iter_mut().for_each(|row| {
-        set_visible(row.iter_mut());
-        set_visible(row.iter_mut().rev())
-    })
+// Looks along every row and column from both ends
+fn set_all_visible(table: &mut Grid<(impl Ord, DirMask)>) {
+    table.iter_rows_mut().for_each(|row| {
+        let row = row.into_slice();
+        set_visible(row.iter_mut(), DirMask::LEFT);
+        set_visible(row.iter_mut().rev(), DirMask::RIGHT)
+    });
+    for x in 0..table.length {
+        set_visible(table.col_mut(x), DirMask::TOP);
+        set_visible(table.col_mut(x).rev(), DirMask::BOTTOM)
+    }
+}
+
+// For every tree, the edges of the forest it can be seen from
+pub fn visibility(input: &str) -> Grid<DirMask> {
+    visibility_of(&parse_heights(input.as_bytes()))
+}
+
+fn visibility_of(heights: &Grid<u32>) -> Grid<DirMask> {
+    let mut table = with_default(heights, &DirMask::default());
+    set_all_visible(&mut table);
+    table
+        .iter_rows()
+        .map(|row| row.map(|(_, visible)| *visible))
+        .collect()
+}
+
+// One frame per edge with the heights of the trees seen from it, then one
+// with every tree seen from anywhere. Level 2 looks at the same forest.
+// In colour, the forest shows hidden trees dim and trees seen from one
+// edge green, from more yellow.
+pub fn visualize(input: &str, _level: Level) -> Vec<String> {
+    let heights = parse_heights(input.as_bytes());
+    let visible = visibility_of(&heights);
+    let digit = |height: u32| char::from_digit(height, 10).unwrap().to_string();
+    let forest = Panel::new(
+        "heights",
+        heights
+            .iter_rows()
+            .zip(visible.iter_rows())
+            .map(|(heights, visible)| {
+                heights
+                    .zip(visible)
+                    .map(|(height, mask)| {
+                        let color = match mask.count() {
+                            0 => Paint::Dim,
+                            1 => Paint::Green,
+                            _ => Paint::Yellow,
+                        };
+                        paint(&digit(*height), color)
+                    })
+                    .collect::<String>()
+            })
+            .join("\n"),
+    );
+    let frame = |title: &str, seen: &dyn Fn(DirMask) -> bool| {
+        let rows = heights
+            .iter_rows()
+            .zip(visible.iter_rows())
+            .map(|(heights, visible)| {
+                heights
+                    .zip(visible)
+                    .map(|(height, mask)| {
+                        if seen(*mask) {
+                            paint(&digit(*height), Paint::Green)
+                        } else {
+                            ".".to_string()
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .join("\n");
+        let count = visible
+            .iter_rows()
+            .flatten()
+            .filter(|mask| seen(**mask))
+            .count();
+        let total = heights.iter_rows().flatten().count();
+        compose(&[
+            forest.clone(),
+            Panel::new(title, rows),
+            Panel::new("trees", format!("seen   {count}\nhidden {}", total - count)),
+        ])
+    };
+    let edges = ["left", "right", "top", "bottom"];
+    let mut frames = DirMask::ALL
+        .into_iter()
+        .zip(edges)
+        .map(|(from, edge)| {
+            frame(&format!("seen from the {edge}"), &|mask| {
+                mask.contains(from)
+            })
+        })
+        .collect_vec();
+    frames.push(frame("seen from any edge", &|mask| !mask.is_empty()));
+    frames
 }
 
 fn set_visible_count<'a, T, I>(row: I)
@@ -85,34 +195,92 @@ where
     }
 }
 
-fn set_row_visible_count(table: &mut [Vec<(impl Ord, usize)>]) {
-    table.iter_mut().for_each(|row| {
+// Looks along every row and column from both ends
+fn set_all_visible_count(table: &mut Grid<(impl Ord, usize)>) {
+    table.iter_rows_mut().for_each(|row| {
+        let row = row.into_slice();
         set_visible_count(row.iter_mut());
         set_visible_count(row.iter_mut().rev())
-    })
+    });
+    for x in 0..table.length {
+        set_visible_count(table.col_mut(x));
+        set_visible_count(table.col_mut(x).rev())
+    }
+}
+
+// A square forest of random heights, about `size` bytes of it
+pub fn scaled_input(rng: &mut Rng, size: usize) -> String {
+    let side = (size as f64).sqrt().ceil().max(1.0) as usize;
+    (0..side)
+        .map(|_| (0..side).map(|_| rng.range(0..=9).to_string()).join(""))
+        .join("\n")
 }
 
 pub fn level1(input: &str) -> usize {
-    let mut table = parse_with_default(input, &false);
-    set_row_visible(&mut table);
-    table = transpose(table);
-    set_row_visible(&mut table);
-    table
-        .into_iter()
-        .flat_map(|row| row.into_iter().filter(|(_, visible)| *visible))
+    level1_bytes(input.as_bytes())
+}
+
+pub fn level1_bytes(input: &[u8]) -> usize {
+    count_visible(&parse_heights(input))
+}
+
+fn count_visible(heights: &Grid<u32>) -> usize {
+    visibility_of(heights)
+        .iter()
+        .filter(|visible| !visible.is_empty())
         .count()
 }
 
+// The `k` trees with the highest scenic scores, best first, ties in reading
+// order
+pub fn best_viewpoints(input: &str, k: usize) -> Vec<(GridPos, usize)> {
+    best_viewpoints_of(&parse_heights(input.as_bytes()), k)
+}
+
+fn best_viewpoints_of(heights: &Grid<u32>, k: usize) -> Vec<(GridPos, usize)> {
+    let mut table = with_default(heights, &1_usize);
+    set_all_visible_count(&mut table);
+    let length = table.length;
+    let mut scores = table
+        .iter()
+        .enumerate()
+        .map(|(i, (_, score))| (GridPos::from((i % length, i / length)), *score))
+        .collect_vec();
+    // Stable, so equal scores keep reading order
+    scores.sort_by(|(_, a), (_, b)| b.cmp(a));
+    scores.truncate(k);
+    scores
+}
+
 pub fn level2(input: &str) -> usize {
-    let mut table = parse_with_default(input, &1_usize);
-    set_row_visible_count(&mut table);
-    table = transpose(table);
-    set_row_visible_count(&mut table);
-    table
-        .into_iter()
-        .flat_map(|row| row.into_iter().map(|(_, visible_count)| visible_count))
-        .max()
-        .unwrap()
+    level2_bytes(input.as_bytes())
+}
+
+pub fn level2_bytes(input: &[u8]) -> usize {
+    best_score(&parse_heights(input))
+}
+
+fn best_score(heights: &Grid<u32>) -> usize {
+    best_viewpoints_of(heights, 1)[0].1
+}
+
+// Each level puts its own per-tree state next to the parsed heights
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Grid<u32>;
+
+    fn parse(input: &str) -> Grid<u32> {
+        parse_heights(input.as_bytes())
+    }
+
+    fn level1(heights: &Grid<u32>) -> String {
+        count_visible(heights).to_string()
+    }
+
+    fn level2(heights: &Grid<u32>) -> String {
+        best_score(heights).to_string()
+    }
 }
 
 #[cfg(test)]
@@ -125,9 +293,45 @@ mod test {
         assert_eq!(level1(test_input), 21)
     }
 
+    #[test]
+    fn visibility_directions() {
+        let test_input = include_str!("./test_input/day8.txt");
+        let visible = visibility(test_input);
+        // The top left 5 is only hidden from the right and bottom
+        assert_eq!(
+            visible.get(&(1, 1).into()),
+            Some(&DirMask(DirMask::LEFT.0 | DirMask::TOP.0))
+        );
+        // The middle 3 is hidden from every side
+        assert!(visible.get(&(2, 2).into()).unwrap().is_empty());
+        // Corners are seen from both their edges, this one from no others
+        assert_eq!(visible.get(&(0, 0).into()).unwrap().count(), 2);
+        assert!(visible.iter().all(|mask| mask.count() <= 4));
+    }
+
+    #[test]
+    fn visualizes_each_edge() {
+        let test_input = include_str!("./test_input/day8.txt");
+        let frames = visualize(test_input, Level::One);
+        assert_eq!(frames.len(), 5);
+        assert!(frames[4].contains("seen   21"), "{}", frames[4]);
+        assert!(frames[2].contains("30373"), "{}", frames[2]);
+    }
+
     #[test]
     fn level2_given_example() {
         let test_input = include_str!("./test_input/day8.txt");
         assert_eq!(level2(test_input), 8)
     }
+
+    #[test]
+    fn best_viewpoints_with_positions() {
+        let test_input = include_str!("./test_input/day8.txt");
+        let best = best_viewpoints(test_input, 3);
+        assert_eq!(
+            best,
+            [((2, 3).into(), 8), ((1, 2).into(), 6), ((2, 1).into(), 4)]
+        );
+        assert_eq!(best_viewpoints(test_input, 100).len(), 25);
+    }
 }
@@ -1,14 +1,24 @@
-use std::{any, cmp::Ordering, collections::HashSet};
-
 use nom::{
-    character::complete::{anychar, char, i64, line_ending},
-    combinator::{map, map_res},
-    multi::separated_list0,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, i64, line_ending},
+    combinator::{cut, map, value, verify},
+    multi::separated_list1,
     sequence::separated_pair,
-    Finish, IResult, Parser,
+    IResult, Parser,
 };
 
-use crate::util::prelude::*;
+use crate::{
+    prelude::*,
+    util::{
+        day::{Answer, Day},
+        export::{Field, Row},
+        flood::Point2,
+        hash::FastHashMap,
+        random::Rng,
+        visited::DenseVisited,
+    },
+};
 
 #[derive(Debug, Copy, Clone)]
 enum Direction {
@@ -16,71 +26,136 @@ enum Direction {
     Right,
     Up,
     Down,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
-impl TryFrom<char> for Direction {
-    type Error = anyhow::Error;
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
+impl Direction {
+    fn delta(&self) -> (i64, i64) {
         use Direction::*;
-        match value {
-            'L' => Ok(Left),
-            'R' => Ok(Right),
-            'U' => Ok(Up),
-            'D' => Ok(Down),
-            _ => Err(anyhow!("unknown direction char")),
+        match self {
+            Left => (-1, 0),
+            Right => (1, 0),
+            Up => (0, 1),
+            Down => (0, -1),
+            UpLeft => (-1, 1),
+            UpRight => (1, 1),
+            DownLeft => (-1, -1),
+            DownRight => (1, -1),
         }
     }
 }
 
+// Diagonals only show up in community variants of the puzzle, and have to be
+// tried first so that "UL" isn't read as "U" followed by garbage.
+fn direction(input: &str) -> IResult<&str, Direction> {
+    use Direction::*;
+    alt((
+        value(UpLeft, tag("UL")),
+        value(UpRight, tag("UR")),
+        value(DownLeft, tag("DL")),
+        value(DownRight, tag("DR")),
+        value(Left, char('L')),
+        value(Right, char('R')),
+        value(Up, char('U')),
+        value(Down, char('D')),
+    ))(input)
+}
+
 #[derive(Debug)]
-struct Move {
+pub struct Move {
     length: i64,
     direction: Direction,
 }
 
 fn direction_line(line: &str) -> IResult<&str, Move> {
     map(
-        separated_pair(map_res(anychar, |c| c.try_into()), char(' '), i64),
+        separated_pair(direction, char(' '), verify(i64, |length| *length > 0)),
         |(direction, length)| Move { direction, length },
     )(line)
 }
 
 fn parse_input(input: &str) -> IResult<&str, Vec<Move>> {
-    separated_list0(line_ending, direction_line)(input)
+    separated_list1(line_ending, cut(direction_line))(input)
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
 struct Pos(i64, i64);
 
 impl Pos {
-    fn move_to_head(&mut self, head_pos: &Pos) {
-        let Pos(head_x, head_y) = head_pos;
-        if (head_x - self.0).abs() > 1 || (head_y - self.1).abs() > 1 {
-            self.0 += (head_x - self.0).signum();
-            self.1 += (head_y - self.1).signum();
-        }
+    fn point(self) -> Point2 {
+        (self.0, self.1)
+    }
+
+    fn follow(&mut self, head_pos: &Pos, rule: &impl Follow) {
+        let (dx, dy) = rule.offset(head_pos.0 - self.0, head_pos.1 - self.1);
+        self.0 += dx;
+        self.1 += dy;
     }
 
     fn step(&mut self, direction: &Direction) {
-        match direction {
-            Direction::Left => self.0 -= 1,
-            Direction::Right => self.0 += 1,
-            Direction::Up => self.1 += 1,
-            Direction::Down => self.1 -= 1,
+        let (dx, dy) = direction.delta();
+        self.0 += dx;
+        self.1 += dy;
+    }
+}
+
+fn touching(dx: i64, dy: i64) -> bool {
+    dx.abs() <= 1 && dy.abs() <= 1
+}
+
+// How a knot moves after the knot ahead of it has moved
+pub trait Follow {
+    // Given where the knot ahead is relative to this one, how far this one moves
+    fn offset(&self, dx: i64, dy: i64) -> (i64, i64);
+}
+
+// The puzzle's rule: once not touching, take one step, diagonally if needed
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Chebyshev;
+
+impl Follow for Chebyshev {
+    fn offset(&self, dx: i64, dy: i64) -> (i64, i64) {
+        if touching(dx, dy) {
+            (0, 0)
+        } else {
+            (dx.signum(), dy.signum())
+        }
+    }
+}
+
+// Only moves straight: once not touching, one step along the axis with the
+// longer gap. For a gap of (2, 1) that is (1, 0), where Chebyshev steps
+// diagonally. A diagonal gap like (2, -2) is a tie, which goes to the x axis,
+// so the knot steps (1, 0) and is left not touching until later moves.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Orthogonal;
+
+impl Follow for Orthogonal {
+    fn offset(&self, dx: i64, dy: i64) -> (i64, i64) {
+        if touching(dx, dy) {
+            (0, 0)
+        } else if dx.abs() >= dy.abs() {
+            (dx.signum(), 0)
+        } else {
+            (0, dy.signum())
         }
     }
 }
 
 #[derive(Debug)]
-struct Rope<const N: usize> {
+struct Rope<const N: usize, F> {
     nodes: [Pos; N],
+    rule: F,
 }
 
-impl<const N: usize> Rope<N> {
-    fn new() -> Self {
+impl<const N: usize, F: Follow> Rope<N, F> {
+    fn new(rule: F) -> Self {
         Rope {
             nodes: [Pos(0, 0); N],
+            rule,
         }
     }
 
@@ -91,7 +166,7 @@ impl<const N: usize> Rope<N> {
         // cf https://blog.rust-lang.org/2022/10/28/gats-stabilization.html#the-borrow-checker-isnt-perfect-and-it-shows
         let mut iter = self.nodes.pairs_mut();
         while let Some((head, tail)) = iter.next() {
-            tail.move_to_head(head)
+            tail.follow(head, &self.rule)
         }
     }
 
@@ -100,14 +175,17 @@ impl<const N: usize> Rope<N> {
     }
 }
 
-fn move_rope<'a, const N: usize>(moves: impl Iterator<Item = &'a Move>) -> usize {
-    let mut rope: Rope<N> = Rope::new();
-    let mut seen = HashSet::new();
-    seen.insert(rope.tail());
+fn move_rope<'a, const N: usize>(
+    moves: impl Iterator<Item = &'a Move>,
+    rule: impl Follow,
+) -> usize {
+    let mut rope: Rope<N, _> = Rope::new(rule);
+    let mut seen = DenseVisited::new();
+    seen.insert(rope.tail().point());
     for Move { direction, length } in moves {
         for _ in 0..*length {
             rope.step(direction);
-            seen.insert(rope.tail());
+            seen.insert(rope.tail().point());
         }
     }
     seen.len()
@@ -127,7 +205,10 @@ trait LendingIterator {
 }
 
 impl<'a, T: 'a> LendingIterator for MutPairs<'a, T> {
-    type Item<'me> = (&'me T, &'me mut T) where Self: 'me;
+    type Item<'me>
+        = (&'me T, &'me mut T)
+    where
+        Self: 'me;
 
     fn next(&'_ mut self) -> Option<Self::Item<'_>> {
         self.offset += 1;
@@ -149,19 +230,111 @@ impl<T> PairMutable<T> for [T] {
     }
 }
 
+// Number of positions the last of N knots visits when every knot follows `rule`
+pub fn tail_positions<const N: usize>(input: &str, rule: impl Follow) -> usize {
+    let moves = parse_or_report(parse_input, input);
+    move_rope::<'_, N>(moves.iter(), rule)
+}
+
+// How often the tail stood on a cell, counting the start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visits {
+    pub x: i64,
+    pub y: i64,
+    pub count: usize,
+}
+
+impl Row for Visits {
+    const COLUMNS: &'static [&'static str] = &["x", "y", "visits"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![self.x.into(), self.y.into(), self.count.into()]
+    }
+}
+
+// Cells the second and the last of ten knots visit, which are the tails of
+// both levels, since a knot only ever follows the ones ahead of it
+fn both_tails<'a>(moves: impl Iterator<Item = &'a Move>, rule: impl Follow) -> (usize, usize) {
+    let mut rope: Rope<10, _> = Rope::new(rule);
+    let mut seen = [DenseVisited::new(), DenseVisited::new()];
+    for Move { direction, length } in moves {
+        for _ in 0..*length {
+            rope.step(direction);
+            seen[0].insert(rope.nodes[1].point());
+            seen[1].insert(rope.tail().point());
+        }
+    }
+    // The start, unless a tail came back to it
+    seen.iter_mut().for_each(|seen| {
+        seen.insert(Pos::default().point());
+    });
+    (seen[0].len(), seen[1].len())
+}
+
+// Every cell the last of N knots visits, bottom row first
+pub fn tail_visits<const N: usize>(input: &str, rule: impl Follow) -> Vec<Visits> {
+    let moves = parse_or_report(parse_input, input);
+    let mut rope: Rope<N, _> = Rope::new(rule);
+    let mut visits = FastHashMap::from_iter([(rope.tail(), 1)]);
+    for Move { direction, length } in &moves {
+        for _ in 0..*length {
+            rope.step(direction);
+            *visits.entry(rope.tail()).or_default() += 1;
+        }
+    }
+    visits
+        .into_iter()
+        .map(|(Pos(x, y), count)| Visits { x, y, count })
+        .sorted_by_key(|visits| (visits.y, visits.x))
+        .collect()
+}
+
+// Random moves of up to 20 steps until the input is `size` bytes long
+pub fn scaled_input(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::with_capacity(size + 8);
+    while input.len() < size {
+        let direction = ["L", "R", "U", "D"][rng.below(4)];
+        input += &format!("{direction} {}\n", 1 + rng.below(20));
+    }
+    input
+}
+
 pub fn level1(input: &str) -> usize {
-    let moves = parse_input(input).finish().unwrap().1;
-    move_rope::<'_, 2>(moves.iter())
+    tail_positions::<2>(input, Chebyshev)
 }
 
 pub fn level2(input: &str) -> usize {
-    let moves = parse_input(input).finish().unwrap().1;
-    move_rope::<'_, 10>(moves.iter())
+    tail_positions::<10>(input, Chebyshev)
+}
+
+pub struct Solver;
+
+impl Day for Solver {
+    type Parsed = Vec<Move>;
+
+    fn parse(input: &str) -> Vec<Move> {
+        parse_or_report(parse_input, input)
+    }
+
+    fn level1(moves: &Vec<Move>) -> String {
+        move_rope::<2>(moves.iter(), Chebyshev).to_string()
+    }
+
+    fn level2(moves: &Vec<Move>) -> String {
+        move_rope::<10>(moves.iter(), Chebyshev).to_string()
+    }
+
+    // The ten knot rope carries level 1's two knot one along
+    fn both(moves: &Vec<Move>) -> (Answer, Answer) {
+        let (one, two) = both_tails(moves.iter(), Chebyshev);
+        (Answer::Number(one as i128), Answer::Number(two as i128))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::complexity::assert_scales_subquadratically;
 
     #[test]
     fn level1_given_example() {
@@ -175,9 +348,92 @@ mod test {
         assert_eq!(level2(test_input), 1)
     }
 
+    #[test]
+    fn both_levels_in_one_pass() {
+        for test_input in [
+            include_str!("./test_input/day9.txt"),
+            include_str!("./test_input/day9_large.txt"),
+        ] {
+            let moves = Solver::parse(test_input);
+            assert_eq!(
+                Solver::both(&moves),
+                (Solver::level1(&moves).into(), Solver::level2(&moves).into())
+            );
+        }
+    }
+
+    #[test]
+    fn counts_visits() {
+        let test_input = include_str!("./test_input/day9.txt");
+        let visits = tail_visits::<2>(test_input, Chebyshev);
+        assert_eq!(visits.len(), 13);
+        // 24 steps after the start
+        assert_eq!(visits.iter().map(|visits| visits.count).sum::<usize>(), 25);
+        assert_eq!(
+            tail_visits::<10>(test_input, Chebyshev),
+            [Visits {
+                x: 0,
+                y: 0,
+                count: 25
+            }]
+        );
+    }
+
     #[test]
     fn level2_larger_example() {
         let test_input = include_str!("./test_input/day9_large.txt");
         assert_eq!(level2(test_input), 36)
     }
+
+    #[test]
+    fn diagonal_moves() {
+        assert_eq!(level1("UR 3\nDL 1\nR 1"), 3);
+        assert_eq!(level2("UR 12\nDR 2"), 6);
+    }
+
+    #[test]
+    fn follow_rules() {
+        assert_eq!(Chebyshev.offset(1, -1), (0, 0));
+        assert_eq!(Chebyshev.offset(2, 1), (1, 1));
+        assert_eq!(Orthogonal.offset(1, -1), (0, 0));
+        assert_eq!(Orthogonal.offset(2, 1), (1, 0));
+        assert_eq!(Orthogonal.offset(-1, 2), (0, 1));
+        // Straight lines can't tell the rules apart
+        assert_eq!(tail_positions::<10>("R 20\nL 2", Orthogonal), 12);
+        assert_eq!(tail_positions::<10>("R 20\nL 2", Chebyshev), 12);
+    }
+
+    #[test]
+    fn diagonal_gaps() {
+        // Chebyshev closes the gap along both axes, while Orthogonal takes
+        // its one straight step along x
+        for (dx, dy) in [(2_i64, -2_i64), (2, 2), (-2, 2), (-2, -2)] {
+            assert_eq!(Chebyshev.offset(dx, dy), (dx.signum(), dy.signum()));
+            assert_eq!(Orthogonal.offset(dx, dy), (dx.signum(), 0));
+        }
+        // Otherwise the longer axis decides
+        assert_eq!(Orthogonal.offset(-2, 1), (-1, 0));
+        assert_eq!(Orthogonal.offset(1, -3), (0, -1));
+        assert_eq!(Chebyshev.offset(-2, 1), (-1, 1));
+    }
+
+    #[test]
+    fn rejects_invalid_moves() {
+        let error = parse_complete(parse_input, "R 4\nU 0\nL 3").unwrap_err();
+        assert_eq!(error.line_col(), (2, 3));
+        let error = parse_complete(parse_input, "R 4\nX 2").unwrap_err();
+        assert_eq!(error.line_col(), (2, 1));
+        let error = parse_complete(parse_input, "R 4\nDR -1").unwrap_err();
+        assert_eq!(error.line_col(), (2, 4));
+    }
+
+    #[test]
+    #[ignore = "timing based and flaky under load, run alone with --ignored"]
+    fn scales_linearly() {
+        assert_scales_subquadratically(
+            |input: &String| level2(input),
+            scaled_input,
+            &[2_000, 8_000, 32_000],
+        );
+    }
 }
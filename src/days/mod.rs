@@ -1,17 +1,608 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
+// Each day compiles only with its dayNN feature, all of which are on by default
+#[cfg(feature = "day01")]
 pub mod day1;
+#[cfg(feature = "day10")]
 pub mod day10;
+#[cfg(feature = "day11")]
 pub mod day11;
+#[cfg(feature = "day12")]
 pub mod day12;
+#[cfg(feature = "day13")]
 pub mod day13;
+#[cfg(feature = "day14")]
 pub mod day14;
+#[cfg(feature = "day15")]
+pub mod day15;
+#[cfg(feature = "day02")]
 pub mod day2;
+#[cfg(feature = "day03")]
 pub mod day3;
+#[cfg(feature = "day04")]
 pub mod day4;
+#[cfg(feature = "day05")]
 pub mod day5;
+#[cfg(feature = "day06")]
 pub mod day6;
+#[cfg(feature = "day07")]
 pub mod day7;
+#[cfg(feature = "day08")]
 pub mod day8;
+#[cfg(feature = "day09")]
 pub mod day9;
-pub mod day15;
+
+use std::{
+    io::{self, BufRead},
+    thread,
+};
+
+use crate::util::{
+    day::{self, Answer, DiffModel, Explain, ParseCache},
+    export::Table,
+    infra::Level,
+    lint::Finding,
+    random::Rng,
+};
+
+// What each level's answer looks like
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnswerType {
+    Number,
+    Text,
+    // Letters drawn on a screen, read off by a human
+    Drawing,
+}
+
+// Facts about a puzzle that don't depend on the solution being compiled in.
+// `needs_params` marks levels that take puzzle parameters, like a row
+// number, that differ between the example and the real input.
+#[derive(Debug)]
+pub struct PuzzleMeta {
+    pub day: u32,
+    pub title: &'static str,
+    pub answer_type: [AnswerType; 2],
+    pub needs_params: bool,
+}
+
+pub const PUZZLES: &[PuzzleMeta] = &[
+    PuzzleMeta {
+        day: 1,
+        title: "Calorie Counting",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 2,
+        title: "Rock Paper Scissors",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 3,
+        title: "Rucksack Reorganization",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 4,
+        title: "Camp Cleanup",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 5,
+        title: "Supply Stacks",
+        answer_type: [AnswerType::Text, AnswerType::Text],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 6,
+        title: "Tuning Trouble",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 7,
+        title: "No Space Left On Device",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 8,
+        title: "Treetop Tree House",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 9,
+        title: "Rope Bridge",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 10,
+        title: "Cathode-Ray Tube",
+        answer_type: [AnswerType::Number, AnswerType::Drawing],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 11,
+        title: "Monkey in the Middle",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 12,
+        title: "Hill Climbing Algorithm",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 13,
+        title: "Distress Signal",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 14,
+        title: "Regolith Reservoir",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: false,
+    },
+    PuzzleMeta {
+        day: 15,
+        title: "Beacon Exclusion Zone",
+        answer_type: [AnswerType::Number, AnswerType::Number],
+        needs_params: true,
+    },
+];
+
+pub fn puzzle(day: u32) -> Option<&'static PuzzleMeta> {
+    PUZZLES.iter().find(|puzzle| puzzle.day == day)
+}
+
+// A fast answer together with a slow but obviously correct one, to be
+// compared on random small inputs by `aoc selftest`
+#[derive(Debug)]
+pub struct Reference {
+    pub name: &'static str,
+    pub generate: fn(&mut Rng) -> String,
+    pub optimized: fn(&str) -> String,
+    pub brute_force: fn(&str) -> String,
+}
+
+pub type StreamSolver = fn(&mut dyn BufRead, Level) -> io::Result<Answer>;
+
+pub type InputDiff = fn(&str, &str) -> anyhow::Result<Vec<String>>;
+
+pub type Perturbation = fn(&str, &str, i64, Level) -> anyhow::Result<Answer>;
+
+#[derive(Debug)]
+pub struct Solution {
+    pub day: u32,
+    // Runs one level through the day's `Day` impl, parsing via the cache
+    pub solve: fn(&str, Level, &mut ParseCache) -> Answer,
+    // Both levels at once, in one pass for days that have one
+    pub both: fn(&str, &mut ParseCache) -> (Answer, Answer),
+    pub lint: Option<fn(&str) -> Vec<Finding>>,
+    // Scrambles what is personal about an input but keeps its structure,
+    // for sharing inputs that break something
+    pub anonymize: Option<fn(&str, &mut Rng) -> String>,
+    // Graphviz export of whatever structure the day builds from its input
+    pub dot: Option<fn(&str) -> String>,
+    // Frames explaining how a level arrives at its answer, for aoc run --visualize
+    pub visualize: Option<fn(&str, Level) -> Vec<String>>,
+    // A random input of about the given number of bytes, for aoc scaling
+    pub scaled: Option<fn(&mut Rng, usize) -> String>,
+    // Solves a level while reading the input, for inputs too big to hold in
+    // memory, for aoc run --stream
+    pub stream: Option<StreamSolver>,
+    // A table of how the day arrives at its answer, for aoc run --export-trace:
+    // day 9's tail visits, day 10's cycles or day 11's rounds
+    pub trace: Option<fn(&str) -> Table>,
+    // The steps to a level's answer, for aoc run --explain
+    pub explain: Option<fn(&str, Level) -> Vec<String>>,
+    // What changed between two inputs, part by part, for aoc diff-input
+    pub diff: Option<InputDiff>,
+    // A level's answer with a named parameter of the parsed input moved by
+    // an amount, for aoc perturb
+    pub perturb: Option<Perturbation>,
+    pub references: &'static [Reference],
+}
+
+pub const SOLUTIONS: &[Solution] = &[
+    #[cfg(feature = "day01")]
+    Solution {
+        day: 1,
+        solve: day::solve::<day1::Solver>,
+        both: day::solve_both::<day1::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: Some(day14::visualize),
+        scaled: Some(day1::scaled_input),
+        stream: Some(day1::stream),
+        trace: None,
+        explain: Some(day1::Solver::explain),
+        diff: None,
+        perturb: Some(day::perturbed::<day1::Solver>),
+        references: &[],
+    },
+    #[cfg(feature = "day02")]
+    Solution {
+        day: 2,
+        solve: day::solve::<day2::Solver>,
+        both: day::solve_both::<day2::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day03")]
+    Solution {
+        day: 3,
+        solve: day::solve::<day3::Solver>,
+        both: day::solve_both::<day3::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day04")]
+    Solution {
+        day: 4,
+        solve: day::solve::<day4::Solver>,
+        both: day::solve_both::<day4::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day05")]
+    Solution {
+        day: 5,
+        solve: day::solve::<day5::Solver>,
+        both: day::solve_both::<day5::Solver>,
+        lint: Some(day5::lint),
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day06")]
+    Solution {
+        day: 6,
+        solve: day::solve::<day6::Solver>,
+        both: day::solve_both::<day6::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: Some(day6::visualize),
+        scaled: Some(day6::scaled_input),
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day07")]
+    Solution {
+        day: 7,
+        solve: day::solve::<day7::Solver>,
+        both: day::solve_both::<day7::Solver>,
+        lint: None,
+        anonymize: Some(day7::anonymize),
+        dot: Some(day7::tree_graph),
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: Some(day7::Solver::explain),
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day08")]
+    Solution {
+        day: 8,
+        solve: day::solve::<day8::Solver>,
+        both: day::solve_both::<day8::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: Some(day8::visualize),
+        scaled: Some(day8::scaled_input),
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day09")]
+    Solution {
+        day: 9,
+        solve: day::solve::<day9::Solver>,
+        both: day::solve_both::<day9::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: Some(day9::scaled_input),
+        stream: None,
+        trace: Some(|input| Table::from_rows(&day9::tail_visits::<10>(input, day9::Chebyshev))),
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day10")]
+    Solution {
+        day: 10,
+        solve: day::solve::<day10::Solver>,
+        both: day::solve_both::<day10::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: Some(|input| Table::from_rows(&day10::trace(input))),
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day11")]
+    Solution {
+        day: 11,
+        solve: day::solve::<day11::Solver>,
+        both: day::solve_both::<day11::Solver>,
+        lint: Some(day11::lint),
+        anonymize: None,
+        dot: Some(day11::throw_graph),
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: Some(|input| Table::from_rows(&day11::inspection_rows(input))),
+        explain: None,
+        diff: Some(day11::Solver::diff),
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day12")]
+    Solution {
+        day: 12,
+        solve: day::solve::<day12::Solver>,
+        both: day::solve_both::<day12::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day13")]
+    Solution {
+        day: 13,
+        solve: day::solve::<day13::Solver>,
+        both: day::solve_both::<day13::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[],
+    },
+    #[cfg(feature = "day14")]
+    Solution {
+        day: 14,
+        solve: day::solve::<day14::Solver>,
+        both: day::solve_both::<day14::Solver>,
+        lint: None,
+        anonymize: None,
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: None,
+        diff: None,
+        perturb: None,
+        references: &[Reference {
+            name: "level2",
+            generate: day14::random_input,
+            optimized: |input| day14::level2(input).to_string(),
+            brute_force: |input| day14::level2_brute_force(input).to_string(),
+        }],
+    },
+    #[cfg(feature = "day15")]
+    Solution {
+        day: 15,
+        solve: day::solve::<day15::Solver>,
+        both: day::solve_both::<day15::Solver>,
+        lint: Some(day15::lint),
+        anonymize: Some(day15::anonymize),
+        dot: None,
+        visualize: None,
+        scaled: None,
+        stream: None,
+        trace: None,
+        explain: Some(day15::Solver::explain),
+        diff: Some(day15::Solver::diff),
+        perturb: Some(day::perturbed::<day15::Solver>),
+        references: &[Reference {
+            name: "level1",
+            generate: day15::random_input,
+            optimized: |input| day15::level1(input, 10).to_string(),
+            brute_force: |input| day15::level1_brute_force(input, 10).to_string(),
+        }],
+    },
+];
+
+pub fn solution(day: u32) -> Option<&'static Solution> {
+    SOLUTIONS.iter().find(|solution| solution.day == day)
+}
+
+// Thread counts `repeated_runs` runs at once
+pub const VERIFY_THREADS: [usize; 2] = [2, 4];
+
+// `run` again on this thread, then on each of `VERIFY_THREADS` threads at
+// once, with where each run happened. Solvers with hidden state, like a
+// global cache, give results that differ from a first run.
+pub fn repeated_runs<T: Send>(run: impl Fn() -> T + Sync) -> Vec<(String, T)> {
+    let mut runs = vec![("a second run".to_string(), run())];
+    for threads in VERIFY_THREADS {
+        let results = thread::scope(|s| {
+            (0..threads)
+                .map(|_| s.spawn(&run))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        runs.extend(
+            results
+                .into_iter()
+                .map(|result| (format!("{threads} threads"), result)),
+        );
+    }
+    runs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Solver = fn() -> String;
+
+    #[allow(unused_macros)]
+    macro_rules! solver {
+        ($day:ident::$level:ident $(, $arg:expr)?) => {
+            (concat!(stringify!($day), " ", stringify!($level)), || {
+                let input = include_str!(concat!("test_input/", stringify!($day), ".txt"));
+                $day::$level(input $(, $arg)?).to_string()
+            })
+        };
+    }
+
+    // Every solver on its example input, so that answers depending on hash
+    // iteration order or other run-to-run state show up as mismatches.
+    const SOLVERS: &[(&str, Solver)] = &[
+        #[cfg(feature = "day01")]
+        solver!(day1::level1),
+        #[cfg(feature = "day01")]
+        solver!(day1::level2),
+        #[cfg(feature = "day02")]
+        solver!(day2::level1),
+        #[cfg(feature = "day02")]
+        solver!(day2::level2),
+        #[cfg(feature = "day03")]
+        solver!(day3::level1),
+        #[cfg(feature = "day03")]
+        solver!(day3::level2),
+        #[cfg(feature = "day04")]
+        solver!(day4::level1),
+        #[cfg(feature = "day04")]
+        solver!(day4::level2),
+        #[cfg(feature = "day05")]
+        solver!(day5::level1),
+        #[cfg(feature = "day05")]
+        solver!(day5::level2),
+        #[cfg(feature = "day06")]
+        solver!(day6::level1),
+        #[cfg(feature = "day06")]
+        solver!(day6::level2),
+        #[cfg(feature = "day07")]
+        solver!(day7::level1),
+        #[cfg(feature = "day07")]
+        solver!(day7::level2),
+        #[cfg(feature = "day08")]
+        solver!(day8::level1),
+        #[cfg(feature = "day08")]
+        solver!(day8::level2),
+        #[cfg(feature = "day09")]
+        solver!(day9::level1),
+        #[cfg(feature = "day09")]
+        solver!(day9::level2),
+        #[cfg(feature = "day10")]
+        solver!(day10::level1),
+        #[cfg(feature = "day10")]
+        solver!(day10::level2),
+        #[cfg(feature = "day11")]
+        solver!(day11::level1),
+        #[cfg(feature = "day11")]
+        solver!(day11::level2),
+        #[cfg(feature = "day12")]
+        solver!(day12::level1),
+        #[cfg(feature = "day12")]
+        solver!(day12::level2),
+        #[cfg(feature = "day13")]
+        solver!(day13::level1),
+        #[cfg(feature = "day13")]
+        solver!(day13::level2),
+        #[cfg(feature = "day14")]
+        solver!(day14::level1),
+        #[cfg(feature = "day14")]
+        solver!(day14::level2),
+        #[cfg(feature = "day15")]
+        solver!(day15::level1, 10),
+        #[cfg(feature = "day15")]
+        solver!(day15::level2, 20),
+    ];
+
+    fn run_all() -> Vec<String> {
+        SOLVERS.iter().map(|(_, solve)| solve()).collect()
+    }
+
+    #[test]
+    fn answers_are_deterministic() {
+        let expected = run_all();
+        for (run, result) in repeated_runs(run_all) {
+            for ((name, _), (answer, expected)) in
+                SOLVERS.iter().zip(result.iter().zip(expected.iter()))
+            {
+                assert_eq!(answer, expected, "{name} differs on {run}");
+            }
+        }
+    }
+}
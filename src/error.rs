@@ -0,0 +1,58 @@
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+};
+
+use thiserror::Error;
+
+// What can go wrong at the crate's boundary, for programs that want to react
+// differently to a wrong answer than to a network outage. Internally errors
+// stay `anyhow` with context, and commands get these converted to `anyhow`
+// like any other error.
+#[derive(Debug, Error)]
+pub enum AocError {
+    #[error("{context}: {message}")]
+    Parse { context: String, message: String },
+    // A solution panicking for any reason but failing to parse its input
+    #[error("{context}: {message}")]
+    Panicked { context: String, message: String },
+    #[error("No input for day {day}, expected it at {}", path.display())]
+    InputMissing { day: u32, path: PathBuf },
+    #[error("Day {day} is not available, either it is not solved yet or its day{day:02} feature is disabled")]
+    NotImplemented { day: u32 },
+    #[error("Request to {url} failed")]
+    Network {
+        url: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("{}", match wait {
+        Some(wait) => format!("Answered too recently, {wait} left to wait"),
+        None => "Too many requests, try again later".to_string(),
+    })]
+    RateLimited { wait: Option<String> },
+    #[error("That's not the right answer{}", match hint {
+        Some(hint) => format!(", it is {hint}"),
+        None => String::new(),
+    })]
+    WrongAnswer { hint: Option<Hint> },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+// Which way a wrong answer is off, when the site says so
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    TooHigh,
+    TooLow,
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hint::TooHigh => "too high",
+            Hint::TooLow => "too low",
+        }
+        .fmt(f)
+    }
+}
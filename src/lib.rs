@@ -1,3 +1,105 @@
-#![feature(control_flow_enum)]
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+};
+
+pub mod compat;
 pub mod days;
+pub mod error;
+pub mod runner;
+pub mod server;
 pub mod util;
+
+pub use error::AocError;
+pub use util::{day::Answer, infra::Level, prelude};
+
+use util::{day::ParseCache, parse::take_parse_panic};
+
+// Solves one level of a day for programs embedding the solutions. Days that
+// aren't compiled in are an error, and so is input a day can't make sense of:
+// the solutions assume well formed input and panic otherwise, which is caught
+// here. Failing to parse is a `Parse` error and any other panic `Panicked`.
+// The panic message still goes to the panic hook, usually stderr.
+pub fn solve(day: u8, level: Level, input: &str) -> Result<Answer, AocError> {
+    let solution =
+        days::solution(day.into()).ok_or(AocError::NotImplemented { day: day.into() })?;
+    take_parse_panic();
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        (solution.solve)(input, level, &mut ParseCache::new())
+    }))
+    .map_err(|panic| {
+        let context = format!("Day {day} level {level} failed on this input");
+        let message = panic_message(&*panic);
+        if take_parse_panic() {
+            AocError::Parse { context, message }
+        } else {
+            AocError::Panicked { context, message }
+        }
+    })
+}
+
+// What a caught panic was raised with
+pub fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown error".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "day01")]
+    #[test]
+    fn solves_by_day_number() {
+        let input = include_str!("./days/test_input/day1.txt");
+        assert_eq!(solve(1, Level::One, input).unwrap(), Answer::Number(24000));
+        assert_eq!(solve(1, Level::Two, input).unwrap(), Answer::Number(45000));
+    }
+
+    #[cfg(feature = "day05")]
+    #[test]
+    fn solves_text_answers() {
+        let input = include_str!("./days/test_input/day5.txt");
+        assert_eq!(
+            solve(5, Level::One, input).unwrap(),
+            Answer::Text("CMZ".to_string())
+        );
+    }
+
+    #[test]
+    fn reports_missing_days() {
+        assert!(solve(0, Level::One, "").is_err());
+        assert!(matches!(
+            solve(26, Level::One, ""),
+            Err(AocError::NotImplemented { day: 26 })
+        ));
+    }
+
+    #[cfg(feature = "day01")]
+    #[test]
+    fn reports_bad_input() {
+        let error = solve(1, Level::One, "not a number").unwrap_err();
+        assert!(matches!(error, AocError::Parse { .. }));
+        assert!(error.to_string().starts_with("Day 1 level 1 failed"));
+    }
+
+    #[cfg(all(feature = "day01", feature = "day12"))]
+    #[test]
+    fn reports_other_panics_apart_from_bad_input() {
+        let error = solve(12, Level::One, "SbcE").unwrap_err();
+        assert!(matches!(error, AocError::Panicked { .. }));
+        assert!(error.to_string().ends_with("No path reaches E"));
+        // A parse failure doesn't carry over to the next panic
+        let error = solve(1, Level::One, "not a number").unwrap_err();
+        assert!(matches!(error, AocError::Parse { .. }));
+        let error = solve(12, Level::Two, "SbcE").unwrap_err();
+        assert!(matches!(error, AocError::Panicked { .. }));
+    }
+}
+
+// cargo-aoc's registry of the solutions in `compat`, which it wants last
+#[cfg(feature = "cargo-aoc-compat")]
+aoc_runner_derive::aoc_lib! { year = 2022 }
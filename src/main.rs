@@ -0,0 +1,1024 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{bail, ensure, Context};
+use aoc::{
+    days,
+    error::AocError,
+    runner::{day_and_input, run},
+    util::{
+        answers, bench, bundle,
+        config::{self, Config},
+        day::{Answer, ParseCache},
+        export::{Format, Table},
+        glyphs::{self, GlyphChoice},
+        http,
+        infra::{self, read_input, Level},
+        leaderboard::{self, Leaderboard, NewStar},
+        math::log_log_slope,
+        random::Rng,
+        viz,
+    },
+};
+use itertools::Itertools;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use pico_args::Arguments;
+
+const USAGE: &str = "\
+Usage: aoc [--config PATH] <command> [options]
+
+Commands:
+  run (--day N [--input PATH] | --all) (--level N | --both-levels | --time) [--stats]
+      [--submit [--advance]] [--visualize [--frame-ms N]] [--quiet | --scientific]
+      [--stream] [--export-trace FORMAT] [--explain] [--verify]
+                                      Print answers, parsing each input once for both
+                                      levels; numbers are grouped in thousands unless
+                                      --quiet prints just the raw answers or --scientific
+                                      uses scientific notation; --stats adds timings
+                                      and cache hits, --time shows a table of parse
+                                      and level times instead,
+                                      --submit sends a single day and level's answer,
+                                      unless answers.tsv has it outside the bracket of
+                                      earlier ones that were too high or too low,
+                                      and after a right level 1 --advance saves the
+                                      level 2 statement to puzzles/dayNN.md and offers
+                                      to run level 2,
+                                      --visualize animates how a day finds its answer,
+                                      --stream reads a single day's input while solving
+                                      it, for days with a streaming solver,
+                                      --export-trace prints a single day's step by step
+                                      record instead, as csv, tsv or jsonl, like day
+                                      10's cycles, --explain tells how days 1, 7
+                                      and 15 arrive at their answers, and --verify
+                                      solves each level again and on 2 and 4 threads
+                                      at once, failing if any answer changes
+  list [--time]                       Show each puzzle's title, answer types and whether
+                                      it can run; --time runs both levels
+  status                              Compare the stars on the calendar with the local
+                                      solvers, flagging days solved here but never
+                                      submitted and days starred without a solver
+  fetch-title --day N                 Print a PUZZLES entry with the title from the site
+  leaderboard [--watch [--notify]]    Show the private leaderboard set by leaderboard in
+                                      aoc.toml; --watch polls it every 15 minutes and
+                                      reports new stars, also to the webhook if one is
+                                      set and as desktop notifications with --notify
+  wait --day N                        Count down to the day's unlock, then download its
+                                      input to input/dayN.txt
+  profile --day N [--level N] [--seconds N] [--out PATH]
+                                      Solve the day (both levels unless --level is given)
+                                      over and over for N seconds and write a flamegraph
+                                      SVG to PATH; needs the profile feature
+  serve [--port N]                    Answer POST /solve/{day}/{level} requests with the
+                                      input as body, as JSON (default port 8080); needs
+                                      the serve feature
+  scaling --day N [--sizes LIST] [--level N] [--seed N]
+                                      Time the day on random inputs of each size in LIST
+                                      (default 1k,10k,100k; k and M suffixes allowed) and
+                                      estimate how runtime grows with input size
+  bench [--day N] [--trend N] [--export FORMAT]
+                                      Time both levels of each day against its budget,
+                                      from day_budgets_ms or runtime_budget_ms in
+                                      aoc.toml, and draw the last N times (default 20)
+                                      kept in bench-history.tsv; --export prints that
+                                      history as csv, tsv or jsonl instead
+  watch --day N                       Rerun the day's example tests and real input whenever
+                                      its source or test input changes
+  lint-input --day N [--input PATH]   Check an input file against the day's invariants
+  anonymize --day N [--input PATH] [--seed N]
+                                      Print the input with whatever identifies it
+                                      scrambled, for sharing inputs that break something
+  perturb --day N [--input PATH] [--level N] --param NAME:DELTAS
+                                      Rerun the day with a parameter of its parsed input
+                                      moved by each of DELTAS, like --param r:-1,+1 for
+                                      day 15's sensor ranges, and show how the answers
+                                      change; --param may be repeated
+  export-dot --day N [--input PATH]   Print the day's input structure as a Graphviz graph
+  diff-input --day N OLD NEW          Compare two inputs by what the day parses from them,
+                                      like day 15's sensors or day 11's monkeys, one line
+                                      per part removed (-), added (+) or changed (~)
+  export-day --day N [--out PATH]     Write the day with the modules it uses as one
+                                      file that compiles on its own, for sharing
+  doctor                              Check the configuration, session cookie, inputs
+                                      and terminal, and list how to fix what's wrong
+  config show                         Print the effective settings and where each comes
+                                      from; flags like --year override environment
+                                      variables like AOC_YEAR, which override aoc.toml
+  selftest [--day N] [--cases N] [--seed N] [--out DIR]
+                                      Compare optimized answers against brute force on
+                                      random inputs, saving mismatching inputs to DIR";
+
+fn lint_input(mut pargs: Arguments) -> anyhow::Result<()> {
+    let (solution, input) = day_and_input(&mut pargs)?;
+    let day = solution.day;
+    let Some(lint) = solution.lint else {
+        bail!("Day {day} has no input validator");
+    };
+    let findings = lint(&input);
+    for finding in &findings {
+        println!("day {day}, {finding}");
+    }
+    if findings.is_empty() {
+        println!("No problems found in day {day} input");
+        Ok(())
+    } else {
+        bail!("{} problem(s) found in day {day} input", findings.len())
+    }
+}
+
+fn anonymize(mut pargs: Arguments) -> anyhow::Result<()> {
+    let (solution, input) = day_and_input(&mut pargs)?;
+    let Some(anonymize) = solution.anonymize else {
+        bail!("Day {} has no anonymizer", solution.day);
+    };
+    // A fresh seed by default, so the original can't be recovered from it
+    let seed = match pargs.opt_value_from_str("--seed")? {
+        Some(seed) => seed,
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_nanos() as u64,
+    };
+    // Some parsers are strict about the final newline, so keep it as it was
+    let mut anonymous = anonymize(&input, &mut Rng::new(seed));
+    if input.ends_with('\n') {
+        anonymous.push('\n');
+    }
+    print!("{anonymous}");
+    Ok(())
+}
+
+fn diff_input(mut pargs: Arguments) -> anyhow::Result<()> {
+    let day: u32 = pargs.value_from_str("--day").context("must pass --day")?;
+    let solution = days::solution(day).ok_or(AocError::NotImplemented { day })?;
+    let Some(diff) = solution.diff else {
+        bail!("Day {day} has no structural diff");
+    };
+    let [old, new]: [PathBuf; 2] = [pargs.free_from_str()?, pargs.free_from_str()?];
+    let read = |path: &PathBuf| {
+        std::fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))
+    };
+    let changes = diff(&read(&old)?, &read(&new)?)?;
+    for change in &changes {
+        println!("{change}");
+    }
+    if changes.is_empty() {
+        println!(
+            "{} and {} describe the same day {day} puzzle",
+            old.display(),
+            new.display()
+        );
+    }
+    Ok(())
+}
+
+fn perturb(mut pargs: Arguments) -> anyhow::Result<()> {
+    let (solution, input) = day_and_input(&mut pargs)?;
+    let day = solution.day;
+    let Some(perturbed) = solution.perturb else {
+        bail!("Day {day} has no parameters to perturb");
+    };
+    let levels = match pargs.opt_value_from_str("--level")? {
+        Some(level) => vec![level],
+        None => vec![Level::One, Level::Two],
+    };
+    // NAME:DELTAS, with DELTAS like +1 or -2,-1,+1,+2
+    let params: Vec<String> = pargs.values_from_str("--param")?;
+    ensure!(!params.is_empty(), "must pass --param NAME:DELTAS");
+    let params = params
+        .iter()
+        .map(|param| {
+            let (name, deltas) = param
+                .split_once(':')
+                .with_context(|| format!("Expected NAME:DELTAS, got {param}"))?;
+            let deltas = deltas
+                .split(',')
+                .map(|delta| delta.parse::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("Expected whole numbers for {name}, got {deltas}"))?;
+            Ok((name, deltas))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    // The solvers assume inputs like the real ones and may panic on what a
+    // perturbation makes of them, which is worth knowing too. The message is
+    // shown in the table, so the panic hook stays quiet meanwhile.
+    let catching = |solve: &dyn Fn() -> anyhow::Result<Answer>| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(solve))
+            .map_err(|panic| aoc::panic_message(&*panic));
+        std::panic::set_hook(hook);
+        result
+    };
+    for level in levels {
+        let original = (solution.solve)(&input, level, &mut ParseCache::new());
+        println!("Day {day} level {level}: {original:#}");
+        for (name, deltas) in &params {
+            for &delta in deltas {
+                let outcome = match catching(&|| perturbed(&input, name, delta, level)) {
+                    Ok(answer) => match (&original, answer?) {
+                        (Answer::Number(before), Answer::Number(after)) => {
+                            format!("{:#} ({:+})", Answer::Number(after), after - before)
+                        }
+                        (_, answer) if answer == original => format!("{answer:#} (same)"),
+                        (_, answer) => format!("{answer:#} (changed)"),
+                    },
+                    Err(message) => format!("no answer, {message}"),
+                };
+                println!("  {name} {delta:+}: {outcome}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn export_dot(mut pargs: Arguments) -> anyhow::Result<()> {
+    let (solution, input) = day_and_input(&mut pargs)?;
+    let Some(dot) = solution.dot else {
+        bail!("Day {} has no graph export", solution.day);
+    };
+    print!("{}", dot(&input));
+    Ok(())
+}
+
+fn selftest(mut pargs: Arguments) -> anyhow::Result<()> {
+    let day: Option<u32> = pargs.opt_value_from_str("--day")?;
+    let cases: usize = pargs.opt_value_from_str("--cases")?.unwrap_or(100);
+    let seed: u64 = pargs.opt_value_from_str("--seed")?.unwrap_or(2022);
+    let out: PathBuf = pargs
+        .opt_value_from_str("--out")?
+        .unwrap_or_else(|| "selftest-failures".into());
+    let solutions = days::SOLUTIONS
+        .iter()
+        .filter(|solution| day.is_none_or(|day| day == solution.day))
+        .collect_vec();
+    if let Some(day) = day {
+        ensure!(!solutions.is_empty(), "Day {day} is not solved yet");
+    }
+    let mut rng = Rng::new(seed);
+    let mut failures = 0;
+    for solution in solutions {
+        for reference in solution.references {
+            let label = format!("day{}-{}", solution.day, reference.name);
+            let mut mismatches = 0;
+            for case in 0..cases {
+                let input = (reference.generate)(&mut rng);
+                let optimized = (reference.optimized)(&input);
+                let brute_force = (reference.brute_force)(&input);
+                if optimized != brute_force {
+                    std::fs::create_dir_all(&out)?;
+                    let path = out.join(format!("{label}-{case}.txt"));
+                    std::fs::write(&path, &input)?;
+                    println!(
+                        "{label}: got {optimized}, brute force says {brute_force}, input saved to {}",
+                        path.display()
+                    );
+                    mismatches += 1;
+                }
+            }
+            println!("{label}: {}/{cases} cases agree", cases - mismatches);
+            failures += mismatches;
+        }
+    }
+    if failures > 0 {
+        bail!("{failures} mismatch(es) found");
+    }
+    Ok(())
+}
+
+fn list(mut pargs: Arguments) -> anyhow::Result<()> {
+    let time = pargs.contains("--time");
+    println!("{:>3}  {:<26}{:<18}Status", "Day", "Title", "Answers");
+    for puzzle in days::PUZZLES {
+        let day = puzzle.day;
+        let answers = puzzle
+            .answer_type
+            .iter()
+            .map(|answer| format!("{answer:?}").to_lowercase())
+            .join(", ");
+        let status = match (days::solution(day), read_input(day)) {
+            (None, _) => format!("disabled, day{day:02} feature is off"),
+            (Some(_), Err(_)) => "no input".to_string(),
+            (Some(solution), Ok(input)) if time => {
+                let mut cache = ParseCache::new();
+                let start = Instant::now();
+                for level in [Level::One, Level::Two] {
+                    (solution.solve)(&input, level, &mut cache);
+                }
+                format!("ready, both levels in {:.2?}", start.elapsed())
+            }
+            (Some(_), Ok(_)) => "ready".to_string(),
+        };
+        let params = if puzzle.needs_params {
+            " (takes parameters)"
+        } else {
+            ""
+        };
+        println!(
+            "{day:>3}  {:<26}{answers:<18}{status}{params}",
+            puzzle.title
+        );
+    }
+    Ok(())
+}
+
+// Stars on the site against the local solvers, and what answers.tsv has on
+// the levels still missing a star
+fn status(config: &Config) -> anyhow::Result<()> {
+    let session = config.session().context("Could not fetch the calendar")?;
+    let stars = infra::stars_from_calendar(&infra::fetch_calendar(config.year, &session)?);
+    let bounds = answers::read_bounds()?;
+    let (mut unsubmitted, mut unsolved) = (0, 0);
+    println!("{:>3}  {:<6}{:<8}Status", "Day", "Stars", "Solver");
+    for day in 1..=25 {
+        let stars = stars.get(&day).copied().unwrap_or(0);
+        let solver = days::solution(day).is_some();
+        let status = match (solver, stars) {
+            (false, 0) => continue,
+            (true, 2) => "done".to_string(),
+            (false, _) => {
+                unsolved += 1;
+                "starred, but no local solver".to_string()
+            }
+            (true, _) => {
+                unsubmitted += 1;
+                // The first level without a star
+                let level = if stars == 0 { Level::One } else { Level::Two };
+                let wrong = bounds
+                    .iter()
+                    .filter(|bound| bound.day == day && bound.level == level)
+                    .count();
+                match wrong {
+                    0 => format!("solved locally, level {level} never submitted"),
+                    wrong => format!(
+                        "solved locally, level {level} had {wrong} answer(s) too high or too low"
+                    ),
+                }
+            }
+        };
+        let solver = if solver { "yes" } else { "no" };
+        println!(
+            "{day:>3}  {:<6}{solver:<8}{status}",
+            "*".repeat(stars.into())
+        );
+    }
+    println!("{unsubmitted} day(s) solved locally without both stars, {unsolved} starred without a local solver");
+    Ok(())
+}
+
+fn export_day(mut pargs: Arguments) -> anyhow::Result<()> {
+    let day: u32 = pargs.value_from_str("--day").context("must pass --day")?;
+    let out: Option<PathBuf> = pargs.opt_value_from_str("--out")?;
+    days::solution(day).ok_or(AocError::NotImplemented { day })?;
+    let bundle = bundle::bundle(day, |path| std::fs::read_to_string(path))?;
+    match out {
+        Some(out) => std::fs::write(&out, bundle)
+            .with_context(|| format!("Could not write {}", out.display()))?,
+        None => print!("{bundle}"),
+    }
+    Ok(())
+}
+
+fn fetch_title(mut pargs: Arguments, config: &Config) -> anyhow::Result<()> {
+    let day: u32 = pargs.value_from_str("--day").context("must pass --day")?;
+    let title = infra::fetch_title(config.year, day)?;
+    println!(
+        "    PuzzleMeta {{\n        day: {day},\n        title: {title:?},\n        answer_type: [AnswerType::Number, AnswerType::Number],\n        needs_params: false,\n    }},"
+    );
+    Ok(())
+}
+
+fn wait(mut pargs: Arguments, config: &Config) -> anyhow::Result<()> {
+    let day: u32 = pargs.value_from_str("--day").context("must pass --day")?;
+    let session = config.session().context("Could not download the input")?;
+    let path = format!("input/day{day}.txt");
+    ensure!(!Path::new(&path).exists(), "{path} is already there");
+    let input = wait_for_input(day, config, session)?;
+    std::fs::create_dir_all("input")?;
+    std::fs::write(&path, input).with_context(|| format!("Could not write {path}"))?;
+    println!("Saved day {day}'s input to {path}");
+    Ok(())
+}
+
+// Redraws a single status line
+fn show_status(status: &str) {
+    print!("\r\x1b[2K{status}");
+    let _ = std::io::stdout().flush();
+}
+
+fn countdown(day: u32, unlock: SystemTime) -> Option<String> {
+    let left = unlock.duration_since(SystemTime::now()).ok()?.as_secs() + 1;
+    Some(format!(
+        "Day {day} unlocks in {}:{:02}:{:02}",
+        left / 3600,
+        left / 60 % 60,
+        left % 60
+    ))
+}
+
+#[cfg(not(feature = "async"))]
+fn wait_for_input(day: u32, config: &Config, session: String) -> anyhow::Result<String> {
+    let unlock = infra::unlock_time(config.year, day);
+    while let Some(status) = countdown(day, unlock) {
+        show_status(&status);
+        std::thread::sleep(Duration::from_millis(250));
+    }
+    show_status("Downloading the input...");
+    let input = http::get_with_retries(&infra::input_url(config.year, day), Some(&session), 10);
+    println!();
+    Ok(input?)
+}
+
+// The countdown keeps ticking on its own task while the download waits for
+// the unlock and retries
+#[cfg(feature = "async")]
+fn wait_for_input(day: u32, config: &Config, session: String) -> anyhow::Result<String> {
+    let unlock = infra::unlock_time(config.year, day);
+    let client = http::nonblocking::Client::new(Some(session))?;
+    let url = infra::input_url(config.year, day);
+    http::nonblocking::block_on(async {
+        let download = async {
+            if let Ok(left) = unlock.duration_since(SystemTime::now()) {
+                tokio::time::sleep(left).await;
+            }
+            client.get_with_retries(&url, 10).await
+        };
+        tokio::pin!(download);
+        let mut ticks = tokio::time::interval(Duration::from_millis(250));
+        loop {
+            tokio::select! {
+                input = &mut download => {
+                    println!();
+                    return Ok(input?);
+                }
+                _ = ticks.tick() => show_status(
+                    &countdown(day, unlock).unwrap_or_else(|| "Downloading the input...".into()),
+                ),
+            }
+        }
+    })?
+}
+
+fn leaderboard(mut pargs: Arguments, config: &Config) -> anyhow::Result<()> {
+    let watch = pargs.contains("--watch");
+    let desktop = pargs.contains("--notify");
+    let id = config
+        .leaderboard
+        .context("leaderboard must be set, in aoc.toml, AOC_LEADERBOARD or --leaderboard")?;
+    let session = config
+        .session()
+        .context("Could not fetch the leaderboard")?;
+    let url = leaderboard::url(config.year, id);
+    if watch {
+        return watch_leaderboard(&url, session, |star| announce(config, desktop, star));
+    }
+    let board = Leaderboard::parse(&http::get(&url, Some(&session))?)?;
+    for (rank, member) in board.standings().iter().enumerate() {
+        println!(
+            "{:>3}) {:>5} {:>3}*  {}",
+            rank + 1,
+            member.local_score,
+            member.stars.len(),
+            member.name
+        );
+    }
+    Ok(())
+}
+
+// Prints a new star, and passes it on to the webhook and the desktop
+fn announce(config: &Config, desktop: bool, star: &NewStar) {
+    show_status("");
+    println!("{star}");
+    if let Some(webhook) = &config.webhook {
+        let json = serde_json::json!({ "text": star.to_string() }).to_string();
+        if let Err(e) = http::post_json(webhook, &json) {
+            println!("{e:#}");
+        }
+    }
+    if desktop {
+        let notified = if cfg!(target_os = "macos") {
+            let script = format!(
+                "display notification {:?} with title \"Advent of Code\"",
+                star.to_string()
+            );
+            Command::new("osascript").args(["-e", &script]).status()
+        } else {
+            Command::new("notify-send")
+                .args(["Advent of Code", &star.to_string()])
+                .status()
+        };
+        if let Err(e) = notified {
+            println!("Could not show a desktop notification: {e}");
+        }
+    }
+}
+
+// Reports the stars earned between polls, forever. The first poll only sets
+// the baseline, and failed polls are reported and retried at the next one.
+fn report_news(
+    previous: &mut Option<Leaderboard>,
+    polled: Result<String, AocError>,
+    on_star: &mut impl FnMut(&NewStar),
+) {
+    match polled
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Leaderboard::parse(&json))
+    {
+        Ok(board) => {
+            match previous {
+                Some(previous) => board.new_stars(previous).iter().for_each(&mut *on_star),
+                None => {
+                    show_status("");
+                    println!("Watching {} members", board.members.len());
+                }
+            }
+            *previous = Some(board);
+        }
+        Err(e) => {
+            show_status("");
+            println!("{e:#}");
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+fn watch_leaderboard(
+    url: &str,
+    session: String,
+    mut on_star: impl FnMut(&NewStar),
+) -> anyhow::Result<()> {
+    let mut previous = None;
+    loop {
+        report_news(&mut previous, http::get(url, Some(&session)), &mut on_star);
+        std::thread::sleep(Duration::from_secs(leaderboard::POLL_INTERVAL_SECS));
+    }
+}
+
+// Shows the time to the next poll while waiting for it
+#[cfg(feature = "async")]
+fn watch_leaderboard(
+    url: &str,
+    session: String,
+    mut on_star: impl FnMut(&NewStar),
+) -> anyhow::Result<()> {
+    let client = http::nonblocking::Client::new(Some(session))?;
+    let interval = Duration::from_secs(leaderboard::POLL_INTERVAL_SECS);
+    http::nonblocking::block_on(async {
+        let mut previous = None;
+        let mut polls = tokio::time::interval(interval);
+        let mut ticks = tokio::time::interval(Duration::from_secs(1));
+        let mut next_poll = tokio::time::Instant::now();
+        loop {
+            tokio::select! {
+                _ = polls.tick() => {
+                    next_poll += interval;
+                    report_news(&mut previous, client.get(url).await, &mut on_star);
+                }
+                _ = ticks.tick() => {
+                    let left = next_poll.saturating_duration_since(tokio::time::Instant::now());
+                    show_status(&format!(
+                        "Next poll in {}:{:02}",
+                        left.as_secs() / 60,
+                        left.as_secs() % 60
+                    ));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(feature = "profile")]
+fn profile(mut pargs: Arguments, config: &Config) -> anyhow::Result<()> {
+    let (solution, input) = day_and_input(&mut pargs)?;
+    let levels = match pargs.opt_value_from_str("--level")? {
+        Some(level) => vec![level],
+        None => vec![Level::One, Level::Two],
+    };
+    let seconds: f64 = pargs
+        .opt_value_from_str("--seconds")?
+        .unwrap_or(config.profile_seconds);
+    let out: PathBuf = pargs
+        .opt_value_from_str("--out")?
+        .unwrap_or_else(|| format!("flamegraph-day{}.svg", solution.day).into());
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(997)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+    // A fresh cache per run so that parsing shows up in the graph too
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed().as_secs_f64() < seconds {
+        let mut cache = ParseCache::new();
+        for level in &levels {
+            std::hint::black_box((solution.solve)(&input, *level, &mut cache));
+        }
+        runs += 1;
+    }
+    let report = guard.report().build()?;
+    report.flamegraph(std::fs::File::create(&out)?)?;
+    println!(
+        "Day {} solved {runs} times in {:.2?}, flamegraph written to {}",
+        solution.day,
+        start.elapsed(),
+        out.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "profile"))]
+fn profile(_: Arguments, _: &Config) -> anyhow::Result<()> {
+    bail!("aoc profile needs the profile feature, try cargo run --release --features profile -- profile")
+}
+
+// "10k" and "1M" as well as plain byte counts
+fn parse_size(size: &str) -> anyhow::Result<usize> {
+    let (digits, factor) = match size.strip_suffix(['k', 'K']) {
+        Some(digits) => (digits, 1_000),
+        None => match size.strip_suffix('M') {
+            Some(digits) => (digits, 1_000_000),
+            None => (size, 1),
+        },
+    };
+    let count: usize = digits
+        .parse()
+        .with_context(|| format!("Invalid size {size:?}"))?;
+    Ok(count * factor)
+}
+
+fn scaling(mut pargs: Arguments) -> anyhow::Result<()> {
+    let day: u32 = pargs.value_from_str("--day").context("must pass --day")?;
+    let sizes: String = pargs
+        .opt_value_from_str("--sizes")?
+        .unwrap_or_else(|| "1k,10k,100k".to_string());
+    let sizes: Vec<usize> = sizes.split(',').map(parse_size).try_collect()?;
+    let levels = match pargs.opt_value_from_str("--level")? {
+        Some(level) => vec![level],
+        None => vec![Level::One, Level::Two],
+    };
+    let seed: u64 = pargs.opt_value_from_str("--seed")?.unwrap_or(2022);
+    let solution = days::solution(day).ok_or(AocError::NotImplemented { day })?;
+    let Some(scaled) = solution.scaled else {
+        bail!("Day {day} has no input generator to scale");
+    };
+    let mut rng = Rng::new(seed);
+    let mut points = Vec::new();
+    println!("{:>10}  {:>12}  Growth", "Bytes", "Time");
+    for size in sizes {
+        let input = scaled(&mut rng, size);
+        // The fastest of a few runs, repeated for at least 100ms on small inputs
+        let mut best = Duration::MAX;
+        let start = Instant::now();
+        for run in 0.. {
+            if run >= 3 && start.elapsed() > Duration::from_millis(100) {
+                break;
+            }
+            let mut cache = ParseCache::new();
+            let run_start = Instant::now();
+            for level in &levels {
+                std::hint::black_box((solution.solve)(&input, *level, &mut cache));
+            }
+            best = best.min(run_start.elapsed());
+        }
+        let point = (input.len() as f64, best.as_secs_f64().max(1e-9));
+        let growth = match points.last() {
+            Some(&last) => format!("x^{:.2}", log_log_slope(&[last, point]).unwrap_or(f64::NAN)),
+            None => String::new(),
+        };
+        println!(
+            "{:>10}  {:>12}  {growth}",
+            input.len(),
+            format!("{best:.2?}")
+        );
+        points.push(point);
+    }
+    match log_log_slope(&points) {
+        Some(exponent) => println!("Runtime grows like bytes^{exponent:.2}"),
+        None => println!("Need at least two different sizes to estimate growth"),
+    }
+    Ok(())
+}
+
+// Times both levels of each day against its budget, and keeps the times in
+// bench::HISTORY_FILE to draw how they changed
+fn bench(mut pargs: Arguments, config: &Config) -> anyhow::Result<()> {
+    let day: Option<u32> = pargs.opt_value_from_str("--day")?;
+    let trend_runs: usize = pargs.opt_value_from_str("--trend")?.unwrap_or(20);
+    // The history so far instead of a new run
+    let export: Option<Format> = pargs.opt_value_from_str("--export")?;
+    let solutions = match day {
+        Some(day) => vec![days::solution(day).ok_or(AocError::NotImplemented { day })?],
+        None => days::SOLUTIONS.iter().collect(),
+    };
+    let mut history = match std::fs::read_to_string(bench::HISTORY_FILE) {
+        Ok(history) => bench::parse_history(&history)
+            .with_context(|| format!("Invalid history in {}", bench::HISTORY_FILE))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e).context(bench::HISTORY_FILE),
+    };
+    if let Some(format) = export {
+        history.retain(|record| day.is_none_or(|day| record.day == day));
+        Table::from_rows(&history).write(format, &mut std::io::stdout().lock())?;
+        return Ok(());
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs();
+    let glyphs = glyphs::current();
+    let mut new_records = String::new();
+    let mut over_budget = Vec::new();
+    println!("{:>3}  {:>12}{:>12}  Trend", "Day", "Time", "Budget");
+    for solution in solutions {
+        let day = solution.day;
+        let Ok(input) = read_input(day) else {
+            println!("{day:>3}  no input");
+            continue;
+        };
+        let mut cache = ParseCache::new();
+        let start = Instant::now();
+        for level in [Level::One, Level::Two] {
+            std::hint::black_box((solution.solve)(&input, level, &mut cache));
+        }
+        let record = bench::Record {
+            timestamp,
+            day,
+            elapsed: start.elapsed(),
+        };
+        history.push(record);
+        new_records += &record.to_line();
+        let budget = config.budget(day);
+        let over = budget.is_some_and(|budget| record.elapsed > budget);
+        if over {
+            over_budget.push(day);
+        }
+        println!(
+            "{day:>3}  {:>12}{:>12}  {}{}",
+            format!("{:.2?}", record.elapsed),
+            budget.map_or(String::new(), |budget| format!("{budget:.0?}")),
+            bench::sparkline(&bench::trend(&history, day, trend_runs), &glyphs),
+            if over { "  OVER BUDGET" } else { "" }
+        );
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(bench::HISTORY_FILE)
+        .and_then(|mut file| file.write_all(new_records.as_bytes()))
+        .with_context(|| format!("Could not write {}", bench::HISTORY_FILE))?;
+    if !over_budget.is_empty() {
+        println!("Over budget: day(s) {}", over_budget.iter().join(", "));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serve")]
+fn serve(mut pargs: Arguments) -> anyhow::Result<()> {
+    let port: u16 = pargs.opt_value_from_str("--port")?.unwrap_or(8080);
+    aoc::server::serve(port)
+}
+
+#[cfg(not(feature = "serve"))]
+fn serve(_: Arguments) -> anyhow::Result<()> {
+    bail!("aoc serve needs the serve feature, try cargo run --release --features serve -- serve")
+}
+
+// Answers and timings for both levels on the real input, from a freshly built runner
+fn run_real_input(day: u32) -> anyhow::Result<Vec<(String, Duration, String)>> {
+    let output = Command::new("cargo")
+        .args(["run", "--quiet", "--release", "--bin", "aoc", "--"])
+        .args([
+            "run",
+            "--both-levels",
+            "--porcelain",
+            "--day",
+            &day.to_string(),
+        ])
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim_end());
+    }
+    String::from_utf8(output.stdout)?
+        .lines()
+        .map(|line| {
+            let (_, level, nanos, answer) = line
+                .splitn(4, '\t')
+                .collect_tuple()
+                .with_context(|| format!("Unexpected runner output {line:?}"))?;
+            let elapsed = Duration::from_nanos(nanos.parse()?);
+            Ok((level.to_string(), elapsed, answer.replace("\\n", "\n")))
+        })
+        .collect()
+}
+
+// Blocks until one of `paths` is written, then lets the burst of events from
+// a single save settle
+fn wait_for_change(
+    events: &Receiver<notify::Result<Event>>,
+    paths: &[PathBuf],
+) -> anyhow::Result<()> {
+    loop {
+        let event = events.recv()??;
+        let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event.paths.iter().any(|path| paths.contains(path));
+        if relevant {
+            while events.recv_timeout(Duration::from_millis(200)).is_ok() {}
+            return Ok(());
+        }
+    }
+}
+
+fn watch(mut pargs: Arguments) -> anyhow::Result<()> {
+    let day: u32 = pargs.value_from_str("--day").context("must pass --day")?;
+    let days_dir = Path::new("src/days")
+        .canonicalize()
+        .context("aoc watch must be run from the repository root")?;
+    let source = days_dir.join(format!("day{day}.rs"));
+    let test_input = days_dir.join(format!("test_input/day{day}.txt"));
+    ensure!(source.exists(), "{} does not exist", source.display());
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // Watching the directories keeps working for editors that save by
+    // replacing the file
+    watcher.watch(&days_dir, RecursiveMode::NonRecursive)?;
+    watcher.watch(&days_dir.join("test_input"), RecursiveMode::NonRecursive)?;
+    let mut previous: HashMap<String, Duration> = HashMap::new();
+    loop {
+        println!("==> day {day}");
+        let tests = Command::new("cargo")
+            .args(["test", "--quiet", "--lib", "--"])
+            .arg(format!("days::day{day}::"))
+            .status()?;
+        if !tests.success() {
+            println!("Example tests failed");
+        }
+        match run_real_input(day) {
+            Ok(answers) => {
+                for (level, elapsed, answer) in answers {
+                    let diff = match previous.insert(level.clone(), elapsed) {
+                        Some(last) if elapsed >= last => format!(", {:.2?} slower", elapsed - last),
+                        Some(last) => format!(", {:.2?} faster", last - elapsed),
+                        None => String::new(),
+                    };
+                    println!("Level {level}: {answer} ({elapsed:.2?}{diff})");
+                }
+            }
+            Err(error) => println!("Real input failed: {error:#}"),
+        }
+        wait_for_change(&rx, &[source.clone(), test_input.clone()])?;
+    }
+}
+
+// A fix for each problem found, and the line describing the check
+type CheckResult = Result<String, (String, String)>;
+
+fn check_session(config: &Config) -> CheckResult {
+    let session = config.session().map_err(|e| {
+        (
+            format!("{e:#}"),
+            "Put your session cookie in SESSION or in the file session_file names".to_string(),
+        )
+    })?;
+    let url = format!("{}/{}", http::site(), config.year);
+    match http::get(&url, Some(&session)) {
+        Ok(page) if infra::logged_in(&page) => Ok(format!("logged in to {url}")),
+        Ok(_) => Err((
+            "the site doesn't know the session cookie".to_string(),
+            "Log in on adventofcode.com again and copy the new session cookie".to_string(),
+        )),
+        Err(e) => Err((
+            format!("{:#}", anyhow::Error::from(e)),
+            "Check your network connection, or whether the site is down".to_string(),
+        )),
+    }
+}
+
+fn check_inputs() -> CheckResult {
+    let (present, missing): (Vec<_>, Vec<_>) = days::SOLUTIONS
+        .iter()
+        .map(|solution| solution.day)
+        .partition(|day| read_input(*day).is_ok());
+    if missing.is_empty() {
+        return Ok(format!("all {} solved days have input", present.len()));
+    }
+    let days = missing.iter().join(", ");
+    Err((
+        format!("no input for day(s) {days}"),
+        format!("Download the input with aoc wait --day N for day(s) {days}"),
+    ))
+}
+
+fn check_terminal(config: &Config) -> CheckResult {
+    let color = if config.use_color() {
+        "with"
+    } else {
+        "without"
+    };
+    let glyphs = config.glyphs();
+    let sample = format!("{}{}", glyphs.solid, glyphs.grain);
+    let unicode = config::unicode_locale(|var| std::env::var(var).ok());
+    if !unicode && config.glyphs == GlyphChoice::Unicode {
+        return Err((
+            format!(
+                "the locale is not UTF-8, so drawings like day 14's {sample} may show up garbled"
+            ),
+            "Set LANG to a UTF-8 locale, like LANG=en_US.UTF-8, or glyphs = \"ascii\" in aoc.toml"
+                .to_string(),
+        ));
+    }
+    let locale = if unicode { "UTF-8" } else { "non UTF-8" };
+    Ok(format!(
+        "{locale} locale, drawing {sample} {color} colors (glyphs = {}, color = {})",
+        config.glyphs, config.color
+    ))
+}
+
+// Checks the setup and prints what to fix
+fn doctor(config: anyhow::Result<Config>) -> anyhow::Result<()> {
+    let mut fixes = Vec::new();
+    let mut report = |name: &str, result: CheckResult| match result {
+        Ok(detail) => println!("ok       {name}: {detail}"),
+        Err((problem, fix)) => {
+            println!("PROBLEM  {name}: {problem}");
+            fixes.push(fix);
+        }
+    };
+    match config {
+        Ok(config) => {
+            report("config", Ok(format!("year {}", config.year)));
+            report("session", check_session(&config));
+            report("inputs", check_inputs());
+            report("terminal", check_terminal(&config));
+        }
+        Err(e) => {
+            report(
+                "config",
+                Err((
+                    format!("{e:#}"),
+                    "Fix the setting in aoc.toml, the environment or the flags".to_string(),
+                )),
+            );
+            report("inputs", check_inputs());
+        }
+    }
+    if fixes.is_empty() {
+        println!("Everything looks fine");
+        return Ok(());
+    }
+    println!("\nTo fix:");
+    for fix in &fixes {
+        println!("  [ ] {fix}");
+    }
+    bail!("{} problem(s) found", fixes.len())
+}
+
+fn config_command(mut pargs: Arguments, config: &Config) -> anyhow::Result<()> {
+    match pargs.subcommand()?.as_deref() {
+        Some("show") => {
+            print!("{}", config.show());
+            Ok(())
+        }
+        _ => bail!("Expected aoc config show"),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut pargs = Arguments::from_env();
+    let config = Config::resolve(&mut pargs);
+    let command = pargs.subcommand()?;
+    // aoc doctor reports a broken configuration instead of failing on it
+    if command.as_deref() == Some("doctor") {
+        return doctor(config);
+    }
+    let config = config?;
+    glyphs::set(config.glyphs());
+    viz::set_color(config.use_color());
+    match command.as_deref() {
+        Some("run") => run(pargs, &config),
+        Some("list") => list(pargs),
+        Some("status") => status(&config),
+        Some("fetch-title") => fetch_title(pargs, &config),
+        Some("leaderboard") => leaderboard(pargs, &config),
+        Some("wait") => wait(pargs, &config),
+        Some("profile") => profile(pargs, &config),
+        Some("serve") => serve(pargs),
+        Some("scaling") => scaling(pargs),
+        Some("bench") => bench(pargs, &config),
+        Some("watch") => watch(pargs),
+        Some("lint-input") => lint_input(pargs),
+        Some("anonymize") => anonymize(pargs),
+        Some("export-dot") => export_dot(pargs),
+        Some("export-day") => export_day(pargs),
+        Some("diff-input") => diff_input(pargs),
+        Some("perturb") => perturb(pargs),
+        Some("selftest") => selftest(pargs),
+        Some("config") => config_command(pargs, &config),
+        Some(command) => bail!("Unknown command {command}\n\n{USAGE}"),
+        None => {
+            println!("{USAGE}");
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    fs::File,
+    io::{BufReader, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, ensure, Context};
+use itertools::Itertools;
+use pico_args::Arguments;
+
+use crate::{
+    days,
+    error::AocError,
+    util::{
+        answers::{self, Bound, Bracket},
+        config::Config,
+        day::{Answer, ParseCache},
+        export::Format,
+        infra::{answer_was_right, fetch_page, level2_statement, read_input, submit, Level},
+        memo, progress, timer,
+    },
+};
+
+// The --day a command applies to, and the input from --input or the day's default file
+pub fn day_and_input(pargs: &mut Arguments) -> anyhow::Result<(&'static days::Solution, String)> {
+    let day: u32 = pargs.value_from_str("--day").context("must pass --day")?;
+    let path: Option<String> = pargs.opt_value_from_str("--input")?;
+    let solution = days::solution(day).ok_or(AocError::NotImplemented { day })?;
+    let input = match path {
+        Some(path) => {
+            std::fs::read_to_string(&path).with_context(|| format!("Could not read {path}"))?
+        }
+        None => read_input(day)?,
+    };
+    Ok((solution, input))
+}
+
+pub fn run(mut pargs: Arguments, config: &Config) -> anyhow::Result<()> {
+    let stats = pargs.contains("--stats");
+    let should_submit = pargs.contains(["-s", "--submit"]);
+    // After a right level 1 answer, save the level 2 statement and offer to run it
+    let advance = pargs.contains("--advance");
+    // One tab separated `day level nanoseconds answer` line per answer, for aoc watch
+    let porcelain = pargs.contains("--porcelain");
+    let visualize = pargs.contains("--visualize");
+    // How the day arrives at each answer, printed before it
+    let explain = pargs.contains("--explain");
+    let frame_ms: u64 = pargs.opt_value_from_str("--frame-ms")?.unwrap_or(50);
+    // Just the raw answers, one per line
+    let quiet = pargs.contains(["-q", "--quiet"]);
+    let scientific = pargs.contains("--scientific");
+    // Reading the input as the day solves it, for inputs too big for memory
+    let stream = pargs.contains("--stream");
+    // A table of parse and level timings instead of the answers
+    let time = pargs.contains("--time");
+    // Solves each level again and on several threads, failing if an answer changes
+    let verify = pargs.contains("--verify");
+    // The day's trace instead of the answers
+    let export_trace: Option<Format> = pargs.opt_value_from_str("--export-trace")?;
+    if let Some(format) = export_trace {
+        let (solution, input) = day_and_input(&mut pargs)?;
+        let Some(trace) = solution.trace else {
+            bail!("Day {} has no trace", solution.day);
+        };
+        trace(&input).write(format, &mut std::io::stdout().lock())?;
+        return Ok(());
+    }
+    let levels = if time || pargs.contains("--both-levels") {
+        vec![Level::One, Level::Two]
+    } else {
+        let level = pargs
+            .value_from_str("--level")
+            .context("must pass --level or --both-levels")?;
+        vec![level]
+    };
+    if stream {
+        return run_streaming(&mut pargs, &levels, quiet);
+    }
+    let days = if pargs.contains("--all") {
+        days::SOLUTIONS
+            .iter()
+            .map(|solution| Ok((solution, read_input(solution.day)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?
+    } else {
+        vec![day_and_input(&mut pargs)?]
+    };
+    ensure!(
+        !should_submit || (days.len() == 1 && levels.len() == 1),
+        "--submit needs a single --day and --level"
+    );
+    ensure!(
+        !advance || (should_submit && levels == [Level::One]),
+        "--advance needs --submit and --level 1"
+    );
+    ensure!(
+        !visualize || days.len() == 1,
+        "--visualize needs a single --day"
+    );
+    if time {
+        ensure!(
+            !should_submit && !visualize && !verify,
+            "--time only reports timings"
+        );
+        return time_days(&days);
+    }
+    let mut cache = ParseCache::new();
+    let mut mismatches = Vec::new();
+    for (solution, input) in days {
+        // Both levels from the day's combined pass, timed together. Porcelain
+        // output keeps one timing per level.
+        let both = (levels.len() == 2 && !porcelain).then(|| {
+            let start = Instant::now();
+            let answers = (solution.both)(&input, &mut cache);
+            (answers, start.elapsed())
+        });
+        for level in &levels {
+            if visualize {
+                let Some(frames) = solution.visualize else {
+                    bail!("Day {} has no visualization", solution.day);
+                };
+                play(&frames(&input, *level), Duration::from_millis(frame_ms));
+            }
+            if explain {
+                let Some(explain) = solution.explain else {
+                    bail!("Day {} has no explanation", solution.day);
+                };
+                println!("Day {} level {level}, step by step:", solution.day);
+                for step in explain(&input, *level) {
+                    println!("  {step}");
+                }
+            }
+            let (answer, elapsed) = match &both {
+                Some(((one, _), elapsed)) if *level == Level::One => (one.clone(), *elapsed),
+                Some(((_, two), _)) => (two.clone(), Duration::ZERO),
+                None => {
+                    let start = Instant::now();
+                    let answer = (solution.solve)(&input, *level, &mut cache);
+                    (answer, start.elapsed())
+                }
+            };
+            if verify {
+                let runs = days::repeated_runs(|| {
+                    (solution.solve)(&input, *level, &mut ParseCache::new())
+                });
+                mismatches.extend(runs.into_iter().filter(|(_, other)| *other != answer).map(
+                    |(run, other)| {
+                        format!(
+                            "Day {} level {level} answered {other} on {run} instead of {answer}",
+                            solution.day
+                        )
+                    },
+                ));
+            }
+            if porcelain {
+                let answer = answer.to_string().replace('\n', "\\n");
+                println!(
+                    "{}\t{level}\t{}\t{answer}",
+                    solution.day,
+                    elapsed.as_nanos()
+                );
+                continue;
+            }
+            if quiet {
+                println!("{answer}");
+            } else if scientific {
+                print!("Day {} level {level}: {answer:.3e}", solution.day);
+            } else {
+                print!("Day {} level {level}: {answer:#}", solution.day);
+            }
+            if stats && !quiet {
+                match both {
+                    Some(_) if *level == Level::One => print!(" ({elapsed:.2?} for both levels)"),
+                    Some(_) => {}
+                    None => print!(" ({elapsed:.2?})"),
+                }
+            }
+            if !quiet {
+                println!();
+            }
+            if should_submit {
+                ensure!(
+                    mismatches.is_empty(),
+                    "Not submitting {answer}, it changed between runs:\n{}",
+                    mismatches.join("\n")
+                );
+                let bracket = Bracket::of(&answers::read_bounds()?, solution.day, *level);
+                ensure!(
+                    bracket.admits(&answer),
+                    "Not submitting {answer}, earlier answers put day {} level {level} {bracket}",
+                    solution.day
+                );
+                let session = config.session().context("Could not submit")?;
+                let response = match submit(config.year, solution.day, *level, &answer, &session) {
+                    Err(AocError::WrongAnswer { hint: Some(hint) }) => {
+                        if let Answer::Number(n) = answer {
+                            answers::record(&Bound {
+                                day: solution.day,
+                                level: *level,
+                                hint,
+                                answer: n,
+                            })?;
+                        }
+                        return Err(AocError::WrongAnswer { hint: Some(hint) }.into());
+                    }
+                    response => response?,
+                };
+                println!("{response}");
+                if advance && answer_was_right(&response) {
+                    advance_to_level2(config, solution, &session)?;
+                    if confirm("Run level 2 now?")? {
+                        let answer = (solution.solve)(&input, Level::Two, &mut cache);
+                        println!("Day {} level 2: {answer:#}", solution.day);
+                    }
+                }
+            }
+        }
+    }
+    if stats {
+        let stats = cache.stats;
+        println!(
+            "Parse cache: {} hit(s), {} miss(es), {:.2?} parsing",
+            stats.hits, stats.misses, stats.parse_time
+        );
+        for timing in timer::take() {
+            println!(
+                "{}: {:.2?} over {} call(s)",
+                timing.label, timing.total, timing.calls
+            );
+        }
+        for progress in progress::take() {
+            let counts = progress
+                .counts
+                .iter()
+                .map(|(name, count)| format!("{name} {:#}", Answer::Number((*count).into())))
+                .join(", ");
+            println!("{}: {counts}", progress.label);
+        }
+        for memo in memo::take() {
+            println!(
+                "{}: {} hit(s), {} miss(es), {:.0}% hit rate",
+                memo.label,
+                memo.hits,
+                memo.misses,
+                memo.hit_rate() * 100.0
+            );
+        }
+    }
+    if !mismatches.is_empty() {
+        bail!(
+            "{} answer(s) changed between runs:\n{}",
+            mismatches.len(),
+            mismatches.join("\n")
+        );
+    }
+    Ok(())
+}
+
+// Runs a single day's streaming solver on --input or the day's default file,
+// opening it again for every level
+fn run_streaming(pargs: &mut Arguments, levels: &[Level], quiet: bool) -> anyhow::Result<()> {
+    let day: u32 = pargs
+        .value_from_str("--day")
+        .context("--stream needs --day")?;
+    let path: String = pargs
+        .opt_value_from_str("--input")?
+        .unwrap_or_else(|| format!("input/day{day}.txt"));
+    let solution = days::solution(day).ok_or(AocError::NotImplemented { day })?;
+    let Some(stream) = solution.stream else {
+        bail!("Day {day} has no streaming solver");
+    };
+    for level in levels {
+        let file = File::open(&path).with_context(|| format!("Could not read {path}"))?;
+        let answer = stream(&mut BufReader::new(file), *level)
+            .with_context(|| format!("Could not solve day {day} level {level}"))?;
+        if quiet {
+            println!("{answer}");
+        } else {
+            println!("Day {day} level {level}: {answer:#}");
+        }
+    }
+    Ok(())
+}
+
+// Saves the level 2 statement the puzzle page now shows to puzzles/dayNN.md
+fn advance_to_level2(
+    config: &Config,
+    solution: &days::Solution,
+    session: &str,
+) -> anyhow::Result<()> {
+    let page = fetch_page(config.year, solution.day, session)?;
+    let statement = level2_statement(&page).context("No level 2 statement on the puzzle page")?;
+    let path = format!("puzzles/day{:02}.md", solution.day);
+    std::fs::create_dir_all("puzzles")?;
+    std::fs::write(&path, statement).with_context(|| format!("Could not write {path}"))?;
+    println!("Saved the level 2 statement to {path}");
+    Ok(())
+}
+
+fn confirm(question: &str) -> anyhow::Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut reply = String::new();
+    std::io::stdin().read_line(&mut reply)?;
+    Ok(matches!(reply.trim(), "y" | "Y" | "yes"))
+}
+
+// Parsing happens within the first level's solve, so it is told apart by the
+// parse time the cache records
+fn time_days(days: &[(&days::Solution, String)]) -> anyhow::Result<()> {
+    println!(
+        "{:>3}  {:>12}{:>12}{:>12}",
+        "Day", "Parse", "Level 1", "Level 2"
+    );
+    let mut slow_parses = Vec::new();
+    for (solution, input) in days {
+        let mut cache = ParseCache::new();
+        let [level1, level2] = [Level::One, Level::Two].map(|level| {
+            let start = Instant::now();
+            (solution.solve)(input, level, &mut cache);
+            start.elapsed()
+        });
+        let parse = cache.stats.parse_time;
+        let level1 = level1.saturating_sub(parse);
+        println!(
+            "{:>3}  {:>12}{:>12}{:>12}",
+            solution.day,
+            format!("{parse:.2?}"),
+            format!("{level1:.2?}"),
+            format!("{level2:.2?}")
+        );
+        if parse > level1 + level2 {
+            slow_parses.push(solution.day);
+        }
+    }
+    for day in slow_parses {
+        println!(
+            "Day {day} spends most of its time parsing, try util::parse::ints or \
+             a simpler nom parser"
+        );
+    }
+    Ok(())
+}
+
+// Draws each frame over the previous one, leaving the last on screen
+fn play(frames: &[String], delay: Duration) {
+    for (i, frame) in frames.iter().enumerate() {
+        println!("\x1b[2J\x1b[H{frame}");
+        if i + 1 < frames.len() {
+            thread::sleep(delay);
+        }
+    }
+}
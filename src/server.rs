@@ -0,0 +1,136 @@
+use std::time::Instant;
+
+use anyhow::Context;
+
+use crate::{solve, Answer, Level};
+
+// The JSON status and body for one request. `POST /solve/{day}/{level}` takes
+// the input as its body and answers with
+// `{"day": 1, "level": 1, "answer": "24000", "kind": "number", "nanos": 1234}`,
+// where the answer is always a string so that big numbers survive JSON
+// parsers using doubles. Failures come back as `{"error": "..."}`.
+pub fn respond(method: &str, path: &str, body: &str) -> (u16, String) {
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+    let (day, level) = match segments[..] {
+        ["solve", day, level] => (day, level),
+        _ => {
+            return error(
+                404,
+                &format!("No route for {path}, try POST /solve/{{day}}/{{level}}"),
+            )
+        }
+    };
+    if method != "POST" {
+        return error(405, "Send the input with POST");
+    }
+    let parsed = day
+        .parse::<u8>()
+        .with_context(|| format!("Invalid day {day:?}"))
+        .and_then(|day| Ok((day, level.parse::<Level>()?)));
+    let (day, level) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => return error(400, &format!("{e:#}")),
+    };
+    let start = Instant::now();
+    match solve(day, level, body) {
+        Ok(answer) => {
+            let nanos = start.elapsed().as_nanos();
+            let kind = match answer {
+                Answer::Number(_) => "number",
+                Answer::Text(_) => "text",
+            };
+            let answer = json_string(&answer.to_string());
+            (
+                200,
+                format!(
+                    r#"{{"day": {day}, "level": {level}, "answer": {answer}, "kind": "{kind}", "nanos": {nanos}}}"#
+                ),
+            )
+        }
+        // Unknown days and inputs a day can't solve look the same from here
+        Err(e) => error(422, &format!("{e:#}")),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, String) {
+    (status, format!(r#"{{"error": {}}}"#, json_string(message)))
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Answers requests one at a time until the process is stopped
+#[cfg(feature = "serve")]
+pub fn serve(port: u16) -> anyhow::Result<()> {
+    let server = tiny_http::Server::http(("0.0.0.0", port))
+        .map_err(|e| anyhow::anyhow!("Could not listen on port {port}: {e}"))?;
+    println!("Listening on http://localhost:{port}, POST inputs to /solve/{{day}}/{{level}}");
+    let content_type = tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, json) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => respond(request.method().as_str(), request.url(), &body),
+            Err(e) => error(400, &format!("Could not read the body: {e}")),
+        };
+        println!("{} {} -> {status}", request.method(), request.url());
+        let response = tiny_http::Response::from_string(json)
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            println!("Could not send the response: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "day01")]
+    #[test]
+    fn solves_posted_input() {
+        let input = include_str!("./days/test_input/day1.txt");
+        let (status, body) = respond("POST", "/solve/1/2", input);
+        assert_eq!(status, 200);
+        assert!(body.starts_with(
+            r#"{"day": 1, "level": 2, "answer": "45000", "kind": "number", "nanos": "#
+        ));
+    }
+
+    #[cfg(feature = "day05")]
+    #[test]
+    fn solves_posted_text_answers() {
+        let input = include_str!("./days/test_input/day5.txt");
+        let (_, body) = respond("POST", "/solve/5/1/", input);
+        assert!(body.contains(r#""answer": "CMZ", "kind": "text""#));
+    }
+
+    #[test]
+    fn reports_errors_as_json() {
+        assert_eq!(respond("GET", "/solve/1/1", "").0, 405);
+        assert_eq!(respond("POST", "/answers", "").0, 404);
+        let (status, body) = respond("POST", "/solve/1/3", "");
+        assert_eq!(
+            (status, body.as_str()),
+            (400, r#"{"error": "Expected one of 1, 2"}"#)
+        );
+        assert_eq!(respond("POST", "/solve/one/1", "").0, 400);
+        assert_eq!(respond("POST", "/solve/40/1", "").0, 422);
+        assert_eq!(json_string("a \"b\"\n\u{1}"), r#""a \"b\"\n\u0001""#);
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{ArrowError, Field as ArrowField, Schema};
+
+use crate::util::export::{Field, Table};
+
+// An exported table as an Arrow record batch, for slicing results in
+// process, or in polars, which takes record batches as they are. A column
+// of only integers or only floats keeps its type, any other becomes text,
+// and one without rows Int64.
+pub fn record_batch(table: &Table) -> Result<RecordBatch, ArrowError> {
+    let (fields, columns): (Vec<_>, Vec<_>) = table
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let cells = table.rows.iter().map(|row| &row[i]).collect::<Vec<_>>();
+            let column = column(&cells);
+            let field = ArrowField::new(*name, column.data_type().clone(), false);
+            (field, column)
+        })
+        .unzip();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+fn column(cells: &[&Field]) -> ArrayRef {
+    let ints = cells
+        .iter()
+        .map(|cell| match cell {
+            Field::Int(value) => Some(*value),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    if let Some(ints) = ints {
+        return Arc::new(Int64Array::from(ints));
+    }
+    let floats = cells
+        .iter()
+        .map(|cell| match cell {
+            Field::Float(value) => Some(*value),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    if let Some(floats) = floats {
+        return Arc::new(Float64Array::from(floats));
+    }
+    Arc::new(StringArray::from(
+        cells
+            .iter()
+            .map(|cell| cell.to_string())
+            .collect::<Vec<_>>(),
+    ))
+}
+
+// The measurements aoc bench kept in bench-history.tsv
+pub fn run_history(history: &str) -> anyhow::Result<RecordBatch> {
+    let history = crate::util::bench::parse_history(history)?;
+    Ok(record_batch(&Table::from_rows(&history))?)
+}
+
+// Items each monkey inspected in each of day 11's first 20 rounds
+#[cfg(feature = "day11")]
+pub fn monkey_inspections(input: &str) -> Result<RecordBatch, ArrowError> {
+    record_batch(&Table::from_rows(&crate::days::day11::inspection_rows(
+        input,
+    )))
+}
+
+// Grains of day 14's sand settled in each row, with or without the floor
+#[cfg(feature = "day14")]
+pub fn sand_rows(input: &str, floor: bool) -> Result<RecordBatch, ArrowError> {
+    record_batch(&Table::from_rows(&crate::days::day14::sand_per_row(
+        input, floor,
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::Array;
+    use arrow_schema::DataType;
+
+    use super::*;
+
+    #[test]
+    fn types_columns() {
+        let table = Table {
+            columns: &["n", "share", "name", "mixed"],
+            rows: vec![
+                vec![1.into(), 0.5.into(), "a".to_string().into(), 2.into()],
+                vec![3.into(), 1.5.into(), "b".to_string().into(), 2.5.into()],
+            ],
+        };
+        let batch = record_batch(&table).unwrap();
+        let types = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                DataType::Int64,
+                DataType::Float64,
+                DataType::Utf8,
+                DataType::Utf8
+            ]
+        );
+        let n = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(n.values(), &[1, 3]);
+        assert_eq!(batch.num_rows(), 2);
+    }
+
+    #[test]
+    fn reads_history() {
+        let batch = run_history("1\t19\t500\n2\t1\t10\n").unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let day = batch.column_by_name("day").unwrap();
+        let day = day.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(day.values(), &[19, 1]);
+        assert_eq!(day.len(), 2);
+    }
+
+    #[cfg(feature = "day14")]
+    #[test]
+    fn counts_sand_per_row() {
+        let batch = sand_rows(include_str!("../days/test_input/day14.txt"), false).unwrap();
+        let settled = batch.column_by_name("settled").unwrap();
+        let settled = settled.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(settled.values().iter().sum::<i64>(), 24);
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    fmt::{self, Display},
+    io::{ErrorKind, Write},
+};
+
+use anyhow::{anyhow, Context};
+
+use crate::{
+    error::Hint,
+    util::{day::Answer, infra::Level},
+};
+
+// Where aoc run --submit keeps the wrong answers the site said were too high
+// or too low, one tab separated `day, level, high|low, answer` line each
+pub const ANSWERS_FILE: &str = "answers.tsv";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bound {
+    pub day: u32,
+    pub level: Level,
+    pub hint: Hint,
+    pub answer: i128,
+}
+
+impl Bound {
+    pub fn to_line(&self) -> String {
+        let hint = match self.hint {
+            Hint::TooHigh => "high",
+            Hint::TooLow => "low",
+        };
+        format!("{}\t{}\t{hint}\t{}\n", self.day, self.level, self.answer)
+    }
+}
+
+pub fn parse_bounds(store: &str) -> anyhow::Result<Vec<Bound>> {
+    store
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let mut fields = line.split('\t');
+            let mut field = |name: &str| {
+                fields
+                    .next()
+                    .with_context(|| format!("line {}: no {name}", i + 1))
+            };
+            let day = field("day")?.parse()?;
+            let level = field("level")?.parse()?;
+            let hint = match field("hint")? {
+                "high" => Hint::TooHigh,
+                "low" => Hint::TooLow,
+                hint => return Err(anyhow!("line {}: {hint} is not high or low", i + 1)),
+            };
+            let answer = field("answer")?.parse()?;
+            Ok(Bound {
+                day,
+                level,
+                hint,
+                answer,
+            })
+        })
+        .collect()
+}
+
+// The bounds recorded so far, none before the first wrong answer
+pub fn read_bounds() -> anyhow::Result<Vec<Bound>> {
+    match std::fs::read_to_string(ANSWERS_FILE) {
+        Ok(store) => {
+            parse_bounds(&store).with_context(|| format!("Invalid answers in {ANSWERS_FILE}"))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).context(ANSWERS_FILE),
+    }
+}
+
+pub fn record(bound: &Bound) -> anyhow::Result<()> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(ANSWERS_FILE)
+        .and_then(|mut file| file.write_all(bound.to_line().as_bytes()))
+        .with_context(|| format!("Could not write {ANSWERS_FILE}"))
+}
+
+// Where a level's answer can still be, strictly between the largest answer
+// that was too low and the smallest that was too high
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Bracket {
+    pub above: Option<i128>,
+    pub below: Option<i128>,
+}
+
+impl Bracket {
+    pub fn of(bounds: &[Bound], day: u32, level: Level) -> Self {
+        bounds
+            .iter()
+            .filter(|bound| bound.day == day && bound.level == level)
+            .fold(Self::default(), |bracket, bound| match bound.hint {
+                Hint::TooLow => Self {
+                    above: bracket.above.max(Some(bound.answer)),
+                    ..bracket
+                },
+                Hint::TooHigh => Self {
+                    below: Some(bracket.below.map_or(bound.answer, |b| b.min(bound.answer))),
+                    ..bracket
+                },
+            })
+    }
+
+    // Text answers have no order, so they are always worth a try
+    pub fn admits(&self, answer: &Answer) -> bool {
+        match answer {
+            Answer::Number(n) => {
+                self.above.is_none_or(|above| *n > above)
+                    && self.below.is_none_or(|below| *n < below)
+            }
+            Answer::Text(_) => true,
+        }
+    }
+}
+
+impl Display for Bracket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.above, self.below) {
+            (Some(above), Some(below)) => write!(f, "above {above} and below {below}"),
+            (Some(above), None) => write!(f, "above {above}"),
+            (None, Some(below)) => write!(f, "below {below}"),
+            (None, None) => write!(f, "unbounded"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn narrows_the_bracket() {
+        let bounds = parse_bounds(
+            "1\t1\tlow\t100\n1\t1\thigh\t500\n1\t1\tlow\t200\n\n1\t2\thigh\t50\n1\t1\thigh\t900\n",
+        )
+        .unwrap();
+        assert_eq!(bounds[0].to_line(), "1\t1\tlow\t100\n");
+        let bracket = Bracket::of(&bounds, 1, Level::One);
+        assert_eq!(bracket.to_string(), "above 200 and below 500");
+        assert!(bracket.admits(&Answer::Number(201)));
+        assert!(!bracket.admits(&Answer::Number(200)));
+        assert!(!bracket.admits(&Answer::Number(600)));
+        assert!(bracket.admits(&Answer::Text("ABC".to_string())));
+        assert_eq!(Bracket::of(&bounds, 1, Level::Two).to_string(), "below 50");
+        assert_eq!(Bracket::of(&bounds, 2, Level::One), Bracket::default());
+        assert!(parse_bounds("1\t1\tclose\t5\n").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fmt::Write, time::Duration};
+
+use anyhow::Context;
+
+use crate::util::{
+    export::{Field, Row},
+    glyphs::Glyphs,
+};
+
+// Where aoc bench keeps its measurements, one tab separated
+// `unix seconds, day, nanoseconds` line per day and run
+pub const HISTORY_FILE: &str = "bench-history.tsv";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    pub timestamp: u64,
+    pub day: u32,
+    // Both levels, parsing included
+    pub elapsed: Duration,
+}
+
+impl Record {
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\n",
+            self.timestamp,
+            self.day,
+            self.elapsed.as_nanos()
+        )
+    }
+}
+
+impl Row for Record {
+    const COLUMNS: &'static [&'static str] = &["timestamp", "day", "nanoseconds"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            self.timestamp.into(),
+            (self.day as usize).into(),
+            (self.elapsed.as_nanos() as u64).into(),
+        ]
+    }
+}
+
+pub fn parse_history(history: &str) -> anyhow::Result<Vec<Record>> {
+    history
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let mut fields = line.split('\t');
+            let mut field = |name: &str| {
+                fields
+                    .next()
+                    .with_context(|| format!("line {}: no {name}", i + 1))
+            };
+            let timestamp = field("timestamp")?.parse()?;
+            let day = field("day")?.parse()?;
+            let nanos = field("nanoseconds")?.parse()?;
+            Ok(Record {
+                timestamp,
+                day,
+                elapsed: Duration::from_nanos(nanos),
+            })
+        })
+        .collect()
+}
+
+// The last `count` runs of `day`, oldest first
+pub fn trend(history: &[Record], day: u32, count: usize) -> Vec<Duration> {
+    let times = history
+        .iter()
+        .filter(|record| record.day == day)
+        .map(|record| record.elapsed)
+        .collect::<Vec<_>>();
+    times[times.len().saturating_sub(count)..].to_vec()
+}
+
+// One bar per time, scaled between the fastest and the slowest
+pub fn sparkline(times: &[Duration], glyphs: &Glyphs) -> String {
+    let (Some(min), Some(max)) = (times.iter().min(), times.iter().max()) else {
+        return String::new();
+    };
+    let top = glyphs.bars.len() - 1;
+    let range = (*max - *min).as_secs_f64();
+    times.iter().fold(String::new(), |mut line, time| {
+        let level = if range == 0.0 {
+            0
+        } else {
+            ((*time - *min).as_secs_f64() / range * top as f64).round() as usize
+        };
+        line.write_char(glyphs.bars[level]).unwrap();
+        line
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::glyphs;
+
+    #[test]
+    fn reads_history_and_trends() {
+        let history = parse_history("1\t19\t500\n2\t1\t10\n\n3\t19\t700\n4\t19\t600\n").unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[1].to_line(), "2\t1\t10\n");
+        let nanos = |ns: &[u64]| {
+            ns.iter()
+                .map(|&n| Duration::from_nanos(n))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(trend(&history, 19, 2), nanos(&[700, 600]));
+        assert_eq!(trend(&history, 19, 10), nanos(&[500, 700, 600]));
+        assert!(trend(&history, 2, 10).is_empty());
+        assert_eq!(
+            format!("{:#}", parse_history("1\t19").unwrap_err()),
+            "line 1: no nanoseconds"
+        );
+    }
+
+    #[test]
+    fn draws_sparklines() {
+        let times = [100, 800, 450, 100].map(Duration::from_millis);
+        assert_eq!(sparkline(&times, &glyphs::UNICODE), "▁█▅▁");
+        assert_eq!(sparkline(&times, &glyphs::ASCII), "_#=_");
+        assert_eq!(sparkline(&times[..1], &glyphs::ASCII), "_");
+        assert_eq!(sparkline(&[], &glyphs::ASCII), "");
+    }
+}
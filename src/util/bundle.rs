@@ -0,0 +1,215 @@
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use itertools::Itertools;
+
+// One day as a single file that compiles on its own, for sharing a solution
+// without the whole repo. The day module goes in as it is, together with
+// every module of the crate it uses, directly or through others, nested the
+// way they are in the crate so that their `crate::` paths still resolve.
+// Whole modules rather than single items, and without their tests. `read`
+// gets paths relative to the crate root, like "src/days/day13.rs".
+pub fn bundle(day: u32, read: impl Fn(&str) -> std::io::Result<String>) -> anyhow::Result<String> {
+    let read = |path: &str| {
+        read(path)
+            .map(|source| without_tests(&source))
+            .with_context(|| format!("Could not read {path}"))
+    };
+    let day_source = read(&format!("src/days/day{day}.rs"))?;
+    let mut util = BTreeSet::new();
+    let mut error = false;
+    let mut pending = crate_paths(&day_source);
+    let mut util_sources = Vec::new();
+    while let Some(path) = pending.pop() {
+        let module = match path.as_slice() {
+            [root, ..] if root == "error" => {
+                error = true;
+                continue;
+            }
+            [root, ..] if root == "prelude" => "prelude".to_string(),
+            [root, module, ..] if root == "util" => module.clone(),
+            _ => continue,
+        };
+        if util.insert(module.clone()) {
+            let source = read(&format!("src/util/{module}.rs"))?;
+            pending.extend(crate_paths(&source));
+            util_sources.push((module, source));
+        }
+    }
+    let error_source = error.then(|| read("src/error.rs")).transpose()?;
+    let dependencies = dependencies(
+        &read("Cargo.toml")?,
+        util_sources
+            .iter()
+            .map(|(_, source)| source)
+            .chain(&error_source)
+            .chain([&day_source]),
+    );
+
+    let mut out = format!(
+        "// Day {day} of Advent of Code 2022, bundled by aoc export-day. Solves both\n\
+         // levels for the input on stdin. Needs edition 2021 and these in\n\
+         // Cargo.toml's [dependencies]:\n"
+    );
+    for dependency in dependencies {
+        out += &format!("//   {dependency}\n");
+    }
+    out += "\n#![allow(dead_code, unused_imports, unexpected_cfgs)]\n\n";
+    out += "use util::{day::Day, prelude};\n\n";
+    out += &format!(
+        "fn main() {{\n    \
+             let mut input = String::new();\n    \
+             std::io::Read::read_to_string(&mut std::io::stdin(), &mut input).unwrap();\n    \
+             let parsed = days::day{day}::Solver::parse(&input);\n    \
+             println!(\"Level 1: {{}}\", days::day{day}::Solver::level1(&parsed));\n    \
+             println!(\"Level 2: {{}}\", days::day{day}::Solver::level2(&parsed));\n\
+         }}\n\n"
+    );
+    out += &format!("mod days {{\n    pub mod day{day} {{\n");
+    out += &indented(&day_source, 2);
+    out += "    }\n}\n";
+    if let Some(source) = error_source {
+        out += "\nmod error {\n";
+        out += &indented(&source, 1);
+        out += "}\n";
+    }
+    out += "\nmod util {\n";
+    for (module, source) in util_sources.iter().sorted() {
+        out += &format!("    pub mod {module} {{\n");
+        out += &indented(source, 2);
+        out += "    }\n";
+    }
+    out += "}\n";
+    Ok(out)
+}
+
+// The source up to its test module, which the repo keeps last in each file
+fn without_tests(source: &str) -> String {
+    match source.find("#[cfg(test)]\nmod test {") {
+        Some(start) => source[..start].trim_end().to_string() + "\n",
+        None => source.to_string(),
+    }
+}
+
+fn indented(source: &str, depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    source
+        .lines()
+        .map(|line| match line {
+            "" => "\n".to_string(),
+            line => format!("{indent}{line}\n"),
+        })
+        .collect()
+}
+
+// The first two segments of every `crate::` path, with use trees like
+// `crate::util::{day::Day, grid::Grid}` split into one path each
+fn crate_paths(source: &str) -> Vec<Vec<String>> {
+    let mut paths = Vec::new();
+    for (start, _) in source.match_indices("crate::") {
+        let mut rest = &source[start + "crate::".len()..];
+        use_tree(&mut rest, Vec::new(), &mut paths);
+    }
+    paths.into_iter().unique().collect()
+}
+
+fn use_tree(rest: &mut &str, prefix: Vec<String>, paths: &mut Vec<Vec<String>>) {
+    *rest = rest.trim_start();
+    if let Some(group) = rest.strip_prefix('{') {
+        *rest = group;
+        loop {
+            use_tree(rest, prefix.clone(), paths);
+            *rest = rest.trim_start();
+            match rest.chars().next() {
+                Some(',') => *rest = &rest[1..],
+                Some('}') => {
+                    *rest = &rest[1..];
+                    return;
+                }
+                _ => return,
+            }
+            // A trailing comma before the closing brace
+            if let Some(after) = rest.trim_start().strip_prefix('}') {
+                *rest = after;
+                return;
+            }
+        }
+    }
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '*'))
+        .unwrap_or(rest.len());
+    let mut path = prefix;
+    path.push(rest[..end].to_string());
+    *rest = &rest[end..];
+    match rest.strip_prefix("::") {
+        Some(after) => {
+            *rest = after;
+            use_tree(rest, path, paths);
+        }
+        None => {
+            path.truncate(2);
+            paths.push(path);
+        }
+    }
+}
+
+// The [dependencies] lines of `manifest` for the crates `sources` name,
+// except optional ones
+fn dependencies<'a>(manifest: &str, sources: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let sources = sources.collect_vec();
+    manifest
+        .lines()
+        .skip_while(|line| line.trim() != "[dependencies]")
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| {
+            // Only used behind features, which are all off in the bundle
+            if line.contains("optional = true") {
+                return None;
+            }
+            let (name, _) = line.split_once(" = ")?;
+            let ident = name.replace('-', "_") + "::";
+            let used = sources.iter().any(|source| {
+                source.match_indices(&ident).any(|(i, _)| {
+                    !source[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ':')
+                })
+            });
+            used.then(|| line.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_use_trees() {
+        let source = "use crate::{\n    prelude::*,\n    util::{day::Day, nested::{nested_list, Nested},},\n};\n\
+            fn f() { crate::error::AocError::Io; }";
+        assert_eq!(
+            crate_paths(source),
+            [
+                vec!["prelude", "*"],
+                vec!["util", "day"],
+                vec!["util", "nested"],
+                vec!["error", "AocError"],
+            ]
+        );
+    }
+
+    #[test]
+    fn bundles_a_day_with_what_it_uses() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let bundle = bundle(13, |path| std::fs::read_to_string(format!("{root}/{path}"))).unwrap();
+        assert!(bundle.contains("//   nom = \"7.1.1\"\n"));
+        assert!(!bundle.contains("//   notify"));
+        assert!(bundle.contains("\n    pub mod day13 {\n"));
+        // Through the prelude and day.rs
+        for module in ["prelude", "day", "nested", "grid", "infra"] {
+            assert!(bundle.contains(&format!("\n    pub mod {module} {{\n")));
+        }
+        assert!(!bundle.contains("pub mod leaderboard"));
+        assert!(!bundle.contains("#[cfg(test)]\n"));
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+use crate::util::{math::log_log_slope, random::Rng};
+
+// Growth exponents above this fail `assert_scales_subquadratically`. Linear
+// and n log n code stays well below, quadratic code lands near 2. No margin
+// survives preemption on a loaded machine, so the tests timing solvers are
+// ignored by default and meant for `cargo test -- --ignored --test-threads=1`.
+pub const MAX_EXPONENT: f64 = 1.5;
+
+// Times `f` on an input from `gen` for each of `sizes`, the fastest of a
+// few runs each, and panics if the runtime grows like size^k for k above
+// `MAX_EXPONENT`. The sizes should span at least a factor of ten, and be
+// large enough that the biggest takes a few milliseconds.
+pub fn assert_scales_subquadratically<I, T>(
+    f: impl Fn(&I) -> T,
+    gen: impl Fn(&mut Rng, usize) -> I,
+    sizes: &[usize],
+) {
+    let mut rng = Rng::new(2022);
+    let points = sizes
+        .iter()
+        .map(|&size| {
+            let input = gen(&mut rng, size);
+            let best = (0..5)
+                .map(|_| {
+                    let start = Instant::now();
+                    std::hint::black_box(f(&input));
+                    start.elapsed()
+                })
+                .min()
+                .unwrap_or(Duration::ZERO);
+            (size as f64, best.as_secs_f64().max(1e-9))
+        })
+        .collect::<Vec<_>>();
+    let exponent = log_log_slope(&points).expect("need at least two different sizes");
+    assert!(
+        exponent <= MAX_EXPONENT,
+        "runtime grows like size^{exponent:.2}, timings (size, seconds): {points:?}"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[ignore = "timing based and flaky under load, run alone with --ignored"]
+    fn accepts_linear() {
+        assert_scales_subquadratically(
+            |v: &Vec<u64>| v.iter().sum::<u64>(),
+            |rng, size| (0..size).map(|_| rng.next_u64() % 100).collect(),
+            &[10_000, 40_000, 160_000],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "runtime grows like size^")]
+    fn rejects_quadratic() {
+        assert_scales_subquadratically(
+            |v: &Vec<u64>| {
+                v.iter()
+                    .map(|a| v.iter().filter(|b| a < b).count())
+                    .sum::<usize>()
+            },
+            |rng, size| (0..size).map(|_| rng.next_u64()).collect(),
+            &[200, 800, 3_200],
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context};
+use itertools::Itertools;
+use pico_args::Arguments;
+use serde::Deserialize;
+
+use crate::util::glyphs::{self, GlyphChoice, Glyphs};
+
+// Settings shared by the aoc commands. Each one is taken from the first of
+//   1. a command line flag, like --year 2022
+//   2. an environment variable, like AOC_YEAR=2022
+//   3. aoc.toml in the current directory, or the file given by --config or AOC_CONFIG
+//   4. the default
+// aoc.toml is TOML with these keys, each a string or a number:
+//   year = 2022
+//   session_file = "~/.config/aoc/session"
+//   profile_seconds = 5
+//   runtime_budget_ms = 1000
+//   day_budgets_ms = "16:250, 19:400"
+//   color = "never"
+//   webhook = "https://example.com/hook"
+//   leaderboard = 123456
+//   glyphs = "ascii"
+// SESSION overrides session_file with the session cookie itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub year: u32,
+    pub session_file: Option<PathBuf>,
+    // How long aoc profile samples when --seconds isn't given
+    pub profile_seconds: f64,
+    // How long one level may take before it counts as too slow
+    pub runtime_budget: Option<Duration>,
+    // Budgets for single days, instead of runtime_budget
+    pub day_budgets: BTreeMap<u32, Duration>,
+    pub color: ColorChoice,
+    // Where to post notifications, like leaderboard changes
+    pub webhook: Option<String>,
+    // The id of the private leaderboard aoc leaderboard shows
+    pub leaderboard: Option<u64>,
+    // What drawings like day 14's cave are made of
+    pub glyphs: GlyphChoice,
+    // Where each setting came from, in `KEYS` order, for aoc config show
+    sources: [Source; KEYS.len()],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    // Only when writing to a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(anyhow!("Expected one of auto, always, never")),
+        }
+    }
+}
+
+impl Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File,
+    Env,
+    Flag,
+}
+
+// Config file key, environment variable and flag for each setting
+const KEYS: [(&str, &str, &str); 9] = [
+    ("year", "AOC_YEAR", "--year"),
+    ("session_file", "AOC_SESSION_FILE", "--session-file"),
+    (
+        "profile_seconds",
+        "AOC_PROFILE_SECONDS",
+        "--profile-seconds",
+    ),
+    (
+        "runtime_budget_ms",
+        "AOC_RUNTIME_BUDGET_MS",
+        "--runtime-budget-ms",
+    ),
+    ("day_budgets_ms", "AOC_DAY_BUDGETS_MS", "--day-budgets-ms"),
+    ("color", "AOC_COLOR", "--color"),
+    ("webhook", "AOC_WEBHOOK", "--webhook"),
+    ("leaderboard", "AOC_LEADERBOARD", "--leaderboard"),
+    ("glyphs", "AOC_GLYPHS", "--glyphs"),
+];
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            year: 2022,
+            session_file: None,
+            profile_seconds: 2.0,
+            runtime_budget: None,
+            day_budgets: BTreeMap::new(),
+            color: ColorChoice::Auto,
+            webhook: None,
+            leaderboard: None,
+            glyphs: GlyphChoice::Auto,
+            sources: [Source::Default; KEYS.len()],
+        }
+    }
+}
+
+impl Config {
+    // Takes the config flags out of `pargs` and merges them with the
+    // environment and config file
+    pub fn resolve(pargs: &mut Arguments) -> anyhow::Result<Self> {
+        let path: Option<PathBuf> = pargs.opt_value_from_str("--config")?;
+        let (path, required) =
+            match path.or_else(|| std::env::var_os("AOC_CONFIG").map(PathBuf::from)) {
+                Some(path) => (path, true),
+                None => (PathBuf::from("aoc.toml"), false),
+            };
+        let file = match std::fs::read_to_string(&path) {
+            Ok(file) => file,
+            Err(_) if !required => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Could not read {}", path.display())),
+        };
+        let mut flags = Vec::new();
+        for (key, _, flag) in KEYS {
+            if let Some(value) = pargs.opt_value_from_str::<_, String>(flag)? {
+                flags.push((key, value));
+            }
+        }
+        Self::merge(&file, |var| std::env::var(var).ok(), &flags)
+            .with_context(|| format!("Invalid configuration, config file {}", path.display()))
+    }
+
+    // Applies the file, then the variables `env` knows, then the flags
+    pub fn merge(
+        file: &str,
+        env: impl Fn(&str) -> Option<String>,
+        flags: &[(&str, String)],
+    ) -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        let settings: BTreeMap<String, Setting> = toml::from_str(file)?;
+        for (key, value) in settings {
+            config.set(&key, &value.to_string(), Source::File)?;
+        }
+        for (key, var, _) in KEYS {
+            if let Some(value) = env(var) {
+                config.set(key, &value, Source::Env).context(var)?;
+            }
+        }
+        for (key, value) in flags {
+            config.set(key, value, Source::Flag)?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str, source: Source) -> anyhow::Result<()> {
+        let Some(index) = KEYS.iter().position(|(k, _, _)| *k == key) else {
+            bail!("unknown setting {key}");
+        };
+        let invalid = || format!("invalid {key} {value:?}");
+        match key {
+            "year" => self.year = value.parse().with_context(invalid)?,
+            "session_file" => self.session_file = Some(expand_home(value)),
+            "profile_seconds" => self.profile_seconds = value.parse().with_context(invalid)?,
+            "runtime_budget_ms" => {
+                let millis = value.parse().with_context(invalid)?;
+                self.runtime_budget = Some(Duration::from_millis(millis));
+            }
+            "day_budgets_ms" => self.day_budgets = parse_budgets(value).with_context(invalid)?,
+            "color" => self.color = value.parse().with_context(invalid)?,
+            "webhook" => self.webhook = Some(value.to_string()),
+            "leaderboard" => self.leaderboard = Some(value.parse().with_context(invalid)?),
+            "glyphs" => self.glyphs = value.parse().with_context(invalid)?,
+            _ => unreachable!("{key} is in KEYS"),
+        }
+        self.sources[index] = source;
+        Ok(())
+    }
+
+    // The session cookie from SESSION, or else the session file
+    pub fn session(&self) -> anyhow::Result<String> {
+        if let Ok(session) = std::env::var("SESSION") {
+            return Ok(session);
+        }
+        let path = self
+            .session_file
+            .as_deref()
+            .context("SESSION or session_file must be set")?;
+        let session = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read session file {}", path.display()))?;
+        Ok(session.trim().to_string())
+    }
+
+    // How long both levels of `day` may take together
+    pub fn budget(&self, day: u32) -> Option<Duration> {
+        self.day_budgets.get(&day).copied().or(self.runtime_budget)
+    }
+
+    pub fn use_color(&self) -> bool {
+        match self.color {
+            ColorChoice::Auto => std::io::stdout().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+
+    pub fn glyphs(&self) -> Glyphs {
+        match self.glyphs {
+            GlyphChoice::Auto if unicode_locale(|var| std::env::var(var).ok()) => glyphs::UNICODE,
+            GlyphChoice::Auto | GlyphChoice::Ascii => glyphs::ASCII,
+            GlyphChoice::Unicode => glyphs::UNICODE,
+        }
+    }
+
+    // `key = value  # source` lines, the effective configuration
+    pub fn show(&self) -> String {
+        let show_path = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|path| format!("{:?}", path.display().to_string()))
+        };
+        let values = [
+            Some(self.year.to_string()),
+            show_path(&self.session_file),
+            Some(self.profile_seconds.to_string()),
+            self.runtime_budget
+                .map(|budget| budget.as_millis().to_string()),
+            (!self.day_budgets.is_empty()).then(|| {
+                let budgets = self
+                    .day_budgets
+                    .iter()
+                    .map(|(day, budget)| format!("{day}:{}", budget.as_millis()))
+                    .join(", ");
+                format!("{budgets:?}")
+            }),
+            Some(format!("{:?}", self.color.to_string())),
+            self.webhook.as_ref().map(|webhook| format!("{webhook:?}")),
+            self.leaderboard.map(|id| id.to_string()),
+            Some(format!("{:?}", self.glyphs.to_string())),
+        ];
+        KEYS.iter()
+            .zip(values)
+            .zip(self.sources)
+            .map(|(((key, var, flag), value), source)| {
+                let from = match source {
+                    Source::Default => "default".to_string(),
+                    Source::File => "config file".to_string(),
+                    Source::Env => var.to_string(),
+                    Source::Flag => flag.to_string(),
+                };
+                match value {
+                    Some(value) => format!("{key} = {value}  # {from}\n"),
+                    None => format!("# {key} is not set\n"),
+                }
+            })
+            .collect()
+    }
+}
+
+// Whether the locale asks for UTF-8 output, going by the first of LC_ALL,
+// LC_CTYPE and LANG that `env` has
+pub fn unicode_locale(env: impl Fn(&str) -> Option<String>) -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|var| env(var).filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+// `day:milliseconds` pairs separated by commas
+fn parse_budgets(value: &str) -> anyhow::Result<BTreeMap<u32, Duration>> {
+    value
+        .split(',')
+        .filter(|budget| !budget.trim().is_empty())
+        .map(|budget| {
+            let (day, millis) = budget
+                .split_once(':')
+                .context("expected day:milliseconds")?;
+            let millis = millis.trim().parse()?;
+            Ok((day.trim().parse()?, Duration::from_millis(millis)))
+        })
+        .collect()
+}
+
+// A value in aoc.toml, which `Config::set` parses like the same value given
+// as a flag or environment variable
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Setting {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Setting::Integer(n) => n.fmt(f),
+            Setting::Float(x) => x.fmt(f),
+            Setting::Text(text) => text.fmt(f),
+        }
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILE: &str = "\
+# Shared settings
+year = 2021
+profile_seconds = 0.5  # quick
+color = \"never\"
+webhook = \"https://example.com/#hook\"
+day_budgets_ms = \"19:400,16:20\"
+";
+
+    #[test]
+    fn flags_beat_env_beat_file() {
+        let env = |var: &str| (var == "AOC_YEAR").then(|| "2020".to_string());
+        let flags = [("year", "2019".to_string())];
+        let config = Config::merge(FILE, env, &flags).unwrap();
+        assert_eq!(config.year, 2019);
+        assert_eq!(config.sources[0], Source::Flag);
+        let config = Config::merge(FILE, env, &[]).unwrap();
+        assert_eq!(config.year, 2020);
+        assert_eq!(config.profile_seconds, 0.5);
+        assert_eq!(config.color, ColorChoice::Never);
+        assert!(!config.use_color());
+        let config = Config::merge(FILE, |_| None, &[]).unwrap();
+        assert_eq!(config.year, 2021);
+        assert_eq!(Config::merge("", |_| None, &[]).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn shows_sources() {
+        let env = |var: &str| (var == "AOC_RUNTIME_BUDGET_MS").then(|| "250".to_string());
+        let config = Config::merge(FILE, env, &[]).unwrap();
+        assert_eq!(config.runtime_budget, Some(Duration::from_millis(250)));
+        assert_eq!(config.budget(19), Some(Duration::from_millis(400)));
+        assert_eq!(config.budget(1), Some(Duration::from_millis(250)));
+        assert_eq!(
+            config.show(),
+            "\
+year = 2021  # config file
+# session_file is not set
+profile_seconds = 0.5  # config file
+runtime_budget_ms = 250  # AOC_RUNTIME_BUDGET_MS
+day_budgets_ms = \"16:20, 19:400\"  # config file
+color = \"never\"  # config file
+webhook = \"https://example.com/#hook\"  # config file
+# leaderboard is not set
+glyphs = \"auto\"  # default
+"
+        );
+    }
+
+    #[test]
+    fn detects_unicode_locales() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |var: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == var)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert!(unicode_locale(env(&[("LANG", "en_US.UTF-8")])));
+        assert!(unicode_locale(env(&[
+            ("LC_ALL", ""),
+            ("LANG", "de_DE.utf8")
+        ])));
+        assert!(!unicode_locale(env(&[
+            ("LC_ALL", "C"),
+            ("LANG", "en_US.UTF-8")
+        ])));
+        assert!(!unicode_locale(env(&[])));
+    }
+
+    #[test]
+    fn rejects_bad_settings() {
+        let error = |file: &str| format!("{:#}", Config::merge(file, |_| None, &[]).unwrap_err());
+        assert_eq!(
+            error("year = \"soon\""),
+            "invalid year \"soon\": invalid digit found in string"
+        );
+        assert_eq!(
+            error("year = 2022.5"),
+            "invalid year \"2022.5\": invalid digit found in string"
+        );
+        assert_eq!(error("\nyears = 2022"), "unknown setting years");
+        for bad in [
+            "year",
+            "webhook = \"http",
+            "color = \"never\" always",
+            "year = soon",
+        ] {
+            assert!(
+                error(bad).starts_with("TOML parse error at line 1"),
+                "{bad}"
+            );
+        }
+        assert!(error("\n\nyear = [2022]").starts_with("TOML parse error at line 3"));
+        assert_eq!(
+            error("day_budgets_ms = \"19=400\""),
+            "invalid day_budgets_ms \"19=400\": expected day:milliseconds"
+        );
+        let env = |_: &str| Some("sometimes".to_string());
+        let error = Config::merge("", env, &[]).unwrap_err();
+        assert!(format!("{error:#}").starts_with("AOC_YEAR: invalid year"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::{hash::Hash, ops::AddAssign};
+
+use itertools::Itertools;
+
+use crate::util::hash::FastHashMap;
+
+// How often each item turned up. Items whose count drops to zero are
+// forgotten, so `len` is the number of different items present.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter<T: Hash + Eq> {
+    counts: FastHashMap<T, usize>,
+}
+
+impl<T: Hash + Eq> Default for Counter<T> {
+    fn default() -> Self {
+        Self {
+            counts: FastHashMap::default(),
+        }
+    }
+}
+
+impl<T: Hash + Eq> Counter<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The count after adding
+    pub fn add(&mut self, item: T) -> usize {
+        self.add_n(item, 1)
+    }
+
+    // Adding none leaves an absent item absent
+    pub fn add_n(&mut self, item: T, n: usize) -> usize {
+        if n == 0 {
+            return self.get(&item);
+        }
+        let count = self.counts.entry(item).or_default();
+        *count += n;
+        *count
+    }
+
+    // The count after removing one, which stays at zero for absent items
+    pub fn remove(&mut self, item: &T) -> usize {
+        let Some(count) = self.counts.get_mut(item) else {
+            return 0;
+        };
+        *count -= 1;
+        let count = *count;
+        if count == 0 {
+            self.counts.remove(item);
+        }
+        count
+    }
+
+    pub fn get(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    // Of all items together
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> + '_ {
+        self.counts.iter().map(|(item, count)| (item, *count))
+    }
+
+    // The `k` most frequent items, most frequent first and the smaller item
+    // first among equally frequent ones
+    pub fn most_common(&self, k: usize) -> Vec<(&T, usize)>
+    where
+        T: Ord,
+    {
+        self.iter()
+            .sorted_by(|(a, m), (b, n)| n.cmp(m).then(a.cmp(b)))
+            .take(k)
+            .collect()
+    }
+}
+
+impl<T: Hash + Eq> AddAssign for Counter<T> {
+    fn add_assign(&mut self, other: Self) {
+        for (item, count) in other.counts {
+            self.add_n(item, count);
+        }
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counts_and_merges() {
+        let mut counter: Counter<char> = "mississippi".chars().collect();
+        assert_eq!(counter.most_common(2), [(&'i', 4), (&'s', 4)]);
+        assert_eq!((counter.get(&'p'), counter.get(&'x')), (2, 0));
+        assert_eq!((counter.len(), counter.total()), (4, 11));
+        counter += "map".chars().collect();
+        assert_eq!(counter.most_common(3), [(&'i', 4), (&'s', 4), (&'p', 3)]);
+        assert_eq!(counter.remove(&'a'), 0);
+        assert_eq!(counter.remove(&'a'), 0);
+        assert_eq!(counter.len(), 4);
+        assert_eq!(counter.add('z'), 1);
+        assert_eq!(counter.most_common(10).last(), Some(&(&'z', 1)));
+    }
+
+    #[test]
+    fn adding_none_leaves_no_entry() {
+        let mut counter = Counter::new();
+        assert_eq!(counter.add_n('a', 0), 0);
+        assert!(counter.is_empty());
+        counter.add_n('b', 2);
+        assert_eq!(counter.add_n('b', 0), 2);
+        assert_eq!(counter.most_common(5), [(&'b', 2)]);
+    }
+}
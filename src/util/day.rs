@@ -0,0 +1,327 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter, LowerExp},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use anyhow::bail;
+
+use crate::util::infra::Level;
+
+// A day's solution split into parsing and the two levels, so that running
+// both levels on the same input only parses it once. Levels that change
+// what they were given, like day 5's stacks, work on their own copy.
+pub trait Day: 'static {
+    type Parsed: 'static;
+
+    fn parse(input: &str) -> Self::Parsed;
+    fn level1(parsed: &Self::Parsed) -> String;
+    fn level2(parsed: &Self::Parsed) -> String;
+
+    // Both answers, overridden by days that get them from one pass faster
+    // than from two
+    fn both(parsed: &Self::Parsed) -> (Answer, Answer) {
+        (Self::level1(parsed).into(), Self::level2(parsed).into())
+    }
+}
+
+// A day that can tell how it arrives at its answers, one line per step with
+// the numbers of the input at hand, for aoc run --explain. From the raw
+// input, since the story often needs what parsing throws away, like names.
+pub trait Explain: Day {
+    fn explain(input: &str, level: Level) -> Vec<String>;
+}
+
+// One thing an input is made of, like a monkey or a sensor, with a key that
+// stays the same across inputs and the fields that may differ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub key: String,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key)?;
+        for (i, (name, value)) in self.fields.iter().enumerate() {
+            let separator = if i == 0 { ": " } else { ", " };
+            write!(f, "{separator}{name} {value}")?;
+        }
+        Ok(())
+    }
+}
+
+// A day whose inputs compare part by part, for aoc diff-input: what one
+// input has that the other hasn't, and the fields that changed in parts
+// with the same key
+pub trait DiffModel: Day {
+    fn parts(input: &str) -> anyhow::Result<Vec<Part>>;
+
+    // One line per difference, `-` for parts only in `old`, `+` for parts
+    // only in `new` and `~` for a changed field, in the order of `old` then
+    // of what `new` adds
+    fn diff(old: &str, new: &str) -> anyhow::Result<Vec<String>> {
+        let old = Self::parts(old)?;
+        let new = Self::parts(new)?;
+        let by_key: HashMap<_, _> = new.iter().map(|part| (&part.key, part)).collect();
+        let old_keys: HashSet<_> = old.iter().map(|part| &part.key).collect();
+        let mut lines = Vec::new();
+        for part in &old {
+            let Some(changed) = by_key.get(&part.key) else {
+                lines.push(format!("- {part}"));
+                continue;
+            };
+            for ((name, before), (_, after)) in part.fields.iter().zip(&changed.fields) {
+                if before != after {
+                    lines.push(format!("~ {} {name}: {before} -> {after}", part.key));
+                }
+            }
+        }
+        lines.extend(
+            new.iter()
+                .filter(|part| !old_keys.contains(&part.key))
+                .map(|part| format!("+ {part}")),
+        );
+        Ok(lines)
+    }
+}
+
+// Something about a day's parsed input that can be moved by an amount,
+// like the range of every sensor
+pub struct Param<P> {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub apply: fn(&mut P, i64),
+}
+
+// A day whose answers can be rerun with its parameters moved, for aoc
+// perturb, to see which parts of the input an answer hinges on
+pub trait Perturb: Day {
+    const PARAMS: &'static [Param<Self::Parsed>];
+}
+
+// The level's answer with the parameter called `name` moved by `delta`
+pub fn perturbed<D: Perturb>(
+    input: &str,
+    name: &str,
+    delta: i64,
+    level: Level,
+) -> anyhow::Result<Answer> {
+    let Some(param) = D::PARAMS.iter().find(|param| param.name == name) else {
+        let known = D::PARAMS
+            .iter()
+            .map(|param| format!("{} ({})", param.name, param.about))
+            .collect::<Vec<_>>();
+        bail!("No parameter {name}, expected one of {}", known.join(", "));
+    };
+    let mut parsed = D::parse(input);
+    (param.apply)(&mut parsed, delta);
+    Ok(match level {
+        Level::One => D::level1(&parsed),
+        Level::Two => D::level2(&parsed),
+    }
+    .into())
+}
+
+// A level's answer. `{}` shows it exactly as it is submitted, `{:#}` groups
+// the digits of numbers in thousands and `{:e}` (or `{:.3e}`) writes numbers
+// in scientific notation. Text answers always show as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl From<String> for Answer {
+    fn from(answer: String) -> Self {
+        match answer.parse::<i128>() {
+            // Only when the number prints back the same, so "007" stays text
+            Ok(n) if n.to_string() == answer => Answer::Number(n),
+            _ => Answer::Text(answer),
+        }
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) if f.alternate() => {
+                let digits = n.unsigned_abs().to_string();
+                let mut grouped = String::new();
+                for (i, digit) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % 3 == 0 {
+                        grouped.push(',');
+                    }
+                    grouped.push(digit);
+                }
+                let sign = if *n < 0 { "-" } else { "" };
+                write!(f, "{sign}{grouped}")
+            }
+            Answer::Number(n) => write!(f, "{n}"),
+            Answer::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+impl LowerExp for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => LowerExp::fmt(n, f),
+            Answer::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    // Spent in `Day::parse` on misses
+    pub parse_time: Duration,
+}
+
+// Parsed inputs by day and input, shared between levels within a run. Keyed
+// by the input itself so that inputs with colliding hashes never share an entry.
+#[derive(Default)]
+pub struct ParseCache {
+    entries: HashMap<TypeId, HashMap<String, Rc<dyn Any>>>,
+    pub stats: CacheStats,
+}
+
+impl ParseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parsed<D: Day>(&mut self, input: &str) -> Rc<D::Parsed> {
+        let entries = self.entries.entry(TypeId::of::<D>()).or_default();
+        if let Some(entry) = entries.get(input) {
+            self.stats.hits += 1;
+            return entry.clone().downcast().unwrap();
+        }
+        self.stats.misses += 1;
+        let start = Instant::now();
+        let parsed = Rc::new(D::parse(input));
+        self.stats.parse_time += start.elapsed();
+        entries.insert(input.to_string(), parsed.clone());
+        parsed
+    }
+}
+
+pub fn solve<D: Day>(input: &str, level: Level, cache: &mut ParseCache) -> Answer {
+    let parsed = cache.parsed::<D>(input);
+    match level {
+        Level::One => D::level1(&parsed),
+        Level::Two => D::level2(&parsed),
+    }
+    .into()
+}
+
+pub fn solve_both<D: Day>(input: &str, cache: &mut ParseCache) -> (Answer, Answer) {
+    D::both(&cache.parsed::<D>(input))
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::*;
+
+    thread_local! {
+        static PARSES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Sum;
+
+    impl Day for Sum {
+        type Parsed = Vec<i64>;
+
+        fn parse(input: &str) -> Vec<i64> {
+            PARSES.with(|parses| parses.set(parses.get() + 1));
+            input.lines().map(|line| line.parse().unwrap()).collect()
+        }
+
+        fn level1(parsed: &Vec<i64>) -> String {
+            parsed.iter().sum::<i64>().to_string()
+        }
+
+        fn level2(parsed: &Vec<i64>) -> String {
+            parsed.iter().product::<i64>().to_string()
+        }
+    }
+
+    impl DiffModel for Sum {
+        fn parts(input: &str) -> anyhow::Result<Vec<Part>> {
+            Ok(input
+                .lines()
+                .enumerate()
+                .map(|(i, line)| Part {
+                    key: format!("line {}", i + 1),
+                    fields: vec![("value", line.to_string())],
+                })
+                .collect())
+        }
+    }
+
+    impl Perturb for Sum {
+        const PARAMS: &'static [Param<Vec<i64>>] = &[Param {
+            name: "n",
+            about: "every number",
+            apply: |numbers, delta| numbers.iter_mut().for_each(|n| *n += delta),
+        }];
+    }
+
+    #[test]
+    fn perturbs_by_name() {
+        let answer = perturbed::<Sum>("2\n3", "n", 1, Level::Two).unwrap();
+        assert_eq!(answer, Answer::Number(12));
+        let error = perturbed::<Sum>("2", "m", 1, Level::One).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No parameter m, expected one of n (every number)"
+        );
+    }
+
+    #[test]
+    fn diffs_by_part() {
+        assert_eq!(
+            Sum::diff("2\n3", "2\n4\n5").unwrap(),
+            ["~ line 2 value: 3 -> 4", "+ line 3: value 5"]
+        );
+        assert_eq!(Sum::diff("2\n3", "2").unwrap(), ["- line 2: value 3"]);
+        assert!(Sum::diff("2", "2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_once_per_input() {
+        let mut cache = ParseCache::new();
+        assert_eq!(
+            solve::<Sum>("2\n3", Level::One, &mut cache),
+            Answer::Number(5)
+        );
+        assert_eq!(
+            solve::<Sum>("2\n3", Level::Two, &mut cache),
+            Answer::Number(6)
+        );
+        assert_eq!(solve::<Sum>("4", Level::Two, &mut cache), Answer::Number(4));
+        assert_eq!(PARSES.with(Cell::get), 2);
+        assert_eq!((cache.stats.hits, cache.stats.misses), (1, 2));
+        assert_eq!(cache.entries[&TypeId::of::<Sum>()].len(), 2);
+    }
+
+    #[test]
+    fn answer_formats() {
+        let big = Answer::from("29703395016".to_string());
+        assert_eq!(big, Answer::Number(29703395016));
+        assert_eq!(format!("{big}"), "29703395016");
+        assert_eq!(format!("{big:#}"), "29,703,395,016");
+        assert_eq!(format!("{big:.3e}"), "2.970e10");
+        assert_eq!(format!("{:#}", Answer::Number(-1234)), "-1,234");
+        assert_eq!(format!("{:#}", Answer::Number(999)), "999");
+        let text = Answer::from("RLFNRTNFB".to_string());
+        assert_eq!(format!("{text:#} {text:e}"), "RLFNRTNFB RLFNRTNFB");
+        assert_eq!(Answer::from("007".to_string()), Answer::Text("007".into()));
+    }
+}
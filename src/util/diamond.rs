@@ -0,0 +1,153 @@
+use crate::util::{flood::Point2, interval::Interval};
+
+// The cells within Manhattan distance `r` of `center`, like the range of a
+// day 15 sensor. Turning the plane by 45 degrees with u = x + y, v = x - y
+// makes a diamond an axis aligned square, where overlaps and gaps between
+// diamonds are plain interval arithmetic. Only (u, v) with u and v of the
+// same parity come from a cell, see `unrotate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diamond {
+    pub center: Point2,
+    pub r: u64,
+}
+
+// A diamond in rotated coordinates, or the overlap of several
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotatedSquare {
+    pub u: Interval,
+    pub v: Interval,
+}
+
+// Saturating, since puzzle coordinates can come close to the i64 bounds
+pub fn rotate((x, y): Point2) -> (i64, i64) {
+    (x.saturating_add(y), x.saturating_sub(y))
+}
+
+pub fn unrotate(u: i64, v: i64) -> Option<Point2> {
+    let (u, v) = (i128::from(u), i128::from(v));
+    if (u + v) % 2 != 0 {
+        return None;
+    }
+    let x = i64::try_from((u + v) / 2).ok()?;
+    let y = i64::try_from((u - v) / 2).ok()?;
+    Some((x, y))
+}
+
+impl Diamond {
+    pub fn new(center: Point2, r: u64) -> Self {
+        Self { center, r }
+    }
+
+    // The smallest diamond around `center` reaching `edge`, unless the two
+    // are further apart than a u64 holds
+    pub fn reaching(center: Point2, edge: Point2) -> Option<Self> {
+        let r = distance(center, edge)?;
+        Some(Self { center, r })
+    }
+
+    pub fn contains(&self, point: Point2) -> bool {
+        distance(self.center, point).is_some_and(|d| d <= self.r)
+    }
+
+    // The cells of row `y` inside the diamond
+    pub fn row(&self, y: i64) -> Option<Interval> {
+        let (x, center_y) = self.center;
+        let dx = self.r.checked_sub(center_y.abs_diff(y))?;
+        Some(Interval::new(
+            x.saturating_sub_unsigned(dx),
+            x.saturating_add_unsigned(dx),
+        ))
+    }
+
+    pub fn to_rotated_square(&self) -> RotatedSquare {
+        let (u, v) = rotate(self.center);
+        let around = |c: i64| {
+            Interval::new(
+                c.saturating_sub_unsigned(self.r),
+                c.saturating_add_unsigned(self.r),
+            )
+        };
+        RotatedSquare {
+            u: around(u),
+            v: around(v),
+        }
+    }
+
+    // Cells in both diamonds, as a rotated rectangle
+    pub fn intersection(&self, other: &Self) -> Option<RotatedSquare> {
+        self.to_rotated_square()
+            .intersection(&other.to_rotated_square())
+    }
+
+    // Whether the two share no cell but leave no cell between them along a
+    // diagonal edge, like two sensors boxing in day 15's distress beacon
+    pub fn gap_diagonal(&self, other: &Self) -> Option<i64> {
+        let (a, b) = (self.to_rotated_square(), other.to_rotated_square());
+        [(a, b), (b, a)]
+            .into_iter()
+            .find(|(below, above)| below.u.end.checked_add(2) == Some(above.u.start))
+            .map(|(below, _)| below.u.end + 1)
+    }
+}
+
+impl RotatedSquare {
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Some(Self {
+            u: self.u.intersection(&other.u)?,
+            v: self.v.intersection(&other.v)?,
+        })
+    }
+}
+
+fn distance((x, y): Point2, (x2, y2): Point2) -> Option<u64> {
+    x.abs_diff(x2).checked_add(y.abs_diff(y2))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotates_both_ways() {
+        for point in [(0, 0), (3, -2), (-7, 11), (i64::MAX / 2, i64::MIN / 2)] {
+            let (u, v) = rotate(point);
+            assert_eq!(unrotate(u, v), Some(point));
+        }
+        assert_eq!(unrotate(1, 2), None);
+    }
+
+    #[test]
+    fn squares_and_rows() {
+        let diamond = Diamond::reaching((8, 7), (2, 10)).unwrap();
+        assert_eq!(diamond.r, 9);
+        assert_eq!(diamond.row(7), Some(Interval::new(-1, 17)));
+        assert_eq!(diamond.row(16), Some(Interval::new(8, 8)));
+        assert_eq!(diamond.row(17), None);
+        let square = diamond.to_rotated_square();
+        assert_eq!(square.u, Interval::new(6, 24));
+        assert_eq!(square.v, Interval::new(-8, 10));
+        assert!(diamond.contains((8, 16)) && !diamond.contains((9, 16)));
+        assert_eq!(
+            Diamond::reaching((i64::MIN, i64::MIN), (i64::MAX, i64::MAX)),
+            None
+        );
+    }
+
+    #[test]
+    fn intersections_and_gaps() {
+        let a = Diamond::new((0, 0), 2);
+        let b = Diamond::new((3, 0), 2);
+        let overlap = a.intersection(&b).unwrap();
+        assert_eq!(
+            (overlap.u, overlap.v),
+            (Interval::new(1, 2), Interval::new(1, 2))
+        );
+        assert_eq!(unrotate(2, 2), Some((2, 0)));
+        assert_eq!(a.intersection(&Diamond::new((10, 0), 2)), None);
+        // u = x + y = 3 runs between a, ending at u = 2, and c, starting at 4
+        let c = Diamond::new((3, 3), 2);
+        assert_eq!(a.gap_diagonal(&c), Some(3));
+        assert_eq!(c.gap_diagonal(&a), Some(3));
+        assert_eq!(a.gap_diagonal(&b), None);
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt::{Display, Write};
+
+#[derive(Debug, Default)]
+pub struct Attributes(Vec<(String, String)>);
+
+impl Attributes {
+    pub fn attr(&mut self, key: &str, value: impl Display) -> &mut Self {
+        self.0.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+impl Display for Attributes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let attrs = self
+            .0
+            .iter()
+            .map(|(key, value)| format!("{key}={}", quote(value)))
+            .collect::<Vec<_>>();
+        write!(f, " [{}]", attrs.join(", "))
+    }
+}
+
+// Graphviz digraph built up node by node and edge by edge, in insertion order:
+//
+//     let mut graph = Digraph::new("example");
+//     graph.node(0).attr("label", "start");
+//     graph.edge(0, 1).attr("label", 3);
+//     graph.to_dot()
+#[derive(Debug)]
+pub struct Digraph {
+    name: String,
+    nodes: Vec<(String, Attributes)>,
+    edges: Vec<(String, String, Attributes)>,
+}
+
+impl Digraph {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn node(&mut self, id: impl Display) -> &mut Attributes {
+        self.nodes.push((id.to_string(), Attributes::default()));
+        &mut self.nodes.last_mut().unwrap().1
+    }
+
+    pub fn edge(&mut self, from: impl Display, to: impl Display) -> &mut Attributes {
+        self.edges
+            .push((from.to_string(), to.to_string(), Attributes::default()));
+        &mut self.edges.last_mut().unwrap().2
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph {} {{\n", id(&self.name));
+        for (node, attrs) in &self.nodes {
+            writeln!(dot, "    {}{attrs};", id(node)).unwrap();
+        }
+        for (from, to, attrs) in &self.edges {
+            writeln!(dot, "    {} -> {}{attrs};", id(from), id(to)).unwrap();
+        }
+        dot + "}\n"
+    }
+}
+
+// Plain identifiers and numbers can go unquoted, anything else is quoted
+fn id(s: &str) -> String {
+    let plain = !s.is_empty()
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let number = !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    if plain || number {
+        s.to_string()
+    } else {
+        quote(s)
+    }
+}
+
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serializes_nodes_then_edges() {
+        let mut graph = Digraph::new("files");
+        graph.node("/").attr("shape", "folder");
+        graph.node("a.txt").attr("label", "a.txt\n12 bytes");
+        graph.node(7);
+        graph.edge("/", "a.txt").attr("label", "say \"hi\"");
+        graph.edge(7, "x_1");
+        assert_eq!(
+            graph.to_dot(),
+            r#"digraph files {
+    "/" [shape="folder"];
+    "a.txt" [label="a.txt\n12 bytes"];
+    7;
+    "/" -> "a.txt" [label="say \"hi\""];
+    7 -> x_1;
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Display},
+    io::{self, Write},
+    str::FromStr,
+};
+
+use anyhow::anyhow;
+use serde_json::{Map, Value};
+
+// A value in an exported row, typed so that JSON lines keep numbers numbers
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<i64> for Field {
+    fn from(value: i64) -> Self {
+        Field::Int(value)
+    }
+}
+
+impl From<i32> for Field {
+    fn from(value: i32) -> Self {
+        Field::Int(value.into())
+    }
+}
+
+// Counts and timestamps, none of which come near i64::MAX
+impl From<usize> for Field {
+    fn from(value: usize) -> Self {
+        Field::Int(value as i64)
+    }
+}
+
+impl From<u64> for Field {
+    fn from(value: u64) -> Self {
+        Field::Int(value as i64)
+    }
+}
+
+impl From<f64> for Field {
+    fn from(value: f64) -> Self {
+        Field::Float(value)
+    }
+}
+
+impl From<String> for Field {
+    fn from(value: String) -> Self {
+        Field::Text(value)
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Int(value) => value.fmt(f),
+            Field::Float(value) => value.fmt(f),
+            Field::Text(value) => value.fmt(f),
+        }
+    }
+}
+
+impl From<&Field> for Value {
+    fn from(field: &Field) -> Self {
+        match field {
+            Field::Int(value) => (*value).into(),
+            // NaN and the infinities have no JSON number and become null
+            Field::Float(value) => (*value).into(),
+            Field::Text(value) => value.as_str().into(),
+        }
+    }
+}
+
+// Something exported one line per value, like a cycle of a CPU trace
+pub trait Row {
+    const COLUMNS: &'static [&'static str];
+    // One per column, in the same order
+    fn fields(&self) -> Vec<Field>;
+}
+
+// Rows of any one type, so that the solution table can hold exporters for
+// different days side by side
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub columns: &'static [&'static str],
+    pub rows: Vec<Vec<Field>>,
+}
+
+impl Table {
+    pub fn from_rows<R: Row>(rows: &[R]) -> Self {
+        Self {
+            columns: R::COLUMNS,
+            rows: rows.iter().map(Row::fields).collect(),
+        }
+    }
+
+    // A header line first, except for JSON lines where every line names
+    // its columns
+    pub fn write(&self, format: Format, out: &mut impl Write) -> io::Result<()> {
+        let header = self.columns.iter().map(|column| Cow::from(*column));
+        match format {
+            Format::Csv => {
+                writeln!(out, "{}", csv_line(header))?;
+                for row in &self.rows {
+                    writeln!(out, "{}", csv_line(row.iter().map(text)))?;
+                }
+            }
+            Format::Tsv => {
+                writeln!(out, "{}", tsv_line(header))?;
+                for row in &self.rows {
+                    writeln!(out, "{}", tsv_line(row.iter().map(text)))?;
+                }
+            }
+            Format::JsonLines => {
+                for row in &self.rows {
+                    let object: Map<String, Value> = self
+                        .columns
+                        .iter()
+                        .zip(row)
+                        .map(|(column, field)| (column.to_string(), field.into()))
+                        .collect();
+                    writeln!(out, "{}", Value::Object(object))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Tsv,
+    // One JSON object per line
+    JsonLines,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "jsonl" => Ok(Format::JsonLines),
+            _ => Err(anyhow!("Expected one of csv, tsv, jsonl")),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+            Format::JsonLines => "jsonl",
+        }
+        .fmt(f)
+    }
+}
+
+fn text(field: &Field) -> Cow<'_, str> {
+    match field {
+        Field::Text(value) => value.into(),
+        _ => field.to_string().into(),
+    }
+}
+
+// Quoted only when it has to be, with quotes doubled inside
+fn csv_line<'a>(fields: impl Iterator<Item = Cow<'a, str>>) -> String {
+    fields
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\"")).into()
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// TSV has no quoting, so tabs and line breaks are escaped the way pandas and
+// polars read them back
+fn tsv_line<'a>(fields: impl Iterator<Item = Cow<'a, str>>) -> String {
+    fields
+        .map(|field| {
+            if field.contains(['\\', '\t', '\n', '\r']) {
+                field
+                    .replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+                    .into()
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Visit(&'static str, usize, f64);
+
+    impl Row for Visit {
+        const COLUMNS: &'static [&'static str] = &["name", "count", "share"];
+
+        fn fields(&self) -> Vec<Field> {
+            vec![self.0.to_string().into(), self.1.into(), self.2.into()]
+        }
+    }
+
+    fn written(format: Format) -> String {
+        let table = Table::from_rows(&[Visit("plain", 1, 0.25), Visit("a, \"b\"\tc", 2, 0.75)]);
+        let mut out = Vec::new();
+        table.write(format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn writes_each_format() {
+        assert_eq!(
+            written(Format::Csv),
+            "name,count,share\nplain,1,0.25\n\"a, \"\"b\"\"\tc\",2,0.75\n"
+        );
+        assert_eq!(
+            written(Format::Tsv),
+            "name\tcount\tshare\nplain\t1\t0.25\na, \"b\"\\tc\t2\t0.75\n"
+        );
+        assert_eq!(
+            written(Format::JsonLines).lines().next(),
+            Some(r#"{"count":1,"name":"plain","share":0.25}"#)
+        );
+    }
+
+    #[test]
+    fn parses_formats() {
+        for format in [Format::Csv, Format::Tsv, Format::JsonLines] {
+            assert_eq!(format.to_string().parse::<Format>().unwrap(), format);
+        }
+        assert!("xlsx".parse::<Format>().is_err());
+    }
+}
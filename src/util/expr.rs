@@ -0,0 +1,141 @@
+use crate::util::rational::Rational;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Num(Rational),
+    Var(String),
+    Bin(Op, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn var(name: &str) -> Self {
+        Expr::Var(name.to_string())
+    }
+
+    pub fn bin(op: Op, left: Expr, right: Expr) -> Self {
+        Expr::Bin(op, Box::new(left), Box::new(right))
+    }
+
+    // None if the expression still contains a variable or divides by zero
+    pub fn eval(&self) -> Option<Rational> {
+        let (slope, offset) = self.linear("")?;
+        slope.is_zero().then_some(offset)
+    }
+
+    // Writes the expression as slope * var + offset, which fails if
+    // var appears nonlinearly, another variable appears, or a divisor is zero.
+    fn linear(&self, var: &str) -> Option<(Rational, Rational)> {
+        match self {
+            Expr::Num(x) => Some((Rational::ZERO, *x)),
+            Expr::Var(name) if name == var => Some((Rational::ONE, Rational::ZERO)),
+            Expr::Var(_) => None,
+            Expr::Bin(op, left, right) => {
+                let (a, b) = left.linear(var)?;
+                let (c, d) = right.linear(var)?;
+                match op {
+                    Op::Add => Some((a + c, b + d)),
+                    Op::Sub => Some((a - c, b - d)),
+                    Op::Mul if a.is_zero() => Some((b * c, b * d)),
+                    Op::Mul if c.is_zero() => Some((a * d, b * d)),
+                    Op::Mul => None,
+                    Op::Div if c.is_zero() => Some((a.checked_div(d)?, b.checked_div(d)?)),
+                    Op::Div => None,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    pub left: Expr,
+    pub right: Expr,
+}
+
+impl Equation {
+    // The unique value for var making both sides equal, if the equation is
+    // linear in var and has exactly one solution.
+    pub fn solve_for(&self, var: &str) -> Option<Rational> {
+        let (a, b) = self.left.linear(var)?;
+        let (c, d) = self.right.linear(var)?;
+        (d - b).checked_div(a - c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn num(x: i64) -> Expr {
+        Expr::Num(x.into())
+    }
+
+    #[test]
+    fn eval_keeps_fractions() {
+        let expr = Expr::bin(Op::Mul, Expr::bin(Op::Div, num(3), num(4)), num(8));
+        assert_eq!(expr.eval(), Some(6.into()));
+        assert_eq!(Expr::bin(Op::Div, num(1), num(0)).eval(), None);
+        assert_eq!(Expr::var("humn").eval(), None);
+    }
+
+    #[test]
+    fn solve_with_non_integer_intermediate() {
+        // (humn / 4) * 8 == 6, where humn / 4 = 3/4 must not be truncated to 0
+        let equation = Equation {
+            left: Expr::bin(
+                Op::Mul,
+                Expr::bin(Op::Div, Expr::var("humn"), num(4)),
+                num(8),
+            ),
+            right: num(6),
+        };
+        assert_eq!(equation.solve_for("humn"), Some(3.into()));
+    }
+
+    #[test]
+    fn solve_given_day21_example() {
+        // root: pppw = sjmn with pppw = (4 + 2 * (humn - 3)) / 4 and sjmn = 150
+        let left = Expr::bin(
+            Op::Div,
+            Expr::bin(
+                Op::Add,
+                num(4),
+                Expr::bin(
+                    Op::Mul,
+                    num(2),
+                    Expr::bin(Op::Sub, Expr::var("humn"), num(3)),
+                ),
+            ),
+            num(4),
+        );
+        let right = Expr::bin(Op::Mul, Expr::bin(Op::Sub, num(32), num(2)), num(5));
+        assert_eq!(Equation { left, right }.solve_for("humn"), Some(301.into()));
+    }
+
+    #[test]
+    fn solve_rejects_degenerate_systems() {
+        let square = Equation {
+            left: Expr::bin(Op::Mul, Expr::var("x"), Expr::var("x")),
+            right: num(4),
+        };
+        assert_eq!(square.solve_for("x"), None);
+        let always = Equation {
+            left: Expr::bin(Op::Add, Expr::var("x"), num(1)),
+            right: Expr::bin(Op::Sub, Expr::var("x"), num(-1)),
+        };
+        assert_eq!(always.solve_for("x"), None);
+        let twice = Equation {
+            left: Expr::bin(Op::Add, Expr::var("x"), Expr::var("x")),
+            right: num(5),
+        };
+        assert_eq!(twice.solve_for("x"), Rational::new(5, 2));
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    ops::RangeInclusive,
+};
+
+pub type Point2 = (i64, i64);
+pub type Point3 = (i64, i64, i64);
+
+// Every cell reachable from `start` through orthogonal steps without leaving
+// `bounds` or entering a solid cell. A solid or out of bounds start gives nothing.
+pub fn flood_fill_3d(
+    bounds: [RangeInclusive<i64>; 3],
+    is_solid: impl Fn(Point3) -> bool,
+    start: Point3,
+) -> HashSet<Point3> {
+    let inside = |(x, y, z): Point3| {
+        bounds[0].contains(&x) && bounds[1].contains(&y) && bounds[2].contains(&z)
+    };
+    flood_fill(
+        start,
+        |p| inside(p) && !is_solid(p),
+        |(x, y, z)| {
+            [
+                (x - 1, y, z),
+                (x + 1, y, z),
+                (x, y - 1, z),
+                (x, y + 1, z),
+                (x, y, z - 1),
+                (x, y, z + 1),
+            ]
+        },
+    )
+}
+
+pub fn flood_fill_2d(
+    bounds: [RangeInclusive<i64>; 2],
+    is_solid: impl Fn(Point2) -> bool,
+    start: Point2,
+) -> HashSet<Point2> {
+    let inside = |(x, y): Point2| bounds[0].contains(&x) && bounds[1].contains(&y);
+    flood_fill(
+        start,
+        |p| inside(p) && !is_solid(p),
+        |(x, y)| [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)],
+    )
+}
+
+fn flood_fill<P, const N: usize>(
+    start: P,
+    is_free: impl Fn(P) -> bool,
+    neighbors: impl Fn(P) -> [P; N],
+) -> HashSet<P>
+where
+    P: Copy + Eq + std::hash::Hash,
+{
+    let mut seen = HashSet::new();
+    if !is_free(start) {
+        return seen;
+    }
+    seen.insert(start);
+    let mut queue = VecDeque::from([start]);
+    while let Some(pos) = queue.pop_front() {
+        for next in neighbors(pos) {
+            if is_free(next) && seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exterior_of_hollow_cube() {
+        // 3x3x3 shell around (1, 1, 1), so the center is not reachable from outside
+        let is_solid = |(x, y, z): Point3| {
+            (0..=2).contains(&x)
+                && (0..=2).contains(&y)
+                && (0..=2).contains(&z)
+                && (x, y, z) != (1, 1, 1)
+        };
+        let exterior = flood_fill_3d([-1..=3, -1..=3, -1..=3], is_solid, (-1, -1, -1));
+        assert_eq!(exterior.len(), 125 - 27);
+        assert!(!exterior.contains(&(1, 1, 1)));
+        let interior = flood_fill_3d([-1..=3, -1..=3, -1..=3], is_solid, (1, 1, 1));
+        assert_eq!(interior.len(), 1);
+    }
+
+    #[test]
+    fn solid_start_is_empty() {
+        assert!(flood_fill_3d([0..=1, 0..=1, 0..=1], |_| true, (0, 0, 0)).is_empty());
+        assert!(flood_fill_2d([0..=1, 0..=1], |_| false, (5, 5)).is_empty());
+    }
+
+    #[test]
+    fn flood_fill_2d_stops_at_wall() {
+        // Vertical wall at x = 2 with a gap at y = 4
+        let filled = flood_fill_2d([0..=4, 0..=4], |(x, y)| x == 2 && y != 4, (0, 0));
+        assert_eq!(filled.len(), 25 - 4);
+        let sealed = flood_fill_2d([0..=4, 0..=3], |(x, _)| x == 2, (0, 0));
+        assert_eq!(sealed.len(), 8);
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    sync::RwLock,
+};
+
+use anyhow::anyhow;
+
+// The characters drawings are made of, with block characters that some
+// terminals and fonts don't have, or the ASCII the puzzles themselves use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    // Rock and walls
+    pub solid: char,
+    // Something that piles up, like day 14's sand
+    pub grain: char,
+    // A lit pixel, like on day 10's screen
+    pub lit: char,
+    pub empty: char,
+    // Bars from lowest to highest, for sparklines
+    pub bars: [char; 8],
+    // Around panels shown side by side
+    pub border: Border,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Border {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+}
+
+pub const UNICODE: Glyphs = Glyphs {
+    solid: '█',
+    grain: '⣿',
+    lit: '⣿',
+    empty: ' ',
+    bars: ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+    border: Border {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+    },
+};
+
+pub const ASCII: Glyphs = Glyphs {
+    solid: '#',
+    grain: 'o',
+    lit: '#',
+    empty: '.',
+    bars: ['_', '.', ',', '-', '=', '+', '*', '#'],
+    border: Border {
+        horizontal: '-',
+        vertical: '|',
+        top_left: '+',
+        top_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphChoice {
+    // Unicode when the locale is UTF-8
+    Auto,
+    Unicode,
+    Ascii,
+}
+
+impl FromStr for GlyphChoice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(GlyphChoice::Auto),
+            "unicode" => Ok(GlyphChoice::Unicode),
+            "ascii" => Ok(GlyphChoice::Ascii),
+            _ => Err(anyhow!("Expected one of auto, unicode, ascii")),
+        }
+    }
+}
+
+impl Display for GlyphChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlyphChoice::Auto => "auto",
+            GlyphChoice::Unicode => "unicode",
+            GlyphChoice::Ascii => "ascii",
+        }
+        .fmt(f)
+    }
+}
+
+// Display impls have no way to take the glyphs as a parameter, so the
+// commands pick them once for the whole process. Unicode until then.
+static CURRENT: RwLock<Glyphs> = RwLock::new(UNICODE);
+
+pub fn set(glyphs: Glyphs) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = glyphs;
+}
+
+pub fn current() -> Glyphs {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
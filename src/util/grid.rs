@@ -0,0 +1,849 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    ops::{Range, RangeInclusive},
+};
+
+use nom::{character::complete::line_ending, Parser};
+
+use crate::util::{
+    neighbors::{neighbors_of, Bounds, ORTHO4},
+    prelude::*,
+};
+
+// A cell of a grid, with y growing downwards
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct GridPos {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl GridPos {
+    pub fn dist(&self, other: &Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    // The orthogonal neighbours within a `max_x` by `max_y` grid
+    pub fn neighbors(&self, max_x: usize, max_y: usize) -> impl Iterator<Item = GridPos> {
+        neighbors_of(*self, ORTHO4, Bounds::grid(max_x, max_y))
+    }
+}
+
+impl From<(usize, usize)> for GridPos {
+    fn from(value: (usize, usize)) -> Self {
+        Self {
+            x: value.0,
+            y: value.1,
+        }
+    }
+}
+
+// Cells in rows, `length` wide and `height` tall
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    inner: Vec<T>,
+    pub length: usize,
+    pub height: usize,
+}
+
+impl<T> Grid<T> {
+    // Rows of `length` cells laid end to end, None if they don't fill the
+    // last row
+    pub fn from_cells(cells: Vec<T>, length: usize) -> Option<Self> {
+        cells.len().is_multiple_of(length).then(|| Self {
+            height: cells.len().checked_div(length).unwrap_or(0),
+            inner: cells,
+            length,
+        })
+    }
+
+    pub fn contains(&self, pos: &GridPos) -> bool {
+        (0..self.length).contains(&pos.x) && (0..self.height).contains(&pos.y)
+    }
+    pub fn get(&self, pos: &GridPos) -> Option<&T> {
+        if self.contains(pos) {
+            self.inner.get(pos.x + self.length * pos.y)
+        } else {
+            None
+        }
+    }
+    pub fn get_mut(&mut self, pos: &GridPos) -> Option<&mut T> {
+        if self.contains(pos) {
+            self.inner.get_mut(pos.x + self.length * pos.y)
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = std::slice::Iter<'_, T>> {
+        self.inner.chunks(self.length).map(|s| s.iter())
+    }
+
+    pub fn iter_rows_mut(&mut self) -> impl Iterator<Item = std::slice::IterMut<'_, T>> {
+        self.inner.chunks_mut(self.length).map(|s| s.iter_mut())
+    }
+
+    pub fn iter_diagonals(
+        &self,
+        direction: Diagonal,
+    ) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T> + ExactSizeIterator> {
+        self.view().iter_diagonals(direction)
+    }
+
+    pub fn view(&self) -> GridView<'_, T> {
+        GridView {
+            cells: &self.inner,
+            length: self.length,
+            height: self.height,
+            step_x: 1,
+            step_y: self.length,
+        }
+    }
+
+    pub fn col(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.view().col(x)
+    }
+
+    pub fn col_mut(
+        &mut self,
+        x: usize,
+    ) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator {
+        assert!(x < self.length, "column {x} is outside the grid");
+        self.inner.iter_mut().skip(x).step_by(self.length)
+    }
+
+    pub fn iter_cols(
+        &self,
+    ) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T> + ExactSizeIterator> {
+        self.view().iter_cols()
+    }
+
+    pub fn neighbors<'a, 'b: 'a>(
+        &'a self,
+        pos: &'b GridPos,
+    ) -> impl Iterator<Item = (GridPos, &'a T)> + 'a {
+        self.contains(pos).then_some(()).into_iter().flat_map(|_| {
+            pos.neighbors(self.length, self.height)
+                .filter_map(|new_pos| self.get(&new_pos).map(|t| (new_pos, t)))
+        })
+    }
+
+    // Moves every cell to where `source` says it comes from: the cell at
+    // (x, y) of the `length` by `height` result is the one at index
+    // `source(x, y)` now. `source` has to be a bijection. Following the
+    // permutation's cycles and swapping along them needs no second buffer.
+    fn permute(
+        mut self,
+        length: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> usize,
+    ) -> Self {
+        let source = |i: usize| source(i % length, i / length);
+        let mut done = bitvec::bitvec![0; self.inner.len()];
+        for start in 0..self.inner.len() {
+            let mut current = start;
+            while !done[current] {
+                done.set(current, true);
+                let next = source(current);
+                if next == start {
+                    break;
+                }
+                self.inner.swap(current, next);
+                current = next;
+            }
+        }
+        self.length = length;
+        self.height = height;
+        self
+    }
+
+    pub fn transpose(self) -> Self {
+        let (length, height) = (self.length, self.height);
+        self.permute(height, length, |x, y| x * length + y)
+    }
+
+    // A quarter turn clockwise, so the top row becomes the right column
+    pub fn rotate_cw(self) -> Self {
+        let (length, height) = (self.length, self.height);
+        self.permute(height, length, |x, y| (height - 1 - x) * length + y)
+    }
+
+    // Flips left to right, reversing every row
+    pub fn mirror_x(mut self) -> Self {
+        if self.length > 0 {
+            self.inner
+                .chunks_mut(self.length)
+                .for_each(|row| row.reverse());
+        }
+        self
+    }
+
+    // Flips top to bottom, reversing the order of the rows
+    pub fn mirror_y(mut self) -> Self {
+        for y in 0..self.height / 2 {
+            let (top, bottom) = self.inner.split_at_mut((self.height - 1 - y) * self.length);
+            top[y * self.length..(y + 1) * self.length].swap_with_slice(&mut bottom[..self.length]);
+        }
+        self
+    }
+
+    pub fn parse<'a, F: Parser<&'a str, Vec<T>, nom::error::Error<&'a str>>>(
+        mut line_parser: F,
+    ) -> impl Parser<&'a str, Self, nom::error::Error<&'a str>> {
+        move |input| match line_parser.parse(input) {
+            Ok((input, first_line)) => {
+                let length = first_line.len();
+                let mut acc = first_line;
+                let mut height = 1;
+                let mut rest = input;
+                loop {
+                    match line_ending::<_, nom::error::Error<&'a str>>(rest) {
+                        Ok((new_rest, _)) => rest = new_rest,
+                        Err(_) => {
+                            return Ok((
+                                input,
+                                Self {
+                                    inner: acc,
+                                    length,
+                                    height,
+                                },
+                            ))
+                        }
+                    }
+                    match line_parser.parse(rest) {
+                        Ok((new_rest, mut row)) if row.len() == length => {
+                            acc.append(&mut row);
+                            height += 1;
+                            rest = new_rest;
+                        }
+                        _ => {
+                            return Ok((
+                                input,
+                                Self {
+                                    inner: acc,
+                                    length,
+                                    height,
+                                },
+                            ))
+                        }
+                    }
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<T: fmt::Display> Grid<T> {
+    // The cells with row and column numbers, see `with_axes`
+    pub fn labeled(&self) -> String {
+        let rows = self
+            .iter_rows()
+            .map(|row| row.map(ToString::to_string).collect::<String>())
+            .collect_vec();
+        with_axes(&rows, (0, 0))
+    }
+}
+
+// Rows of one character cells, with row numbers to their left and the
+// numbers of every fifth column above them, written top to bottom. The top
+// left cell is at `origin`, so grids that start elsewhere show their own
+// coordinates.
+pub fn with_axes(rows: &[String], (x0, y0): Point2) -> String {
+    let width = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    let row_labels = (y0..).take(rows.len()).map(|y| y.to_string()).collect_vec();
+    let margin = row_labels.iter().map(String::len).max().unwrap_or(0);
+    let column_labels = (x0..)
+        .take(width)
+        .map(|x| (x.rem_euclid(5) == 0).then(|| x.to_string()))
+        .collect_vec();
+    let label_height = column_labels
+        .iter()
+        .flatten()
+        .map(String::len)
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for line in 0..label_height {
+        let digits = column_labels
+            .iter()
+            .map(|label| {
+                // Lined up at the bottom, right above the cells
+                label
+                    .as_deref()
+                    .and_then(|label| {
+                        let skip = label_height - label.len();
+                        line.checked_sub(skip).map(|i| label.as_bytes()[i] as char)
+                    })
+                    .unwrap_or(' ')
+            })
+            .collect::<String>();
+        writeln!(out, "{:margin$} {}", "", digits.trim_end()).unwrap();
+    }
+    for (label, row) in row_labels.iter().zip(rows) {
+        writeln!(out, "{label:>margin$} {row}").unwrap();
+    }
+    out
+}
+
+// Which way a diagonal goes when read from left to right: `Falling` moves
+// down a row with every column, `Rising` moves up one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagonal {
+    Falling,
+    Rising,
+}
+
+// A borrowed grid of cells at fixed steps through a slice. Windows, strides
+// and transposes only change the steps, so none of them copy any cells.
+#[derive(Debug)]
+pub struct GridView<'a, T> {
+    cells: &'a [T],
+    pub length: usize,
+    pub height: usize,
+    // How far apart horizontal and vertical neighbours are in `cells`
+    step_x: usize,
+    step_y: usize,
+}
+
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn get(&self, pos: &GridPos) -> Option<&'a T> {
+        (pos.x < self.length && pos.y < self.height)
+            .then(|| &self.cells[pos.x * self.step_x + pos.y * self.step_y])
+    }
+
+    pub fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &'a T> + ExactSizeIterator {
+        assert!(y < self.height, "row {y} is outside the view");
+        let view = *self;
+        (0..self.length).map(move |x| &view.cells[x * view.step_x + y * view.step_y])
+    }
+
+    pub fn col(&self, x: usize) -> impl DoubleEndedIterator<Item = &'a T> + ExactSizeIterator {
+        assert!(x < self.length, "column {x} is outside the view");
+        let view = *self;
+        (0..self.height).map(move |y| &view.cells[x * view.step_x + y * view.step_y])
+    }
+
+    pub fn iter_rows(
+        &self,
+    ) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &'a T> + ExactSizeIterator> {
+        let view = *self;
+        (0..self.height).map(move |y| view.row(y))
+    }
+
+    pub fn iter_cols(
+        &self,
+    ) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &'a T> + ExactSizeIterator> {
+        let view = *self;
+        (0..self.length).map(move |x| view.col(x))
+    }
+
+    // Every diagonal going in `direction`, starting from the one through the
+    // bottom left corner for falling diagonals and the top left corner for
+    // rising ones, each read from left to right
+    pub fn iter_diagonals(
+        &self,
+        direction: Diagonal,
+    ) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &'a T> + ExactSizeIterator> {
+        let view = *self;
+        let count = if self.length == 0 || self.height == 0 {
+            0
+        } else {
+            self.length + self.height - 1
+        };
+        (0..count).map(move |k| {
+            let (x0, y0, len) = match direction {
+                Diagonal::Falling => {
+                    let (x0, y0) = if k < view.height {
+                        (0, view.height - 1 - k)
+                    } else {
+                        (k + 1 - view.height, 0)
+                    };
+                    (x0, y0, (view.length - x0).min(view.height - y0))
+                }
+                Diagonal::Rising => {
+                    let (x0, y0) = if k < view.height {
+                        (0, k)
+                    } else {
+                        (k + 1 - view.height, view.height - 1)
+                    };
+                    (x0, y0, (view.length - x0).min(y0 + 1))
+                }
+            };
+            (0..len).map(move |i| {
+                let y = match direction {
+                    Diagonal::Falling => y0 + i,
+                    Diagonal::Rising => y0 - i,
+                };
+                &view.cells[(x0 + i) * view.step_x + y * view.step_y]
+            })
+        })
+    }
+
+    // Cells row by row
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        self.iter_rows().flatten()
+    }
+
+    // The part of the view in columns `xs` and rows `ys`
+    pub fn window(&self, xs: Range<usize>, ys: Range<usize>) -> Self {
+        assert!(
+            xs.start <= xs.end
+                && xs.end <= self.length
+                && ys.start <= ys.end
+                && ys.end <= self.height,
+            "window {xs:?} by {ys:?} is outside the view"
+        );
+        let offset = (xs.start * self.step_x + ys.start * self.step_y).min(self.cells.len());
+        Self {
+            cells: &self.cells[offset..],
+            length: xs.len(),
+            height: ys.len(),
+            ..*self
+        }
+    }
+
+    // Every `dx`th column of every `dy`th row, starting from the top left
+    pub fn stride(&self, dx: usize, dy: usize) -> Self {
+        assert!(dx > 0 && dy > 0, "strides need to be positive");
+        Self {
+            length: self.length.div_ceil(dx),
+            height: self.height.div_ceil(dy),
+            step_x: self.step_x * dx,
+            step_y: self.step_y * dy,
+            ..*self
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self {
+            cells: self.cells,
+            length: self.height,
+            height: self.length,
+            step_x: self.step_y,
+            step_y: self.step_x,
+        }
+    }
+}
+
+impl<J, T> FromIterator<J> for Grid<T>
+where
+    J: Iterator<Item = T>,
+{
+    fn from_iter<I: IntoIterator<Item = J>>(iter: I) -> Self {
+        let mut iter = iter.into_iter();
+        let mut height = 0;
+        if let Some(first) = iter.next() {
+            let mut acc = first.collect_vec();
+            let length = acc.len();
+            height += 1;
+            for row in iter {
+                acc.extend(row);
+                height += 1;
+                if acc.len() != height * length {
+                    panic!("Expected {length} elements")
+                }
+            }
+            Self {
+                inner: acc,
+                height,
+                length,
+            }
+        } else {
+            Self {
+                inner: Vec::new(),
+                length: 0,
+                height: 0,
+            }
+        }
+    }
+}
+
+// Cells at arbitrary, possibly negative, coordinates, for puzzles whose
+// area keeps growing. Tracks the smallest box around everything inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point2, T>,
+    // Smallest and largest corner of the bounding box
+    bounds: Option<(Point2, Point2)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, point: Point2) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    pub fn get_mut(&mut self, point: Point2) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn insert(&mut self, point: Point2, value: T) -> Option<T> {
+        let corners = self.bounds.into_iter().flat_map(|(min, max)| [min, max]);
+        self.bounds = bounding_box(corners.chain([point]));
+        self.cells.insert(point, value)
+    }
+
+    // Shrinks the bounding box again if `point` was on its edge
+    pub fn remove(&mut self, point: Point2) -> Option<T> {
+        let removed = self.cells.remove(&point)?;
+        if let Some(((min_x, min_y), (max_x, max_y))) = self.bounds {
+            let (x, y) = point;
+            if x == min_x || x == max_x || y == min_y || y == max_y {
+                self.bounds = bounding_box(self.cells.keys().copied());
+            }
+        }
+        Some(removed)
+    }
+
+    // Columns and rows spanned by the cells, None while empty
+    pub fn bounds(&self) -> Option<(RangeInclusive<i64>, RangeInclusive<i64>)> {
+        self.bounds
+            .map(|((min_x, min_y), (max_x, max_y))| (min_x..=max_x, min_y..=max_y))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+        self.cells.iter().map(|(point, value)| (*point, value))
+    }
+
+    // Draws the given columns and rows only, with `.` for missing cells
+    pub fn crop(&self, xs: RangeInclusive<i64>, ys: RangeInclusive<i64>) -> Cropped<'_, T> {
+        Cropped { grid: self, xs, ys }
+    }
+
+    // The bounding box as a dense grid, and where its top left cell is
+    pub fn into_dense(self) -> (Point2, Grid<Option<T>>) {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds else {
+            return ((0, 0), std::iter::empty::<std::iter::Empty<_>>().collect());
+        };
+        let length = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+        let mut inner = std::iter::repeat_with(|| None)
+            .take(length * height)
+            .collect_vec();
+        for ((x, y), value) in self.cells {
+            inner[(x - min_x) as usize + (y - min_y) as usize * length] = Some(value);
+        }
+        let grid = Grid {
+            inner,
+            length,
+            height,
+        };
+        ((min_x, min_y), grid)
+    }
+
+    // Every cell `keep` accepts, with the grid's top left cell at `origin`
+    pub fn from_dense(origin: Point2, grid: Grid<T>, keep: impl Fn(&T) -> bool) -> Self {
+        let length = grid.length;
+        grid.inner
+            .into_iter()
+            .enumerate()
+            .filter(|(_, value)| keep(value))
+            .map(|(i, value)| {
+                let point = (
+                    origin.0 + (i % length) as i64,
+                    origin.1 + (i / length) as i64,
+                );
+                (point, value)
+            })
+            .collect()
+    }
+}
+
+fn bounding_box(points: impl Iterator<Item = Point2>) -> Option<(Point2, Point2)> {
+    points.fold(None, |bounds, (x, y)| {
+        Some(match bounds {
+            None => ((x, y), (x, y)),
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            }
+        })
+    })
+}
+
+impl<T> FromIterator<(Point2, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point2, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (point, value) in iter {
+            grid.insert(point, value);
+        }
+        grid
+    }
+}
+
+pub struct Cropped<'a, T> {
+    grid: &'a SparseGrid<T>,
+    xs: RangeInclusive<i64>,
+    ys: RangeInclusive<i64>,
+}
+
+// One line per row, with y growing downwards
+impl<T: fmt::Display> fmt::Display for Cropped<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in self.ys.clone() {
+            for x in self.xs.clone() {
+                match self.grid.get((x, y)) {
+                    Some(value) => write!(f, "{value}")?,
+                    None => write!(f, ".")?,
+                }
+            }
+            if y != *self.ys.end() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Everything within the bounding box
+impl<T: fmt::Display> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bounds() {
+            Some((xs, ys)) => self.crop(xs, ys).fmt(f),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 3 wide and 2 tall:
+    // 0 1 2
+    // 3 4 5
+    fn wide() -> Grid<u32> {
+        [0..3, 3..6].into_iter().collect()
+    }
+
+    fn rows(grid: &Grid<u32>) -> Vec<Vec<u32>> {
+        grid.iter_rows().map(|row| row.copied().collect()).collect()
+    }
+
+    #[test]
+    fn neighbors_and_distance() {
+        let pos = GridPos { x: 0, y: 1 };
+        assert_eq!(
+            pos.neighbors(3, 2).collect_vec(),
+            [GridPos { x: 1, y: 1 }, GridPos { x: 0, y: 0 }]
+        );
+        assert_eq!(pos.dist(&GridPos { x: 2, y: 0 }), 3);
+    }
+
+    #[test]
+    fn collects_rows() {
+        let grid = wide();
+        assert_eq!((grid.length, grid.height), (3, 2));
+        assert_eq!(grid.iter().copied().collect_vec(), [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn transposes_non_square() {
+        let grid = wide().transpose();
+        assert_eq!((grid.length, grid.height), (2, 3));
+        assert_eq!(rows(&grid), [[0, 3], [1, 4], [2, 5]]);
+        assert_eq!(grid.transpose(), wide());
+        let tall: Grid<u32> = (0..7).map(|y| y * 2..y * 2 + 2).collect();
+        assert_eq!(tall.clone().transpose().transpose(), tall);
+    }
+
+    #[test]
+    fn rotates_clockwise() {
+        let grid = wide().rotate_cw();
+        assert_eq!((grid.length, grid.height), (2, 3));
+        assert_eq!(rows(&grid), [[3, 0], [4, 1], [5, 2]]);
+        let full_turn = grid.rotate_cw().rotate_cw().rotate_cw();
+        assert_eq!(full_turn, wide());
+    }
+
+    #[test]
+    fn mirrors() {
+        assert_eq!(rows(&wide().mirror_x()), [[2, 1, 0], [5, 4, 3]]);
+        assert_eq!(rows(&wide().mirror_y()), [[3, 4, 5], [0, 1, 2]]);
+        let odd: Grid<u32> = [0..2, 2..4, 4..6].into_iter().collect();
+        assert_eq!(rows(&odd.mirror_y()), [[4, 5], [2, 3], [0, 1]]);
+        // Transposing and mirroring is the same as rotating
+        assert_eq!(wide().transpose().mirror_x(), wide().rotate_cw());
+    }
+
+    #[test]
+    fn columns() {
+        let mut grid = wide();
+        assert_eq!(grid.col(1).copied().collect_vec(), [1, 4]);
+        assert_eq!(grid.col(2).rev().copied().collect_vec(), [5, 2]);
+        let cols = grid
+            .iter_cols()
+            .map(|col| col.copied().collect_vec())
+            .collect_vec();
+        assert_eq!(cols, [[0, 3], [1, 4], [2, 5]]);
+        grid.col_mut(0).rev().for_each(|cell| *cell += 10);
+        assert_eq!(rows(&grid), [[10, 1, 2], [13, 4, 5]]);
+    }
+
+    #[test]
+    fn views() {
+        // 0  1  2  3
+        // 4  5  6  7
+        // 8  9 10 11
+        let grid: Grid<u32> = (0..3).map(|y| y * 4..y * 4 + 4).collect();
+        let view_rows = |view: GridView<u32>| {
+            view.iter_rows()
+                .map(|row| row.copied().collect_vec())
+                .collect_vec()
+        };
+        let window = grid.view().window(1..3, 1..3);
+        assert_eq!(view_rows(window), [[5, 6], [9, 10]]);
+        assert_eq!(window.get(&(1, 0).into()), Some(&6));
+        assert_eq!(window.get(&(2, 0).into()), None);
+        assert_eq!(view_rows(grid.view().stride(2, 2)), [[0, 2], [8, 10]]);
+        assert_eq!(
+            view_rows(grid.view().stride(3, 1)),
+            [[0, 3], [4, 7], [8, 11]]
+        );
+        let transposed = grid.view().transpose();
+        assert_eq!((transposed.length, transposed.height), (3, 4));
+        assert_eq!(
+            transposed.iter().copied().collect_vec(),
+            grid.clone().transpose().iter().copied().collect_vec()
+        );
+        let corner = grid
+            .view()
+            .window(2..4, 0..3)
+            .transpose()
+            .window(1..3, 0..2);
+        assert_eq!(view_rows(corner), [[6, 10], [7, 11]]);
+        assert_eq!(grid.view().window(4..4, 3..3).iter().count(), 0);
+    }
+
+    #[test]
+    fn diagonals() {
+        let diagonals = |grid: &Grid<u32>, direction| {
+            grid.iter_diagonals(direction)
+                .map(|diagonal| diagonal.copied().collect_vec())
+                .collect_vec()
+        };
+        let grid = wide();
+        assert_eq!(
+            diagonals(&grid, Diagonal::Falling),
+            [vec![3], vec![0, 4], vec![1, 5], vec![2]]
+        );
+        assert_eq!(
+            diagonals(&grid, Diagonal::Rising),
+            [vec![0], vec![3, 1], vec![4, 2], vec![5]]
+        );
+        let tall = grid.transpose();
+        assert_eq!(
+            diagonals(&tall, Diagonal::Falling),
+            [vec![2], vec![1, 5], vec![0, 4], vec![3]]
+        );
+        assert_eq!(
+            diagonals(&tall, Diagonal::Rising),
+            [vec![0], vec![1, 3], vec![2, 4], vec![5]]
+        );
+        // Every cell is on exactly one diagonal each way
+        let big: Grid<u32> = (0..4).map(|y| y * 7..y * 7 + 7).collect();
+        for direction in [Diagonal::Falling, Diagonal::Rising] {
+            let mut cells = diagonals(&big, direction).concat();
+            cells.sort();
+            assert_eq!(cells, (0..28).collect_vec());
+        }
+        let window = big.view().window(1..4, 1..3);
+        let rising = window
+            .iter_diagonals(Diagonal::Rising)
+            .map(|diagonal| diagonal.rev().copied().collect_vec())
+            .collect_vec();
+        assert_eq!(rising, [vec![8], vec![9, 15], vec![10, 16], vec![17]]);
+        let empty: Grid<u32> = std::iter::empty::<std::ops::Range<u32>>().collect();
+        assert_eq!(empty.iter_diagonals(Diagonal::Falling).count(), 0);
+    }
+
+    #[test]
+    fn labels_axes() {
+        assert_eq!(wide().labeled(), "  0\n0 012\n1 345\n");
+        let rows = ["..#.....", "#......."].map(String::from);
+        assert_eq!(
+            with_axes(&rows, (498, 9)),
+            "     5    5\n     0    0\n     0    5\n 9 ..#.....\n10 #.......\n"
+        );
+        assert_eq!(
+            with_axes(&["ab".to_string()], (-6, 0)),
+            "   -\n   5\n0 ab\n"
+        );
+        assert_eq!(with_axes(&[], (0, 0)), "");
+    }
+
+    #[test]
+    fn sparse_bounds_and_display() {
+        let mut grid: SparseGrid<char> = [((-2, 1), '#'), ((1, -1), 'H')].into_iter().collect();
+        assert_eq!(grid.bounds(), Some((-2..=1, -1..=1)));
+        assert_eq!(grid.to_string(), "...H\n....\n#...");
+        assert_eq!(grid.crop(0..=1, -1..=0).to_string(), ".H\n..");
+        grid.insert((0, 0), 's');
+        assert_eq!(grid.remove((-2, 1)), Some('#'));
+        assert_eq!(grid.bounds(), Some((0..=1, -1..=0)));
+        assert_eq!(grid.to_string(), ".H\ns.");
+        grid.remove((0, 0));
+        grid.remove((1, -1));
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.to_string(), "");
+    }
+
+    #[test]
+    fn sparse_round_trips_through_dense() {
+        let sparse: SparseGrid<u32> = [((5, -3), 1), ((7, -2), 2), ((6, -3), 3)]
+            .into_iter()
+            .collect();
+        let (origin, dense) = sparse.clone().into_dense();
+        assert_eq!(origin, (5, -3));
+        assert_eq!((dense.length, dense.height), (3, 2));
+        assert_eq!(
+            dense.iter().copied().collect_vec(),
+            [Some(1), Some(3), None, None, None, Some(2)]
+        );
+        let back = SparseGrid::from_dense(origin, dense, Option::is_some);
+        assert_eq!(back.len(), 3);
+        assert_eq!(back.get((7, -2)), Some(&Some(2)));
+        let (origin, dense) = SparseGrid::<u32>::new().into_dense();
+        assert_eq!((origin, dense.length, dense.height), ((0, 0), 0, 0));
+        let skipped = SparseGrid::from_dense((0, 0), wide(), |x| x % 2 == 0);
+        assert_eq!(skipped.bounds(), Some((0..=2, 0..=1)));
+        assert_eq!(skipped.get((1, 1)), Some(&4));
+    }
+}
@@ -0,0 +1,43 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{BuildHasherDefault, Hash, Hasher},
+};
+
+// A hasher for the crate's own states, like visited positions. No input is
+// adversarial, so SipHash's protection against collision attacks buys
+// nothing and costs a lot on small keys. Chosen by the ahash and fxhash
+// features, ahash first, with std's SipHash when neither is on.
+#[cfg(feature = "ahash")]
+pub type FastHasher = ahash::AHasher;
+#[cfg(all(feature = "fxhash", not(feature = "ahash")))]
+pub type FastHasher = rustc_hash::FxHasher;
+#[cfg(not(any(feature = "ahash", feature = "fxhash")))]
+pub type FastHasher = std::collections::hash_map::DefaultHasher;
+
+pub type FastBuildHasher = BuildHasherDefault<FastHasher>;
+pub type FastHashMap<K, V> = HashMap<K, V, FastBuildHasher>;
+pub type FastHashSet<T> = HashSet<T, FastBuildHasher>;
+
+// The same for equal states within a build, for cycle detection and state
+// fingerprints. Not stable across hasher choices or versions.
+pub fn hash_state(state: &impl Hash) -> u64 {
+    let mut hasher = FastHasher::default();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hashes_equal_states_equally() {
+        assert_eq!(hash_state(&(3, -4)), hash_state(&(3, -4)));
+        assert_ne!(hash_state(&(3, -4)), hash_state(&(-4, 3)));
+        let mut seen = FastHashSet::default();
+        assert!(seen.insert((0_i64, 0_i64)));
+        assert!(!seen.insert((0, 0)));
+        let counts: FastHashMap<_, usize> = [("a", 1), ("b", 2)].into_iter().collect();
+        assert_eq!(counts["b"], 2);
+    }
+}
@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use crate::error::AocError;
+
+// Requests to adventofcode.com, blocking over ureq. With the async feature,
+// `nonblocking` makes the same requests from a single threaded tokio runtime,
+// for commands that keep the terminal updating while they wait on the site.
+
+// The site asks automated tools to say who they are
+const USER_AGENT: &str = "github.com/bnarnold/adventofcode-2022 aoc";
+
+// Where the site is, which AOC_BASE_URL moves elsewhere, like to the mock
+// server of the integration tests
+pub fn site() -> String {
+    std::env::var("AOC_BASE_URL").unwrap_or_else(|_| "https://adventofcode.com".to_string())
+}
+
+pub fn get(url: &str, session: Option<&str>) -> Result<String, AocError> {
+    let mut request = ureq::get(url).set("User-Agent", USER_AGENT);
+    if let Some(session) = session {
+        request = request.set("Cookie", &format!("session={session}"));
+    }
+    read(url, request.call())
+}
+
+pub fn post_form(url: &str, session: &str, form: &str) -> Result<String, AocError> {
+    let response = ureq::post(url)
+        .set("User-Agent", USER_AGENT)
+        .set("Content-Type", "application/x-www-form-urlencoded")
+        .set("Cookie", &format!("session={session}"))
+        .send_string(form);
+    read(url, response)
+}
+
+// For webhooks, which want `{"text": "..."}` style bodies
+pub fn post_json(url: &str, json: &str) -> Result<(), AocError> {
+    let response = ureq::post(url)
+        .set("User-Agent", USER_AGENT)
+        .set("Content-Type", "application/json")
+        .send_string(json);
+    read(url, response).map(drop)
+}
+
+fn read(url: &str, response: Result<ureq::Response, ureq::Error>) -> Result<String, AocError> {
+    match response {
+        Err(ureq::Error::Status(429, _)) => Err(AocError::RateLimited { wait: None }),
+        Err(e) => Err(network(url, e)),
+        Ok(response) => response.into_string().map_err(|e| network(url, e)),
+    }
+}
+
+fn network(url: &str, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> AocError {
+    AocError::Network {
+        url: url.to_string(),
+        source: source.into(),
+    }
+}
+
+// Whether trying again might help: the connection failed or the server had
+// trouble. Refusals like a 404 before the unlock, a bad session or a rate
+// limit would only be asked for again.
+fn is_transient(error: &AocError) -> bool {
+    let AocError::Network { source, .. } = error else {
+        return false;
+    };
+    if let Some(ureq::Error::Status(status, _)) = source.downcast_ref::<ureq::Error>() {
+        return *status >= 500;
+    }
+    #[cfg(feature = "async")]
+    if let Some(e) = source.downcast_ref::<reqwest::Error>() {
+        return e.status().is_none_or(|status| status.is_server_error());
+    }
+    true
+}
+
+// Polls `url` until it answers, since pages tend to fail for a few seconds
+// right after a puzzle unlocks
+pub fn get_with_retries(url: &str, session: Option<&str>, tries: u32) -> Result<String, AocError> {
+    let mut attempt = 1;
+    loop {
+        match get(url, session) {
+            Err(e) if attempt < tries && is_transient(&e) => {
+                std::thread::sleep(Duration::from_secs(1));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub mod nonblocking {
+    use std::{future::Future, time::Duration};
+
+    use anyhow::Context;
+
+    use super::{is_transient, network, USER_AGENT};
+    use crate::error::AocError;
+
+    // A runtime on the calling thread, with no worker pool, for driving the
+    // futures below from the synchronous commands
+    pub fn block_on<F: Future>(future: F) -> anyhow::Result<F::Output> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Could not start the async runtime")?;
+        Ok(runtime.block_on(future))
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Client {
+        inner: reqwest::Client,
+        session: Option<String>,
+    }
+
+    impl Client {
+        pub fn new(session: Option<String>) -> anyhow::Result<Self> {
+            let inner = reqwest::Client::builder()
+                .user_agent(USER_AGENT)
+                .build()
+                .context("Could not build the HTTP client")?;
+            Ok(Self { inner, session })
+        }
+
+        pub async fn get(&self, url: &str) -> Result<String, AocError> {
+            let mut request = self.inner.get(url);
+            if let Some(session) = &self.session {
+                request = request.header("Cookie", format!("session={session}"));
+            }
+            let response = request.send().await.map_err(|e| network(url, e))?;
+            if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(AocError::RateLimited { wait: None });
+            }
+            response
+                .error_for_status()
+                .map_err(|e| network(url, e))?
+                .text()
+                .await
+                .map_err(|e| network(url, e))
+        }
+
+        pub async fn get_with_retries(&self, url: &str, tries: u32) -> Result<String, AocError> {
+            let mut attempt = 1;
+            loop {
+                match self.get(url).await {
+                    Err(e) if attempt < tries && is_transient(&e) => {
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,20 @@
-use std::{fmt::Display, str::FromStr};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::ErrorKind,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use anyhow::{anyhow, Context};
+use anyhow::anyhow;
 
-#[derive(Debug)]
+use crate::{
+    error::{AocError, Hint},
+    util::http,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Level {
     One,
     Two,
@@ -32,32 +44,304 @@ impl Display for Level {
     }
 }
 
-#[derive(Debug)]
-pub struct Submit;
+// The title from a puzzle page's "--- Day 1: Calorie Counting ---" heading
+pub fn title_from_page(page: &str) -> Option<&str> {
+    let heading = page.split("<h2>--- Day ").nth(1)?;
+    let (_, title) = heading.split_once(": ")?;
+    title.split_once(" ---</h2>").map(|(title, _)| title)
+}
+
+pub fn fetch_title(year: u32, day: u32) -> Result<String, AocError> {
+    let url = format!("{}/{year}/day/{day}", http::site());
+    let page = http::get(&url, None)?;
+    title_from_page(&page)
+        .map(str::to_string)
+        .ok_or_else(|| AocError::Parse {
+            context: format!("Puzzle page {url}"),
+            message: "no title found".to_string(),
+        })
+}
+
+// Stars per day from the year's calendar page, which labels each day's link
+// like `aria-label="Day 3, two stars"`. Days the page has no link for yet
+// are left out.
+pub fn stars_from_calendar(page: &str) -> BTreeMap<u32, u8> {
+    page.split("aria-label=\"Day ")
+        .skip(1)
+        .filter_map(|label| {
+            let (label, _) = label.split_once('"')?;
+            let (day, stars) = match label.split_once(", ") {
+                Some((day, "one star")) => (day, 1),
+                Some((day, "two stars")) => (day, 2),
+                Some(_) => return None,
+                None => (label, 0),
+            };
+            Some((day.parse().ok()?, stars))
+        })
+        .collect()
+}
+
+// The calendar as the logged in user sees it, with their stars
+pub fn fetch_calendar(year: u32, session: &str) -> Result<String, AocError> {
+    http::get(&format!("{}/{year}", http::site()), Some(session))
+}
+
+#[cfg(feature = "embed-inputs")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/inputs.rs"));
+}
+
+// The input compiled in with embed-inputs, if build.rs found one for the day
+pub fn embedded_input(day: u32) -> Option<&'static str> {
+    #[cfg(feature = "embed-inputs")]
+    return embedded::INPUTS
+        .iter()
+        .find(|(d, _)| *d == day)
+        .map(|(_, input)| *input);
+    #[cfg(not(feature = "embed-inputs"))]
+    {
+        let _ = day;
+        None
+    }
+}
+
+pub fn input_path(day: u32) -> PathBuf {
+    PathBuf::from(format!("input/day{day}.txt"))
+}
 
-pub fn parse_args() -> anyhow::Result<(Level, Option<Submit>)> {
-    let mut pargs = pico_args::Arguments::from_env();
-    let args = (
-        pargs
-            .value_from_str("--level")
-            .context("must pass --level")?,
-        pargs.contains(["-s", "--submit"]).then_some(Submit),
-    );
-    Ok(args)
+// Reading errors for the input at `path`, a missing file getting its own
+pub fn input_error(day: u32, path: PathBuf, e: std::io::Error) -> AocError {
+    match e.kind() {
+        ErrorKind::NotFound => AocError::InputMissing { day, path },
+        _ => e.into(),
+    }
+}
+
+pub fn read_input(day: u32) -> Result<String, AocError> {
+    if let Some(input) = embedded_input(day) {
+        return Ok(input.to_string());
+    }
+    let path = input_path(day);
+    std::fs::read_to_string(&path).map_err(|e| input_error(day, path, e))
 }
 
 pub fn submit(
+    year: u32,
     day: u32,
     level: Level,
     data: impl Display,
-    session: String,
-) -> anyhow::Result<ureq::Response> {
-    let url = format!("https://adventofcode.com/2022/day/{day}/answer");
-    let session_cookie = format!("session={session}");
+    session: &str,
+) -> Result<String, AocError> {
+    let url = format!("{}/{year}/day/{day}/answer", http::site());
     let payload = format!("level={level}&answer={data}");
-    ureq::post(&url)
-        .set("Content-Type", "x-www-form-urlencoded")
-        .set("Cookie", &session_cookie)
-        .send_string(&payload)
-        .context("Submit failed")
+    verdict(http::post_form(&url, session, &payload)?)
+}
+
+// The page the site answers a submission with, unless it says the answer
+// was wrong or came too soon after the last one
+pub fn verdict(response: String) -> Result<String, AocError> {
+    if response.contains("That's not the right answer") {
+        let hint = if response.contains("your answer is too high") {
+            Some(Hint::TooHigh)
+        } else if response.contains("your answer is too low") {
+            Some(Hint::TooLow)
+        } else {
+            None
+        };
+        return Err(AocError::WrongAnswer { hint });
+    }
+    if response.contains("You gave an answer too recently") {
+        let wait = response
+            .split_once("You have ")
+            .and_then(|(_, rest)| rest.split_once(" left to wait"))
+            .map(|(wait, _)| wait.to_string());
+        return Err(AocError::RateLimited { wait });
+    }
+    Ok(response)
+}
+
+// Whether a page was served to a logged in user, which the header shows
+pub fn logged_in(page: &str) -> bool {
+    page.contains("/auth/logout")
+}
+
+// Whether the page the site answers a submission with accepted it
+pub fn answer_was_right(response: &str) -> bool {
+    response.contains("That's the right answer")
+}
+
+// The puzzle page as the logged in user sees it, with level 2 once level 1
+// is solved
+pub fn fetch_page(year: u32, day: u32, session: &str) -> Result<String, AocError> {
+    let url = format!("{}/{year}/day/{day}", http::site());
+    http::get(&url, Some(session))
+}
+
+pub fn input_url(year: u32, day: u32) -> String {
+    format!("{}/{year}/day/{day}/input", http::site())
+}
+
+// Puzzles unlock at midnight US Eastern time, which is UTC-5 in December
+pub fn unlock_time(year: u32, day: u32) -> SystemTime {
+    let days = days_from_civil(year.into(), 12, day.into());
+    UNIX_EPOCH + Duration::from_secs((days * 86_400 + 5 * 3_600) as u64)
+}
+
+// Days from 1970-01-01 to a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// The level 2 statement from a puzzle page, as markdown. The page has one
+// article per level it shows, the second starting "--- Part Two ---".
+pub fn level2_statement(page: &str) -> Option<String> {
+    let article = page.split("<article class=\"day-desc\">").nth(2)?;
+    let (article, _) = article.split_once("</article>")?;
+    Some(html_to_markdown(article))
+}
+
+// Just the markup puzzle statements use: headings, paragraphs, code, emphasis,
+// lists and links
+fn html_to_markdown(html: &str) -> String {
+    let mut markdown = String::new();
+    let mut in_pre = false;
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        markdown.push_str(&unescape(&rest[..start]));
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        match name {
+            "h2" => markdown.push_str("## "),
+            "/h2" | "/p" => markdown.push_str("\n\n"),
+            "pre" => {
+                in_pre = true;
+                markdown.push_str("```\n");
+            }
+            "/pre" => {
+                in_pre = false;
+                if !markdown.ends_with('\n') {
+                    markdown.push('\n');
+                }
+                markdown.push_str("```\n\n");
+            }
+            "code" | "/code" if !in_pre => markdown.push('`'),
+            "em" | "/em" => markdown.push('*'),
+            "li" => markdown.push_str("- "),
+            "/li" | "/ul" => markdown.push('\n'),
+            _ => {}
+        }
+    }
+    markdown.push_str(&unescape(rest));
+    markdown.trim().to_string() + "\n"
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_title_in_page() {
+        let page =
+            "<main><article class=\"day-desc\"><h2>--- Day 7: No Space Left On Device ---</h2><p>";
+        assert_eq!(title_from_page(page), Some("No Space Left On Device"));
+        assert_eq!(title_from_page("<h2>Advent of Code</h2>"), None);
+    }
+
+    #[test]
+    fn reads_stars_from_calendar() {
+        let page = "<pre class=\"calendar\">\
+            <a aria-label=\"Day 3\" href=\"/2022/day/3\" class=\"calendar-day3\">\
+            <a aria-label=\"Day 2, one star\" href=\"/2022/day/2\" class=\"calendar-day2 calendar-complete\">\
+            <a aria-label=\"Day 1, two stars\" href=\"/2022/day/1\" class=\"calendar-day1 calendar-verycomplete\">\
+            <span aria-hidden=\"true\" class=\"calendar-day4\">";
+        assert_eq!(
+            stars_from_calendar(page),
+            BTreeMap::from([(1, 2), (2, 1), (3, 0)])
+        );
+    }
+
+    #[test]
+    fn unlocks_at_midnight_eastern() {
+        let unlock = unlock_time(2022, 1).duration_since(UNIX_EPOCH).unwrap();
+        assert_eq!(unlock.as_secs(), 1_669_870_800);
+        let unlock = unlock_time(2020, 25).duration_since(UNIX_EPOCH).unwrap();
+        assert_eq!(unlock.as_secs(), 1_608_872_400);
+    }
+
+    #[test]
+    fn extracts_level2_statement() {
+        let page = "<main><article class=\"day-desc\"><h2>--- Day 1: Calorie Counting ---</h2>\
+            <p>Level one.</p></article><p>Your puzzle answer was <code>1</code>.</p>\
+            <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>\
+            <p>Find the <em>top three</em> Elves &amp; sum <code>a &lt; b</code>:</p>\
+            <ul><li>One</li><li><a href=\"/x\">Two</a></li></ul>\
+            <pre><code>1000\n2000\n</code></pre><p>Done?</p></article></main>";
+        assert_eq!(
+            level2_statement(page).unwrap(),
+            "\
+## --- Part Two ---
+
+Find the *top three* Elves & sum `a < b`:
+
+- One
+- Two
+
+```
+1000
+2000
+```
+
+Done?
+"
+        );
+        let page = page.split("<p>Your").next().unwrap();
+        assert_eq!(level2_statement(page), None);
+        assert!(answer_was_right(
+            "<article><p>That's the right answer!  You are <em>one gold star</em>"
+        ));
+        assert!(!answer_was_right(
+            "<article><p>That's not the right answer."
+        ));
+    }
+
+    #[test]
+    fn reads_verdicts() {
+        let right = "<article><p>That's the right answer!  You are <em>one gold star</em>";
+        assert_eq!(verdict(right.to_string()).unwrap(), right);
+        let wrong = "<article><p>That's not the right answer; your answer is too high.";
+        assert!(matches!(
+            verdict(wrong.to_string()),
+            Err(AocError::WrongAnswer {
+                hint: Some(Hint::TooHigh)
+            })
+        ));
+        let wrong = "<article><p>That's not the right answer.  If you're stuck";
+        assert_eq!(
+            verdict(wrong.to_string()).unwrap_err().to_string(),
+            "That's not the right answer"
+        );
+        let early = "<article><p>You gave an answer too recently; you have to wait after \
+            submitting an answer before trying again.  You have 34s left to wait.";
+        assert_eq!(
+            verdict(early.to_string()).unwrap_err().to_string(),
+            "Answered too recently, 34s left to wait"
+        );
+    }
 }
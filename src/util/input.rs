@@ -0,0 +1,73 @@
+use crate::util::grid::{Grid, GridPos};
+
+// The input file mapped into memory rather than read into a String, so that
+// stress-scale inputs only cost the pages a parser touches and whatever it
+// builds from them
+#[cfg(feature = "mmap")]
+pub fn map_input(day: u32) -> Result<memmap2::Mmap, crate::error::AocError> {
+    use crate::util::infra::{input_error, input_path};
+
+    let path = input_path(day);
+    map_file(&path).map_err(|e| input_error(day, path, e))
+}
+
+#[cfg(feature = "mmap")]
+pub fn map_file(path: &std::path::Path) -> std::io::Result<memmap2::Mmap> {
+    let file = std::fs::File::open(path)?;
+    // Safety: the file must not change while it is mapped, and nothing in
+    // aoc writes an input while a day is running
+    unsafe { memmap2::Mmap::map(&file) }
+}
+
+// The lines of `input` without their line endings, and without the empty
+// line after a final newline
+pub fn byte_lines(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let input = input.strip_suffix(b"\n").unwrap_or(input);
+    input
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(move |_| !input.is_empty())
+}
+
+// A grid with one cell per byte, None if `cell` rejects a byte or the lines
+// aren't all as long as the first
+pub fn byte_grid<T>(
+    input: &[u8],
+    mut cell: impl FnMut(GridPos, u8) -> Option<T>,
+) -> Option<Grid<T>> {
+    let mut cells = Vec::new();
+    let mut length = None;
+    for (y, line) in byte_lines(input).enumerate() {
+        if *length.get_or_insert(line.len()) != line.len() {
+            return None;
+        }
+        for (x, &b) in line.iter().enumerate() {
+            cells.push(cell(GridPos { x, y }, b)?);
+        }
+    }
+    Grid::from_cells(cells, length.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::prelude::*;
+
+    #[test]
+    fn splits_lines() {
+        assert_eq!(byte_lines(b"ab\r\ncd\n").collect_vec(), [b"ab", b"cd"]);
+        assert_eq!(byte_lines(b"ab\n\ncd").count(), 3);
+        assert_eq!(byte_lines(b"").count(), 0);
+    }
+
+    #[test]
+    fn reads_grids() {
+        let digits = |_, b: u8| b.is_ascii_digit().then_some(b - b'0');
+        let grid = byte_grid(b"123\n456\n", digits).unwrap();
+        assert_eq!((grid.length, grid.height), (3, 2));
+        assert_eq!(grid.get(&GridPos { x: 2, y: 1 }), Some(&6));
+        assert_eq!(byte_grid(b"123\n45\n", digits), None);
+        assert_eq!(byte_grid(b"123\n4x6\n", digits), None);
+        assert_eq!(byte_grid(b"", digits).map(|grid| grid.height), Some(0));
+    }
+}
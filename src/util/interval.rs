@@ -0,0 +1,290 @@
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+
+// Inclusive on both ends, like the puzzles usually give ranges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub fn new(a: i64, b: i64) -> Self {
+        Self {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    // Number of integers covered, never zero. Saturates for intervals
+    // spanning more than usize can count.
+    pub fn size(&self) -> usize {
+        let size = self.end.abs_diff(self.start).saturating_add(1);
+        usize::try_from(size).unwrap_or(usize::MAX)
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        (self.start..=self.end).contains(&x)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start <= end).then_some(Self { start, end })
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..={}", self.start, self.end)
+    }
+}
+
+// Sorted, non-overlapping and non-adjacent intervals; touching ones get merged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisjointIntervals {
+    intervals: Vec<Interval>,
+}
+
+impl DisjointIntervals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        let first = self
+            .intervals
+            .partition_point(|other| other.end.saturating_add(1) < interval.start);
+        let last = self
+            .intervals
+            .partition_point(|other| other.start <= interval.end.saturating_add(1));
+        let merged = self.intervals[first..last]
+            .iter()
+            .fold(interval, |acc, other| Interval {
+                start: acc.start.min(other.start),
+                end: acc.end.max(other.end),
+            });
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        let i = self.intervals.partition_point(|other| other.end < x);
+        self.intervals.get(i).is_some_and(|other| other.contains(x))
+    }
+
+    // Total number of integers covered
+    pub fn len(&self) -> usize {
+        self.intervals
+            .iter()
+            .fold(0, |len, interval| len.saturating_add(interval.size()))
+    }
+
+    // Number of integers covered that lie in `range`
+    pub fn covered_len_within(&self, range: Interval) -> usize {
+        let first = self
+            .intervals
+            .partition_point(|other| other.end < range.start);
+        self.intervals[first..]
+            .iter()
+            .map_while(|interval| interval.intersection(&range))
+            .fold(0, |len, interval| len.saturating_add(interval.size()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval> + '_ {
+        self.intervals.iter()
+    }
+
+    // Every interval grown by `by` on both sides, up to the bounds of i64
+    pub fn widened(&self, by: i64) -> Self {
+        self.iter()
+            .map(|i| Interval::new(i.start.saturating_sub(by), i.end.saturating_add(by)))
+            .collect()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut intervals: Vec<Interval> = Vec::new();
+        for interval in self.intervals.iter().merge(&other.intervals) {
+            match intervals.last_mut() {
+                Some(last) if interval.start <= last.end.saturating_add(1) => {
+                    last.end = last.end.max(interval.end);
+                }
+                _ => intervals.push(*interval),
+            }
+        }
+        Self { intervals }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut ours, mut theirs) = (self.iter().peekable(), other.iter().peekable());
+        while let (Some(a), Some(b)) = (ours.peek(), theirs.peek()) {
+            intervals.extend(a.intersection(b));
+            if a.end < b.end {
+                ours.next();
+            } else {
+                theirs.next();
+            }
+        }
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = Vec::new();
+        let mut cuts = other.intervals.iter().peekable();
+        for interval in &self.intervals {
+            // None once a cut reaches i64::MAX, leaving nothing after it
+            let mut start = Some(interval.start);
+            while let (Some(from), Some(cut)) = (start, cuts.peek()) {
+                if cut.end < from {
+                    cuts.next();
+                    continue;
+                }
+                if cut.start > interval.end {
+                    break;
+                }
+                if cut.start > from {
+                    result.push(Interval::new(from, cut.start - 1));
+                }
+                start = cut.end.checked_add(1);
+                if cut.end > interval.end {
+                    break;
+                }
+                cuts.next();
+            }
+            if let Some(start) = start.filter(|&start| start <= interval.end) {
+                result.push(Interval::new(start, interval.end));
+            }
+        }
+        Self { intervals: result }
+    }
+}
+
+impl FromIterator<Interval> for DisjointIntervals {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut result = Self::new();
+        for interval in iter {
+            result.insert(interval);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::{complexity::assert_scales_subquadratically, random::Rng};
+
+    fn pairs(intervals: &DisjointIntervals) -> Vec<(i64, i64)> {
+        intervals.iter().map(|i| (i.start, i.end)).collect_vec()
+    }
+
+    #[test]
+    fn insert_merges_touching() {
+        let intervals: DisjointIntervals = [(5, 7), (1, 2), (10, 12), (3, 3), (8, 8)]
+            .into_iter()
+            .map(|(a, b)| Interval::new(a, b))
+            .collect();
+        assert_eq!(pairs(&intervals), vec![(1, 3), (5, 8), (10, 12)]);
+        assert_eq!(intervals.len(), 10);
+        assert!(intervals.contains(6) && !intervals.contains(4));
+        let mut bridged = intervals.clone();
+        bridged.insert(Interval::new(4, 9));
+        assert_eq!(pairs(&bridged), vec![(1, 12)]);
+    }
+
+    #[test]
+    fn widen_and_subtract() {
+        let intervals: DisjointIntervals = [Interval::new(0, 0), Interval::new(4, 10)]
+            .into_iter()
+            .collect();
+        assert_eq!(pairs(&intervals.widened(1)), vec![(-1, 1), (3, 11)]);
+        let cuts: DisjointIntervals = [(-5, -1), (5, 5), (7, 8), (10, 20)]
+            .into_iter()
+            .map(|(a, b)| Interval::new(a, b))
+            .collect();
+        assert_eq!(
+            pairs(&intervals.difference(&cuts)),
+            vec![(0, 0), (4, 4), (6, 6), (9, 9)]
+        );
+        assert!(intervals.difference(&intervals).is_empty());
+    }
+
+    #[test]
+    fn union_and_intersection() {
+        let a: DisjointIntervals = [(1, 3), (6, 10), (15, 15)]
+            .into_iter()
+            .map(|(a, b)| Interval::new(a, b))
+            .collect();
+        let b: DisjointIntervals = [(4, 4), (8, 12), (20, 22)]
+            .into_iter()
+            .map(|(a, b)| Interval::new(a, b))
+            .collect();
+        assert_eq!(
+            pairs(&a.union(&b)),
+            vec![(1, 4), (6, 12), (15, 15), (20, 22)]
+        );
+        assert_eq!(a.union(&b), b.union(&a));
+        assert_eq!(pairs(&a.intersection(&b)), vec![(8, 10)]);
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+        assert!(a.intersection(&DisjointIntervals::new()).is_empty());
+        assert_eq!(a.union(&DisjointIntervals::new()), a);
+        assert_eq!(a.covered_len_within(Interval::new(2, 7)), 4);
+        assert_eq!(a.covered_len_within(Interval::new(11, 14)), 0);
+        assert_eq!(
+            a.covered_len_within(Interval::new(i64::MIN, i64::MAX)),
+            a.len()
+        );
+    }
+
+    #[test]
+    fn saturates_near_i64_bounds() {
+        let all = Interval::new(i64::MIN, i64::MAX);
+        assert_eq!(all.size(), usize::MAX);
+        let mut ends: DisjointIntervals = [Interval::new(i64::MAX - 1, i64::MAX)]
+            .into_iter()
+            .collect();
+        ends.insert(Interval::new(i64::MIN, i64::MIN + 2));
+        assert_eq!(ends.len(), 5);
+        assert_eq!(
+            pairs(&ends.widened(3)),
+            vec![(i64::MIN, i64::MIN + 5), (i64::MAX - 4, i64::MAX)]
+        );
+        let upper: DisjointIntervals = [Interval::new(0, i64::MAX)].into_iter().collect();
+        assert_eq!(
+            pairs(&ends.difference(&upper)),
+            vec![(i64::MIN, i64::MIN + 2)]
+        );
+        assert!(upper.difference(&upper).is_empty());
+        let lower: DisjointIntervals = [Interval::new(i64::MIN, -1)].into_iter().collect();
+        assert_eq!(pairs(&upper.difference(&lower)), vec![(0, i64::MAX)]);
+        ends.insert(all);
+        assert_eq!(pairs(&ends), vec![(i64::MIN, i64::MAX)]);
+        assert_eq!(pairs(&ends.widened(1)), vec![(i64::MIN, i64::MAX)]);
+    }
+
+    #[test]
+    #[ignore = "timing based and flaky under load, run alone with --ignored"]
+    fn sweeping_insertion_scales_linearly() {
+        // Intervals in increasing order, some overlapping the last, like a
+        // sweep over sorted input
+        let sweep = |rng: &mut Rng, size: usize| {
+            let mut start = 0;
+            (0..size)
+                .map(|_| {
+                    start += rng.range(-5..=20);
+                    Interval::new(start, start + rng.range(0..=10))
+                })
+                .collect_vec()
+        };
+        assert_scales_subquadratically(
+            |intervals: &Vec<Interval>| intervals.iter().copied().collect::<DisjointIntervals>(),
+            sweep,
+            &[10_000, 40_000, 160_000],
+        );
+    }
+}
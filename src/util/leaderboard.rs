@@ -0,0 +1,207 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use anyhow::{anyhow, Context};
+use serde_json::Value;
+
+use crate::util::{http, infra::Level};
+
+// The site asks for private leaderboards to be fetched at most every 15 minutes
+pub const POLL_INTERVAL_SECS: u64 = 15 * 60;
+
+pub fn url(year: u32, id: u64) -> String {
+    format!("{}/{year}/leaderboard/private/view/{id}.json", http::site())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub id: u64,
+    // Anonymous users have no name on the site
+    pub name: String,
+    pub local_score: u64,
+    // When each star was earned, as Unix seconds
+    pub stars: BTreeMap<(u32, u8), u64>,
+}
+
+// Members by id, from a private leaderboard's JSON
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    pub members: BTreeMap<u64, Member>,
+}
+
+// A star someone earned between two polls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewStar {
+    pub name: String,
+    pub day: u32,
+    pub level: Level,
+    pub timestamp: u64,
+}
+
+impl Display for NewStar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} just got day {} part {}",
+            self.name, self.day, self.level
+        )
+    }
+}
+
+impl Leaderboard {
+    // `{"members": {"id": {"name": ..., "local_score": ...,
+    // "completion_day_level": {"day": {"level": {"get_star_ts": ...}}}}}}`,
+    // ignoring whatever else the site sends along
+    pub fn parse(json: &str) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_str(json).context("Leaderboard is not JSON")?;
+        let members = value
+            .get("members")
+            .and_then(Value::as_object)
+            .context("Leaderboard has no members")?;
+        let members = members
+            .iter()
+            .map(|(id, member)| {
+                let member = parse_member(id, member).with_context(|| format!("Member {id}"))?;
+                Ok((member.id, member))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { members })
+    }
+
+    // Members by descending local score, then name
+    pub fn standings(&self) -> Vec<&Member> {
+        let mut members: Vec<_> = self.members.values().collect();
+        members.sort_by(|a, b| b.local_score.cmp(&a.local_score).then(a.name.cmp(&b.name)));
+        members
+    }
+
+    // Stars in `self` that `previous` didn't have, oldest first
+    pub fn new_stars(&self, previous: &Leaderboard) -> Vec<NewStar> {
+        let mut new_stars: Vec<_> = self
+            .members
+            .values()
+            .flat_map(|member| {
+                let before = previous.members.get(&member.id);
+                member
+                    .stars
+                    .iter()
+                    .filter(move |(key, _)| !before.is_some_and(|b| b.stars.contains_key(key)))
+                    .map(|(&(day, level), &timestamp)| NewStar {
+                        name: member.name.clone(),
+                        day,
+                        level: if level == 1 { Level::One } else { Level::Two },
+                        timestamp,
+                    })
+            })
+            .collect();
+        new_stars.sort_by_key(|star| (star.timestamp, star.day, star.name.clone()));
+        new_stars
+    }
+}
+
+fn parse_member(id: &str, member: &Value) -> anyhow::Result<Member> {
+    let id = id.parse().context("invalid id")?;
+    let name = match member.get("name").and_then(Value::as_str) {
+        Some(name) => name.to_string(),
+        None => format!("(anonymous user #{id})"),
+    };
+    let local_score = member
+        .get("local_score")
+        .and_then(Value::as_u64)
+        .unwrap_or_default();
+    let mut stars = BTreeMap::new();
+    let days = member
+        .get("completion_day_level")
+        .and_then(Value::as_object)
+        .context("no completion_day_level")?;
+    for (day, levels) in days {
+        let day = day
+            .parse()
+            .with_context(|| format!("invalid day {day:?}"))?;
+        let levels = levels
+            .as_object()
+            .ok_or_else(|| anyhow!("day {day} is not an object"))?;
+        for (level, star) in levels {
+            let level = match level.as_str() {
+                "1" => 1,
+                "2" => 2,
+                _ => return Err(anyhow!("invalid level {level:?} on day {day}")),
+            };
+            let timestamp = star
+                .get("get_star_ts")
+                .and_then(Value::as_u64)
+                .with_context(|| format!("no get_star_ts for day {day} level {level}"))?;
+            stars.insert((day, level), timestamp);
+        }
+    }
+    Ok(Member {
+        id,
+        name,
+        local_score,
+        stars,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BEFORE: &str = r#"{"event": "2022", "owner_id": 1, "members": {
+        "1": {"id": 1, "name": "Ada", "stars": 1, "local_score": 10,
+              "completion_day_level": {"16": {"1": {"get_star_ts": 100, "star_index": 5}}}},
+        "2": {"id": 2, "name": null, "stars": 0, "local_score": 0,
+              "completion_day_level": {}}
+    }}"#;
+
+    const AFTER: &str = r#"{"event": "2022", "owner_id": 1, "members": {
+        "1": {"id": 1, "name": "Ada", "stars": 2, "local_score": 20,
+              "completion_day_level": {"16": {"1": {"get_star_ts": 100},
+                                              "2": {"get_star_ts": 300}}}},
+        "2": {"id": 2, "name": null, "stars": 1, "local_score": 9,
+              "completion_day_level": {"16": {"1": {"get_star_ts": 200}}}},
+        "3": {"id": 3, "name": "Grace", "stars": 1, "local_score": 9,
+              "completion_day_level": {"1": {"1": {"get_star_ts": 250}}}}
+    }}"#;
+
+    #[test]
+    fn parses_members() {
+        let leaderboard = Leaderboard::parse(AFTER).unwrap();
+        let names: Vec<_> = leaderboard
+            .standings()
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect();
+        assert_eq!(names, ["Ada", "(anonymous user #2)", "Grace"]);
+        assert_eq!(leaderboard.members[&1].stars[&(16, 2)], 300);
+        assert!(Leaderboard::parse("{}").is_err());
+        let error =
+            Leaderboard::parse(r#"{"members": {"1": {"completion_day_level": {"3": {"4": {}}}}}}"#)
+                .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Member 1: invalid level \"4\" on day 3"
+        );
+    }
+
+    #[test]
+    fn reports_new_stars_in_order() {
+        let before = Leaderboard::parse(BEFORE).unwrap();
+        let after = Leaderboard::parse(AFTER).unwrap();
+        let news: Vec<_> = after
+            .new_stars(&before)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            news,
+            [
+                "(anonymous user #2) just got day 16 part 1",
+                "Grace just got day 1 part 1",
+                "Ada just got day 16 part 2",
+            ]
+        );
+        assert!(after.new_stars(&after).is_empty());
+    }
+}
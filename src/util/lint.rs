@@ -0,0 +1,40 @@
+use std::fmt::Display;
+
+use crate::util::span::line_col;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub line: usize,
+    // When the finding is about one spot of the line
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    // At the character `offset` bytes into `input`
+    pub fn at(input: &str, offset: usize, message: impl Into<String>) -> Self {
+        let (line, column) = line_col(input, offset);
+        Self {
+            line,
+            column: Some(column),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "line {}, column {column}: {}", self.line, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
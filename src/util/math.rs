@@ -0,0 +1,39 @@
+pub fn gcd(a: u64, b: u64) -> u64 {
+    let mut x = a;
+    let mut y = b;
+    while y != 0 {
+        let z = x.rem_euclid(y);
+        x = y;
+        y = z;
+    }
+    x
+}
+
+pub fn lcm(a: u64, b: u64) -> u64 {
+    a * (b / gcd(a, b))
+}
+
+// Least squares slope of log(y) against log(x), the k in y ~ x^k. Needs two
+// points with different x, all positive.
+pub fn log_log_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let logs: Vec<_> = points.iter().map(|(x, y)| (x.ln(), y.ln())).collect();
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let spread: f64 = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let covariance: f64 = logs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    (spread > 0.0).then(|| covariance / spread)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn slope_of_powers() {
+        let squares = [1.0, 10.0, 100.0].map(|x: f64| (x, 3.0 * x * x));
+        assert!((log_log_slope(&squares).unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(log_log_slope(&[(5.0, 1.0), (5.0, 2.0)]), None);
+        assert_eq!(log_log_slope(&[]), None);
+    }
+}
@@ -0,0 +1,158 @@
+use std::{cell::RefCell, collections::HashMap, hash::Hash};
+
+// The cached value for `key`, computing it first if there is none. `compute`
+// gets the cache back for recursive calls:
+//
+//     fn ways(n: u64, cache: &mut HashMap<u64, u64>) -> u64 {
+//         memoize(cache, n, |cache| match n {
+//             0 | 1 => 1,
+//             _ => ways(n - 1, cache) + ways(n - 2, cache),
+//         })
+//     }
+pub fn memoize<K, V, F>(cache: &mut HashMap<K, V>, key: K, compute: F) -> V
+where
+    K: Hash + Eq,
+    V: Clone,
+    F: FnOnce(&mut HashMap<K, V>) -> V,
+{
+    if let Some(value) = cache.get(&key) {
+        return value.clone();
+    }
+    let value = compute(cache);
+    cache.insert(key, value.clone());
+    value
+}
+
+// Hits and misses of one labelled cache, for aoc run --stats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoStats {
+    pub label: &'static str,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl MemoStats {
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 / (self.hits + self.misses).max(1) as f64
+    }
+}
+
+thread_local! {
+    // In the order labels were first recorded, like the timings
+    static STATS: RefCell<Vec<MemoStats>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(label: &'static str, hits: usize, misses: usize) {
+    STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        match stats.iter_mut().find(|stats| stats.label == label) {
+            Some(stats) => {
+                stats.hits += hits;
+                stats.misses += misses;
+            }
+            None => stats.push(MemoStats {
+                label,
+                hits,
+                misses,
+            }),
+        }
+    })
+}
+
+// Everything recorded on this thread since the last call
+pub fn take() -> Vec<MemoStats> {
+    STATS.with(|stats| stats.take())
+}
+
+// A cache that counts its hits and misses, and records them under its label
+// when dropped
+#[derive(Debug)]
+pub struct Memo<K, V> {
+    label: &'static str,
+    cache: HashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            cache: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    // Like `memoize`, with the whole Memo handed to `compute`
+    pub fn get_or(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+impl<K, V> Drop for Memo<K, V> {
+    fn drop(&mut self) {
+        record(self.label, self.hits, self.misses);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ways(n: u64, cache: &mut HashMap<u64, u64>) -> u64 {
+        memoize(cache, n, |cache| match n {
+            0 | 1 => 1,
+            _ => ways(n - 1, cache) + ways(n - 2, cache),
+        })
+    }
+
+    fn paths(x: u32, y: u32, memo: &mut Memo<(u32, u32), u64>) -> u64 {
+        memo.get_or((x, y), |memo| match (x, y) {
+            (0, _) | (_, 0) => 1,
+            _ => paths(x - 1, y, memo) + paths(x, y - 1, memo),
+        })
+    }
+
+    #[test]
+    fn memoizes_recursion() {
+        let mut cache = HashMap::new();
+        assert_eq!(ways(90, &mut cache), 4_660_046_610_375_530_309);
+        assert_eq!(cache.len(), 91);
+    }
+
+    #[test]
+    fn counts_hits() {
+        take();
+        let mut memo = Memo::new("lattice paths");
+        assert_eq!(paths(16, 16, &mut memo), 601_080_390);
+        // Everything but the corner, which no cell needs
+        assert_eq!(memo.len(), 17 * 17 - 1);
+        drop(memo);
+        let stats = take();
+        assert_eq!(stats.len(), 1);
+        // Each of the 16 * 16 cells off the edges asks for two others
+        let calls = 1 + 2 * 16 * 16;
+        assert_eq!(
+            (stats[0].misses, stats[0].hits),
+            (17 * 17 - 1, calls - (17 * 17 - 1))
+        );
+        assert!(stats[0].hit_rate() > 0.4);
+        assert!(take().is_empty());
+    }
+}
@@ -1,2 +1,41 @@
+#[cfg(feature = "analytics")]
+pub mod analytics;
+pub mod answers;
+pub mod bench;
+pub mod bundle;
+#[cfg(test)]
+pub mod complexity;
+pub mod config;
+pub mod counter;
+pub mod day;
+pub mod diamond;
+pub mod dot;
+pub mod export;
+pub mod expr;
+pub mod flood;
+pub mod glyphs;
+pub mod grid;
+pub mod hash;
+pub mod http;
 pub mod infra;
+pub mod input;
+pub mod interval;
+pub mod leaderboard;
+pub mod lint;
+pub mod math;
+pub mod memo;
+pub mod neighbors;
+pub mod nested;
+pub mod parse;
 pub mod prelude;
+pub mod progress;
+pub mod random;
+pub mod rational;
+pub mod rope_list;
+pub mod search;
+pub mod simulation;
+pub mod span;
+pub mod sparsegrid;
+pub mod timer;
+pub mod visited;
+pub mod viz;
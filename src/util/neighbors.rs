@@ -0,0 +1,92 @@
+use std::ops::Range;
+
+use crate::util::grid::GridPos;
+
+// Which cells count as next to one, as offsets in the order they are visited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pattern(pub &'static [(isize, isize)]);
+
+pub const ORTHO4: Pattern = Pattern(&[(-1, 0), (1, 0), (0, -1), (0, 1)]);
+pub const DIAG8: Pattern = Pattern(&[
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+]);
+// Straight down first, then down left and down right, like falling sand
+pub const SAND3: Pattern = Pattern(&[(0, 1), (-1, 1), (1, 1)]);
+
+// The columns and rows a neighbour has to fall into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bounds {
+    pub xs: Range<usize>,
+    pub ys: Range<usize>,
+}
+
+impl Bounds {
+    // All of a `length` by `height` grid
+    pub fn grid(length: usize, height: usize) -> Self {
+        Self {
+            xs: 0..length,
+            ys: 0..height,
+        }
+    }
+
+    pub fn contains(&self, pos: GridPos) -> bool {
+        self.xs.contains(&pos.x) && self.ys.contains(&pos.y)
+    }
+}
+
+impl Pattern {
+    // The cell at each offset from `pos` in order, None for those out of
+    // bounds, for when which one is missing matters
+    pub fn around(self, pos: GridPos, bounds: Bounds) -> impl Iterator<Item = Option<GridPos>> {
+        self.0.iter().map(move |&(dx, dy)| {
+            let x = pos.x.checked_add_signed(dx)?;
+            let y = pos.y.checked_add_signed(dy)?;
+            Some(GridPos { x, y }).filter(|&next| bounds.contains(next))
+        })
+    }
+}
+
+// The cells of `pattern` around `pos` that are within `bounds`
+pub fn neighbors_of(
+    pos: GridPos,
+    pattern: Pattern,
+    bounds: Bounds,
+) -> impl Iterator<Item = GridPos> {
+    pattern.around(pos, bounds).flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::prelude::*;
+
+    #[test]
+    fn stays_in_bounds() {
+        let pos = GridPos { x: 0, y: 1 };
+        assert_eq!(
+            neighbors_of(pos, ORTHO4, Bounds::grid(3, 2)).collect_vec(),
+            [GridPos { x: 1, y: 1 }, GridPos { x: 0, y: 0 }]
+        );
+        assert_eq!(neighbors_of(pos, DIAG8, Bounds::grid(3, 3)).count(), 5);
+        assert_eq!(
+            SAND3.around(pos, Bounds::grid(3, 3)).collect_vec(),
+            [
+                Some(GridPos { x: 0, y: 2 }),
+                None,
+                Some(GridPos { x: 1, y: 2 })
+            ]
+        );
+        let window = Bounds { xs: 5..8, ys: 0..3 };
+        assert_eq!(
+            neighbors_of(GridPos { x: 5, y: 1 }, SAND3, window).collect_vec(),
+            [GridPos { x: 5, y: 2 }, GridPos { x: 6, y: 2 }]
+        );
+    }
+}
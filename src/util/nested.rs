@@ -0,0 +1,228 @@
+use std::{cmp::Ordering, fmt};
+
+use nom::{
+    branch::alt,
+    character::complete::{char, space0},
+    combinator::{cond, cut, map, opt},
+    multi::separated_list0,
+    sequence::{delimited, preceded},
+    IResult,
+};
+
+use crate::util::parse::DepthGuard;
+
+// Bracketed lists of lists and leaves, like `[1,[2,[]]]`. The derived order
+// is structural, with leaves before lists, so it agrees with `==`; day 13's
+// packet order is `packet_cmp`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Nested<T> {
+    Leaf(T),
+    List(Vec<Nested<T>>),
+}
+
+impl<T> Nested<T> {
+    pub fn map<U>(&self, f: &impl Fn(&T) -> U) -> Nested<U> {
+        match self {
+            Nested::Leaf(t) => Nested::Leaf(f(t)),
+            Nested::List(items) => Nested::List(items.iter().map(|item| item.map(f)).collect()),
+        }
+    }
+
+    // How many lists deep the innermost item is, 0 for a leaf on its own
+    pub fn depth(&self) -> usize {
+        match self {
+            Nested::Leaf(_) => 0,
+            Nested::List(items) => 1 + items.iter().map(Nested::depth).max().unwrap_or(0),
+        }
+    }
+
+    // The leaves from left to right, however deep they are
+    pub fn leaves(&self) -> impl Iterator<Item = &T> {
+        let mut stack = vec![std::slice::from_ref(self).iter()];
+        std::iter::from_fn(move || loop {
+            match stack.last_mut()?.next() {
+                Some(Nested::Leaf(t)) => return Some(t),
+                Some(Nested::List(items)) => stack.push(items.iter()),
+                None => {
+                    stack.pop();
+                }
+            }
+        })
+    }
+}
+
+impl<T: Ord> Nested<T> {
+    // Lists compare item by item, and a leaf compared with a list counts as
+    // the list holding just that leaf. So unlike `==`, this can find
+    // different nestings like `2` and `[[2]]` equal.
+    pub fn packet_cmp(&self, other: &Self) -> Ordering {
+        let items_cmp = |xs: &[Self], ys: &[Self]| {
+            xs.iter()
+                .zip(ys)
+                .map(|(x, y)| x.packet_cmp(y))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| xs.len().cmp(&ys.len()))
+        };
+        match (self, other) {
+            (Nested::Leaf(x), Nested::Leaf(y)) => x.cmp(y),
+            (Nested::Leaf(_), Nested::List(ys)) => items_cmp(std::slice::from_ref(self), ys),
+            (Nested::List(xs), Nested::Leaf(_)) => items_cmp(xs, std::slice::from_ref(other)),
+            (Nested::List(xs), Nested::List(ys)) => items_cmp(xs, ys),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Nested<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Nested::Leaf(t) => t.fmt(f),
+            Nested::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    item.fmt(f)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+// A bracketed list with leaves parsed by `leaf`. Spaces around commas and
+// brackets are fine, and so is a comma after the last item. Once a list has
+// started anything else fails right where it goes wrong, rather than
+// wherever the caller gives up, including at a list nested more than
+// `DepthGuard::DEFAULT_MAX` deep.
+pub fn nested_list<'a, T>(
+    leaf: impl Fn(&'a str) -> IResult<&'a str, T>,
+) -> impl Fn(&'a str) -> IResult<&'a str, Nested<T>> {
+    nested_list_max_depth(leaf, DepthGuard::DEFAULT_MAX)
+}
+
+// Like `nested_list`, failing at lists nested more than `max_depth` deep
+pub fn nested_list_max_depth<'a, T>(
+    leaf: impl Fn(&'a str) -> IResult<&'a str, T>,
+    max_depth: usize,
+) -> impl Fn(&'a str) -> IResult<&'a str, Nested<T>> {
+    move |input| {
+        map(
+            |input| list(&leaf, DepthGuard::new(max_depth), input),
+            Nested::List,
+        )(input)
+    }
+}
+
+fn comma(input: &str) -> IResult<&str, char> {
+    delimited(space0, char(','), space0)(input)
+}
+
+fn list<'a, T>(
+    leaf: &impl Fn(&'a str) -> IResult<&'a str, T>,
+    guard: DepthGuard,
+    input: &'a str,
+) -> IResult<&'a str, Vec<Nested<T>>> {
+    let (rest, _) = char('[')(input)?;
+    let guard = guard.descend(input)?;
+    cut(move |input| items(leaf, guard, input))(rest)
+}
+
+fn items<'a, T>(
+    leaf: &impl Fn(&'a str) -> IResult<&'a str, T>,
+    guard: DepthGuard,
+    input: &'a str,
+) -> IResult<&'a str, Vec<Nested<T>>> {
+    let (input, _) = space0(input)?;
+    let (input, items) = separated_list0(
+        comma,
+        alt((
+            map(|input| list(leaf, guard, input), Nested::List),
+            map(leaf, Nested::Leaf),
+        )),
+    )(input)?;
+    let (input, _) = cond(!items.is_empty(), opt(comma))(input)?;
+    let (input, _) = preceded(space0, char(']'))(input)?;
+    Ok((input, items))
+}
+
+#[cfg(test)]
+mod test {
+    use nom::character::complete::{alpha1, u8};
+
+    use super::*;
+    use crate::util::parse::parse_complete;
+
+    fn numbers(input: &str) -> Nested<u8> {
+        parse_complete(nested_list(u8), input).unwrap()
+    }
+
+    #[test]
+    fn parses_any_leaves() {
+        let words = parse_complete(nested_list(alpha1), "[a, [bc, []], d,]").unwrap();
+        assert_eq!(words.to_string(), "[a,[bc,[]],d]");
+        assert_eq!(
+            words.leaves().copied().collect::<Vec<_>>(),
+            ["a", "bc", "d"]
+        );
+        assert_eq!(
+            words.map(&|word| word.len()),
+            numbers("[1,[2,[]],1]").map(&|n| *n as usize)
+        );
+        let error = parse_complete(nested_list(alpha1), "[a,[b,7]]").unwrap_err();
+        assert_eq!(error.line_col(), (1, 7));
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let deep = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert_eq!(
+            parse_complete(nested_list_max_depth(u8, 3), &deep(3))
+                .unwrap()
+                .depth(),
+            3
+        );
+        let error = parse_complete(nested_list_max_depth(u8, 3), &deep(4)).unwrap_err();
+        assert_eq!(error.offset(), 3);
+        let error = parse_complete(nested_list(u8), &deep(100_000)).unwrap_err();
+        assert_eq!(error.offset(), DepthGuard::DEFAULT_MAX);
+    }
+
+    #[test]
+    fn depth_and_leaves() {
+        assert_eq!(Nested::Leaf(3).depth(), 0);
+        assert_eq!(numbers("[]").depth(), 1);
+        assert_eq!(numbers("[1,[[]],[2]]").depth(), 3);
+        let leaves = numbers("[[[1]],2,[],[3,[4]]]")
+            .leaves()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(leaves, [1, 2, 3, 4]);
+        assert_eq!(numbers("[[],[[]]]").leaves().count(), 0);
+    }
+
+    #[test]
+    fn leaves_compare_like_lists() {
+        let packet_cmp = |a, b| numbers(a).packet_cmp(&numbers(b));
+        assert_eq!(packet_cmp("[1,1,3,1,1]", "[1,1,5,1,1]"), Ordering::Less);
+        assert_eq!(packet_cmp("[[1],[2,3,4]]", "[[1],4]"), Ordering::Less);
+        assert_eq!(packet_cmp("[9]", "[[8,7,6]]"), Ordering::Greater);
+        assert_eq!(packet_cmp("[[4,4],4,4]", "[[4,4],4,4,4]"), Ordering::Less);
+        assert_eq!(packet_cmp("[[[]]]", "[[]]"), Ordering::Greater);
+        assert_eq!(packet_cmp("[[2]]", "[2]"), Ordering::Equal);
+        assert_eq!(Nested::Leaf(2).packet_cmp(&numbers("[2]")), Ordering::Equal);
+    }
+
+    #[test]
+    fn ordering_agrees_with_equality() {
+        let (a, b) = (numbers("[[2]]"), numbers("[2]"));
+        assert_ne!(a, b);
+        assert_ne!(a.cmp(&b), Ordering::Equal);
+        assert!(Nested::Leaf(9) < numbers("[]"));
+        assert!(numbers("[1,[2]]") < numbers("[1,[3]]"));
+        assert_eq!(
+            numbers("[1,[2]]").cmp(&numbers("[1, [2]]")),
+            Ordering::Equal
+        );
+    }
+}
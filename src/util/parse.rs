@@ -0,0 +1,292 @@
+use std::{
+    cell::Cell,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme, LabeledSpan, SourceSpan};
+use nom::{combinator::all_consuming, error::ErrorKind, Finish, IResult};
+use smallvec::SmallVec;
+
+use crate::util::span::{line_col, Spanned};
+
+#[derive(Debug)]
+pub struct ParseError {
+    src: String,
+    offset: usize,
+    kind: ErrorKind,
+}
+
+impl ParseError {
+    pub fn new(input: &str, rest: &str, kind: ErrorKind) -> Self {
+        Self {
+            src: input.to_string(),
+            offset: input.len() - rest.len(),
+            kind,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    // 1-based line and column of the offending character
+    pub fn line_col(&self) -> (usize, usize) {
+        line_col(&self.src, self.offset)
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+            .render_report(&mut out, self)
+            .unwrap();
+        out
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (line, col) = self.line_col();
+        write!(
+            f,
+            "Failed to parse input at line {line}, column {col} ({})",
+            self.kind.description()
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Diagnostic for ParseError {
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.src)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let len = self.src[self.offset..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+        let span = Spanned::new((), self.offset, self.offset + len);
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some("unexpected input".to_string()),
+            SourceSpan::from(&span),
+        ))))
+    }
+}
+
+// Runs `parser` over all of `input` but its final line breaks, failing at
+// the first character it leaves over, past any line breaks in front of it
+pub fn parse_complete<'a, T>(
+    parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+    input: &'a str,
+) -> Result<T, ParseError> {
+    let trimmed = input.trim_end_matches(['\r', '\n']);
+    all_consuming(parser)(trimmed)
+        .finish()
+        .map(|(_, result)| result)
+        .map_err(|e| {
+            let rest = match e.code {
+                ErrorKind::Eof => e.input.trim_start_matches(['\r', '\n']),
+                _ => e.input,
+            };
+            ParseError::new(trimmed, rest, e.code)
+        })
+}
+
+thread_local! {
+    // Whether this thread panicked over a ParseError since the last
+    // `take_parse_panic`
+    static PARSE_PANICKED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn panic_with_report(error: &ParseError) -> ! {
+    PARSE_PANICKED.with(|panicked| panicked.set(true));
+    panic!("\n{}", error.render())
+}
+
+// Whether a caught panic came from `parse_or_report` or `or_report` failing
+// to parse, rather than from a bug or an answer that doesn't exist. Clears
+// the flag for the next panic.
+pub fn take_parse_panic() -> bool {
+    PARSE_PANICKED.with(|panicked| panicked.replace(false))
+}
+
+// For the level functions, which have no error channel: panics with the
+// rendered report instead of nom's debug output.
+pub fn parse_or_report<'a, T>(
+    parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+    input: &'a str,
+) -> T {
+    parse_complete(parser, input).unwrap_or_else(|e| panic_with_report(&e))
+}
+
+// Like `parse_or_report` for parsing that checks more than the syntax, like
+// day 11's monkeys all being declared. Errors that aren't a ParseError panic
+// with their chain of causes rather than unwrap's debug output.
+pub fn or_report<T>(result: anyhow::Result<T>) -> T {
+    result.unwrap_or_else(|e| match e.downcast_ref::<ParseError>() {
+        Some(parse_error) => panic_with_report(parse_error),
+        None => panic!("{e:#}"),
+    })
+}
+
+// How deep a recursive parser is, so that deeply nested input fails to
+// parse instead of overflowing the stack. Each recursive call descends,
+// and descending past `max` levels fails right there with `TooLarge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthGuard {
+    depth: usize,
+    max: usize,
+}
+
+impl DepthGuard {
+    // Deep enough for any puzzle input, and shallow enough for the 2MB
+    // stacks of test threads
+    pub const DEFAULT_MAX: usize = 256;
+
+    pub fn new(max: usize) -> Self {
+        Self { depth: 0, max }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn descend(self, input: &str) -> Result<Self, nom::Err<nom::error::Error<&str>>> {
+        if self.depth >= self.max {
+            return Err(nom::Err::Failure(nom::error::Error::new(
+                input,
+                ErrorKind::TooLarge,
+            )));
+        }
+        Ok(Self {
+            depth: self.depth + 1,
+            ..self
+        })
+    }
+}
+
+impl Default for DepthGuard {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX)
+    }
+}
+
+// Every integer in `line`, ignoring whatever separates them. A minus directly
+// in front of the digits makes them negative unless it follows a digit, so
+// "x=-2" gives -2 while the range "7-3" gives 7 and 3.
+// Panics on numbers that don't fit into T, to not silently skip them.
+pub fn ints<T>(line: &str) -> SmallVec<[T; 8]>
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    let bytes = line.as_bytes();
+    let mut result = SmallVec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let negative = i > 0 && bytes[i - 1] == b'-' && (i < 2 || !bytes[i - 2].is_ascii_digit());
+        let start = if negative { i - 1 } else { i };
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        let token = &line[start..i];
+        result.push(
+            token
+                .parse()
+                .unwrap_or_else(|e| panic!("{token} is out of range: {e:?}")),
+        );
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use nom::{
+        character::complete::{i64, line_ending},
+        multi::separated_list1,
+    };
+
+    use super::*;
+    use anyhow::Context;
+
+    fn numbers(input: &str) -> IResult<&str, Vec<i64>> {
+        separated_list1(line_ending, i64)(input)
+    }
+
+    #[test]
+    fn reports_line_and_column() {
+        let error = parse_complete(numbers, "12\n34\n5x6").unwrap_err();
+        assert_eq!(error.offset(), 7);
+        assert_eq!(error.line_col(), (3, 2));
+        assert!(error.to_string().contains("line 3, column 2"));
+        let rendered = error.render();
+        assert!(rendered.contains("5x6"), "{rendered}");
+        assert!(rendered.contains("unexpected input"), "{rendered}");
+    }
+
+    #[test]
+    fn ints_in_lines() {
+        assert_eq!(
+            ints::<i64>("Sensor at x=2, y=-18: closest beacon is at x=-2, y=15")[..],
+            [2, -18, -2, 15]
+        );
+        assert_eq!(ints::<u32>("2-4,6-8")[..], [2, 4, 6, 8]);
+        assert_eq!(ints::<usize>("move 13 from 1 to 10")[..], [13, 1, 10]);
+        assert_eq!(ints::<i32>("-5--3 a-1")[..], [-5, -3, -1]);
+        assert!(ints::<i64>("noop").is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "300 is out of range")]
+    fn ints_out_of_range() {
+        ints::<u8>("1 300");
+    }
+
+    #[test]
+    fn depth_guard_stops_descending() {
+        let guard = DepthGuard::new(2);
+        let guard = guard.descend("a").unwrap().descend("b").unwrap();
+        assert_eq!(guard.depth(), 2);
+        assert_eq!(
+            guard.descend("c"),
+            Err(nom::Err::Failure(nom::error::Error::new(
+                "c",
+                ErrorKind::TooLarge
+            )))
+        );
+    }
+
+    #[test]
+    fn passes_through_success() {
+        assert_eq!(parse_complete(numbers, "1\n2").unwrap(), vec![1, 2]);
+        assert_eq!(parse_complete(numbers, "1\r\n2\r\n").unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "line 2: no item")]
+    fn reports_the_chain_of_other_errors() {
+        let result: anyhow::Result<()> = Err(anyhow::anyhow!("no item")).context("line 2");
+        or_report(result);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected input")]
+    fn reports_parse_errors_with_their_span() {
+        or_report(parse_complete(numbers, "1\nx").map_err(anyhow::Error::from));
+    }
+
+    #[test]
+    fn rejects_what_the_parser_leaves_over() {
+        let error = parse_complete(numbers, "1\n2\nx\n3\n").unwrap_err();
+        assert_eq!(error.line_col(), (3, 1));
+        let error = parse_complete(numbers, "1\n2 \n\n").unwrap_err();
+        assert_eq!(error.line_col(), (2, 2));
+        assert_eq!(error.kind, ErrorKind::Eof);
+    }
+}
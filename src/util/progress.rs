@@ -0,0 +1,150 @@
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// Counters that threads working on one search bump together, like nodes
+// expanded and states pruned. Adding is a relaxed atomic increment, so
+// threads never wait on each other, and anything holding a reference can
+// read the counts while the search runs. The totals go to aoc run --stats
+// when it is dropped:
+//
+//     let progress = AtomicProgress::new("day16 search", &["expanded", "pruned"]);
+//     let expanded = progress.counter("expanded");
+//     thread::scope(|s| s.spawn(|| expanded.add(1)));
+#[derive(Debug)]
+pub struct AtomicProgress {
+    label: &'static str,
+    counters: Vec<ProgressCounter>,
+}
+
+#[derive(Debug)]
+pub struct ProgressCounter {
+    name: &'static str,
+    count: AtomicU64,
+}
+
+impl ProgressCounter {
+    pub fn add(&self, n: u64) {
+        self.count.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+impl AtomicProgress {
+    pub fn new(label: &'static str, names: &[&'static str]) -> Self {
+        Self {
+            label,
+            counters: names
+                .iter()
+                .map(|&name| ProgressCounter {
+                    name,
+                    count: AtomicU64::new(0),
+                })
+                .collect(),
+        }
+    }
+
+    // Look it up once and keep it for hot loops. Panics on a name that
+    // wasn't passed to `new`.
+    pub fn counter(&self, name: &str) -> &ProgressCounter {
+        self.counters
+            .iter()
+            .find(|counter| counter.name == name)
+            .unwrap_or_else(|| panic!("{} has no counter {name}", self.label))
+    }
+
+    // The counts so far, while other threads may still be adding
+    pub fn snapshot(&self) -> ProgressStats {
+        ProgressStats {
+            label: self.label,
+            counts: self
+                .counters
+                .iter()
+                .map(|counter| (counter.name, counter.get()))
+                .collect(),
+        }
+    }
+}
+
+// What one labelled set of counters added up to, for aoc run --stats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressStats {
+    pub label: &'static str,
+    pub counts: Vec<(&'static str, u64)>,
+}
+
+thread_local! {
+    // In the order labels were first recorded, like the timings
+    static STATS: RefCell<Vec<ProgressStats>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(recorded: ProgressStats) {
+    STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        match stats.iter_mut().find(|stats| stats.label == recorded.label) {
+            Some(stats) => {
+                for (name, count) in recorded.counts {
+                    match stats.counts.iter_mut().find(|(known, _)| *known == name) {
+                        Some((_, total)) => *total += count,
+                        None => stats.counts.push((name, count)),
+                    }
+                }
+            }
+            None => stats.push(recorded),
+        }
+    })
+}
+
+// Everything recorded on this thread since the last call. Progress is
+// recorded on the thread that drops it, usually the one that created it.
+pub fn take() -> Vec<ProgressStats> {
+    STATS.with(|stats| stats.take())
+}
+
+impl Drop for AtomicProgress {
+    fn drop(&mut self) {
+        record(self.snapshot());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn adds_up_across_threads() {
+        take();
+        for _ in 0..2 {
+            let progress = AtomicProgress::new("search", &["expanded", "pruned"]);
+            let (expanded, pruned) = (progress.counter("expanded"), progress.counter("pruned"));
+            thread::scope(|s| {
+                for i in 0..4 {
+                    s.spawn(move || {
+                        for _ in 0..1000 {
+                            expanded.add(1);
+                        }
+                        pruned.add(i);
+                    });
+                }
+            });
+            assert_eq!(
+                progress.snapshot().counts,
+                [("expanded", 4000), ("pruned", 6)]
+            );
+        }
+        assert_eq!(
+            take(),
+            [ProgressStats {
+                label: "search",
+                counts: vec![("expanded", 8000), ("pruned", 12)]
+            }]
+        );
+        assert!(take().is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+use std::ops::RangeInclusive;
+
+// SplitMix64: tiny, seedable and plenty random for generating puzzle inputs
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let width = range.end().abs_diff(*range.start()) + 1;
+        range.start().wrapping_add_unsigned(self.next_u64() % width)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stays_in_range_and_repeats_per_seed() {
+        let mut rng = Rng::new(3);
+        let values: Vec<_> = (0..1000).map(|_| rng.range(-2..=2)).collect();
+        assert!(values.iter().all(|x| (-2..=2).contains(x)));
+        assert!((-2..=2).all(|x| values.contains(&x)));
+        let mut again = Rng::new(3);
+        assert_eq!(
+            values[..5],
+            (0..5).map(|_| again.range(-2..=2)).collect::<Vec<_>>()
+        );
+    }
+}
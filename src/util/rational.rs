@@ -0,0 +1,163 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+// Exact fraction kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: i128,
+    denom: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let mut x = a.abs();
+    let mut y = b.abs();
+    while y != 0 {
+        let z = x % y;
+        x = y;
+        y = z;
+    }
+    x
+}
+
+impl Rational {
+    pub const ZERO: Self = Self { numer: 0, denom: 1 };
+    pub const ONE: Self = Self { numer: 1, denom: 1 };
+
+    pub fn new(numer: i128, denom: i128) -> Option<Self> {
+        if denom == 0 {
+            return None;
+        }
+        let divisor = gcd(numer, denom) * denom.signum();
+        Some(Self {
+            numer: numer / divisor,
+            denom: denom / divisor,
+        })
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.numer
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.numer)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer == 0
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        Self::new(self.numer * rhs.denom, self.denom * rhs.numer)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self {
+            numer: value.into(),
+            denom: 1,
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            self.numer.fmt(f)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.numer * rhs.denom + rhs.numer * self.denom,
+            self.denom * rhs.denom,
+        )
+        .unwrap()
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(self.numer * rhs.numer, self.denom * rhs.denom).unwrap()
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).expect("division by zero")
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numer * other.denom).cmp(&(other.numer * self.denom))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalizes() {
+        assert_eq!(Rational::new(6, -4), Rational::new(-3, 2));
+        assert_eq!(Rational::new(0, 5), Some(Rational::ZERO));
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(Rational::new(-3, 2).unwrap().to_string(), "-3/2");
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        let third = Rational::new(1, 3).unwrap();
+        assert_eq!(third + third + third, Rational::ONE);
+        assert_eq!((Rational::from(7) / 2.into()) * 2.into(), 7.into());
+        assert!(third < Rational::new(1, 2).unwrap());
+        assert_eq!(Rational::ONE.checked_div(Rational::ZERO), None);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::util::prelude::*;
+
+// Sequence split into blocks of roughly sqrt(n) ids, so that removing and
+// inserting by position costs O(sqrt(n)) instead of shifting the whole Vec.
+// Elements keep the id they were created with, which allows looking up
+// their current position without scanning all values.
+#[derive(Debug)]
+pub struct RopeList<T> {
+    values: Vec<T>,
+    blocks: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+    block_size: usize,
+}
+
+impl<T> FromIterator<T> for RopeList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values = iter.into_iter().collect_vec();
+        let block_size = ((values.len() as f64).sqrt() as usize).max(1);
+        let blocks = (0..values.len())
+            .chunks(block_size)
+            .into_iter()
+            .map(|chunk| chunk.collect_vec())
+            .collect_vec();
+        let mut result = Self {
+            block_of: vec![0; values.len()],
+            values,
+            blocks,
+            block_size,
+        };
+        result.reindex_from(0);
+        result
+    }
+}
+
+impl<T> RopeList<T> {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn value(&self, id: usize) -> &T {
+        &self.values[id]
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.id_at(index).map(|id| &self.values[id])
+    }
+
+    pub fn id_at(&self, index: usize) -> Option<usize> {
+        let (block, offset) = self.locate(index)?;
+        self.blocks[block].get(offset).copied()
+    }
+
+    pub fn index_of(&self, id: usize) -> usize {
+        let block = self.block_of[id];
+        let before: usize = self.blocks[..block].iter().map(Vec::len).sum();
+        before + self.blocks[block].iter().position(|i| *i == id).unwrap()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.blocks.iter().flatten().map(|id| &self.values[*id])
+    }
+
+    // Moves the element at `index` by `offset` positions as if the list were circular,
+    // i.e. with the element itself taken out, and returns its new index.
+    // Landing in front of the first element is the same as appending, and the latter is used.
+    pub fn move_by(&mut self, index: usize, offset: i64) -> usize {
+        let len = self.len();
+        if len < 2 || index >= len {
+            return index;
+        }
+        let modulus = len as i64 - 1;
+        let target = match (index as i64 + offset.rem_euclid(modulus)).rem_euclid(modulus) {
+            0 => modulus,
+            target => target,
+        } as usize;
+        if target != index {
+            let id = self.remove_at(index);
+            self.insert_at(target, id);
+        }
+        target
+    }
+
+    pub fn nth_after(&self, value: &T, k: usize) -> Option<&T>
+    where
+        T: PartialEq,
+    {
+        let start = self.iter().position(|t| t == value)?;
+        self.get((start + k) % self.len())
+    }
+
+    fn locate(&self, index: usize) -> Option<(usize, usize)> {
+        let mut offset = index;
+        for (i, block) in self.blocks.iter().enumerate() {
+            if offset < block.len() {
+                return Some((i, offset));
+            }
+            offset -= block.len();
+        }
+        let last = self.blocks.len().checked_sub(1)?;
+        (offset == 0).then(|| (last, self.blocks[last].len()))
+    }
+
+    fn remove_at(&mut self, index: usize) -> usize {
+        let (block, offset) = self.locate(index).unwrap();
+        self.blocks[block].remove(offset)
+    }
+
+    fn insert_at(&mut self, index: usize, id: usize) {
+        let (block, offset) = self.locate(index).unwrap();
+        self.blocks[block].insert(offset, id);
+        self.block_of[id] = block;
+        if self.blocks[block].len() > 2 * self.block_size {
+            let tail = self.blocks[block].split_off(self.block_size);
+            self.blocks.insert(block + 1, tail);
+            self.reindex_from(block + 1);
+        }
+    }
+
+    fn reindex_from(&mut self, start: usize) {
+        for (i, block) in self.blocks.iter().enumerate().skip(start) {
+            for id in block {
+                self.block_of[*id] = i;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mix(values: &[i64]) -> RopeList<i64> {
+        let mut list: RopeList<i64> = values.iter().copied().collect();
+        for id in 0..list.len() {
+            let index = list.index_of(id);
+            list.move_by(index, *list.value(id));
+        }
+        list
+    }
+
+    #[test]
+    fn mixing_given_example() {
+        let list = mix(&[1, 2, -3, 3, -2, 0, 4]);
+        assert_eq!(
+            list.iter().copied().collect_vec(),
+            vec![1, 2, -3, 4, 0, 3, -2]
+        );
+        let coordinates = [1000, 2000, 3000].map(|k| *list.nth_after(&0, k).unwrap());
+        assert_eq!(coordinates, [4, -3, 2]);
+    }
+
+    #[test]
+    fn matches_naive_vec() {
+        let mut seed: i64 = 17;
+        let values = (0..500)
+            .map(|_| {
+                seed = (seed * 1_103_515_245 + 12345).rem_euclid(1 << 31);
+                seed - (1 << 30)
+            })
+            .collect_vec();
+        let mut naive = values.iter().copied().enumerate().collect_vec();
+        for id in 0..values.len() {
+            let index = naive.iter().position(|(i, _)| *i == id).unwrap();
+            let item = naive.remove(index);
+            let target = match (index as i64 + item.1).rem_euclid(naive.len() as i64) as usize {
+                0 => naive.len(),
+                target => target,
+            };
+            naive.insert(target, item);
+        }
+        assert_eq!(
+            mix(&values).iter().copied().collect_vec(),
+            naive.into_iter().map(|(_, v)| v).collect_vec()
+        );
+    }
+}
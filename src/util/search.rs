@@ -0,0 +1,237 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+struct Entry<N> {
+    priority: usize,
+    cost: usize,
+    node: N,
+}
+
+impl<N> PartialEq for Entry<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N> Eq for Entry<N> {}
+
+impl<N> PartialOrd for Entry<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Lowest priority first, and among those the cheapest so far
+impl<N> Ord for Entry<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then(self.cost.cmp(&other.cost))
+            .reverse()
+    }
+}
+
+// The cheapest goal reachable from `start` and what getting there costs.
+// `neighbors` gives each next node with the cost of the step there, and
+// `heuristic` a lower bound on the cost from a node to the nearest goal.
+//
+// Nodes aren't closed once expanded: whenever a cheaper way to a node turns
+// up it goes back into the queue, and entries that have been beaten by then
+// are skipped when they come out. So the answer is the cheapest one even
+// for heuristics that are admissible but not consistent, at the price of
+// expanding some nodes more than once for those.
+pub fn a_star<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    heuristic: impl Fn(&N) -> usize,
+    is_goal: impl Fn(&N) -> bool,
+) -> Option<(N, usize)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut best = HashMap::from([(start.clone(), 0)]);
+    let mut queue = BinaryHeap::from([Entry {
+        priority: heuristic(&start),
+        cost: 0,
+        node: start,
+    }]);
+    while let Some(Entry { cost, node, .. }) = queue.pop() {
+        if best.get(&node).is_some_and(|&known| known < cost) {
+            continue;
+        }
+        if is_goal(&node) {
+            return Some((node, cost));
+        }
+        for (next, step) in neighbors(&node) {
+            let cost = cost + step;
+            if best.get(&next).is_none_or(|&known| cost < known) {
+                best.insert(next.clone(), cost);
+                queue.push(Entry {
+                    priority: cost + heuristic(&next),
+                    cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+pub fn dijkstra<N, I>(
+    start: N,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl Fn(&N) -> bool,
+) -> Option<(N, usize)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    a_star(start, neighbors, |_| 0, is_goal)
+}
+
+// The nearest goal from `start` in steps, for graphs where every step costs
+// the same. A queue hands out nodes in the order of their distance already,
+// so there is no heap and every node is expanded once.
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    is_goal: impl Fn(&N) -> bool,
+) -> Option<(N, usize)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((node, steps)) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some((node, steps));
+        }
+        for next in neighbors(&node) {
+            if seen.insert(next.clone()) {
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::util::random::Rng;
+
+    type Graph = HashMap<char, Vec<(char, usize)>>;
+
+    fn graph(edges: &[(char, char, usize)]) -> Graph {
+        let mut graph = Graph::new();
+        for &(from, to, cost) in edges {
+            graph.entry(from).or_default().push((to, cost));
+        }
+        graph
+    }
+
+    #[test]
+    fn reopens_nodes_for_inconsistent_heuristics() {
+        // The estimate at B is exact but the one at A and C is far too low,
+        // so C is first expanded through A, the expensive way
+        let graph = graph(&[
+            ('S', 'A', 1),
+            ('S', 'B', 2),
+            ('A', 'C', 3),
+            ('B', 'C', 1),
+            ('C', 'G', 3),
+        ]);
+        let heuristic = |node: &char| if *node == 'B' { 4 } else { 0 };
+        let neighbors = |node: &char| graph.get(node).cloned().unwrap_or_default();
+        assert_eq!(
+            a_star('S', neighbors, heuristic, |node| *node == 'G'),
+            Some(('G', 6))
+        );
+    }
+
+    #[test]
+    fn later_cheaper_paths_win() {
+        // G is pushed expensively straight from S before the long cheap way
+        // around is found, and zero cost steps are fine too
+        let graph = graph(&[
+            ('S', 'G', 10),
+            ('S', 'A', 0),
+            ('A', 'B', 1),
+            ('B', 'C', 0),
+            ('C', 'G', 2),
+        ]);
+        let neighbors = |node: &char| graph.get(node).cloned().unwrap_or_default();
+        assert_eq!(
+            dijkstra('S', neighbors, |node| *node == 'G'),
+            Some(('G', 3))
+        );
+        assert_eq!(dijkstra('S', neighbors, |node| *node == 'X'), None);
+        assert_eq!(
+            dijkstra('G', neighbors, |node| *node == 'G'),
+            Some(('G', 0))
+        );
+    }
+
+    // Costs from `source` by relaxing every edge until nothing improves
+    fn bellman_ford(edges: &[Vec<(usize, usize)>], source: usize) -> Vec<Option<usize>> {
+        let mut best = vec![None; edges.len()];
+        best[source] = Some(0);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (from, out) in edges.iter().enumerate() {
+                let Some(cost) = best[from] else { continue };
+                for &(to, step) in out {
+                    if best[to].is_none_or(|known| cost + step < known) {
+                        best[to] = Some(cost + step);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn random_graphs_agree_with_relaxation() {
+        let mut rng = Rng::new(426);
+        for _ in 0..300 {
+            let nodes = 2 + rng.below(12);
+            let edges = (0..nodes)
+                .map(|_| {
+                    (0..rng.below(4))
+                        .map(|_| (rng.below(nodes), rng.below(10)))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let costs = (0..nodes)
+                .map(|source| bellman_ford(&edges, source))
+                .collect::<Vec<_>>();
+            for (goal, expected) in costs[0].iter().enumerate() {
+                // The true cost on even nodes and nothing on odd ones is
+                // admissible, but usually not consistent
+                let heuristic = |node: &usize| match costs[*node][goal] {
+                    Some(cost) if node.is_multiple_of(2) => cost,
+                    _ => 0,
+                };
+                let neighbors = |node: &usize| edges[*node].clone();
+                let found = a_star(0, neighbors, heuristic, |node| *node == goal);
+                assert_eq!(found.map(|(_, cost)| cost), *expected);
+                // The same edges all costing one
+                let steps = |node: &usize| edges[*node].iter().map(|&(to, _)| to).collect_vec();
+                let unit = |node: &usize| steps(node).into_iter().map(|to| (to, 1));
+                assert_eq!(
+                    bfs(0, steps, |node| *node == goal).map(|(_, steps)| steps),
+                    dijkstra(0, unit, |node| *node == goal).map(|(_, cost)| cost)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::ops::ControlFlow;
+
+// Something that advances one step at a time, like monkeys playing rounds or
+// sand falling grain by grain, and may at some point finish with an output.
+// Simulations that never finish can use `Infallible` as their output.
+pub trait Simulation {
+    type Output;
+
+    fn step(&mut self) -> ControlFlow<Self::Output>;
+}
+
+// Runs at most `n` steps, returning the output if the simulation finished first.
+pub fn run_n_steps<S: Simulation>(sim: &mut S, n: usize) -> Option<S::Output> {
+    run_n_steps_with(sim, n, |_, _| {})
+}
+
+pub fn run_until_break<S: Simulation>(sim: &mut S) -> S::Output {
+    run_until_break_with(sim, |_, _| {})
+}
+
+// The `_with` drivers call `observe` after every step that didn't finish the
+// simulation, with the number of steps taken so far. That is the hook for
+// progress output or drawing animation frames.
+pub fn run_n_steps_with<S: Simulation>(
+    sim: &mut S,
+    n: usize,
+    mut observe: impl FnMut(usize, &S),
+) -> Option<S::Output> {
+    for steps in 1..=n {
+        if let ControlFlow::Break(output) = sim.step() {
+            return Some(output);
+        }
+        observe(steps, sim);
+    }
+    None
+}
+
+pub fn run_until_break_with<S: Simulation>(
+    sim: &mut S,
+    mut observe: impl FnMut(usize, &S),
+) -> S::Output {
+    let mut steps = 0;
+    loop {
+        if let ControlFlow::Break(output) = sim.step() {
+            return output;
+        }
+        steps += 1;
+        observe(steps, sim);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Collatz sequence, finishing with the number of steps once it reaches 1
+    struct Collatz(u64, usize);
+
+    impl Simulation for Collatz {
+        type Output = usize;
+
+        fn step(&mut self) -> ControlFlow<usize> {
+            if self.0 == 1 {
+                return ControlFlow::Break(self.1);
+            }
+            self.0 = if self.0.is_multiple_of(2) {
+                self.0 / 2
+            } else {
+                3 * self.0 + 1
+            };
+            self.1 += 1;
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn drivers_stop_on_break() {
+        assert_eq!(run_until_break(&mut Collatz(6, 0)), 8);
+        assert_eq!(run_n_steps(&mut Collatz(6, 0), 20), Some(8));
+        let mut sim = Collatz(6, 0);
+        assert_eq!(run_n_steps(&mut sim, 3), None);
+        assert_eq!(sim.0, 5);
+    }
+
+    #[test]
+    fn observer_sees_every_step() {
+        let mut seen = Vec::new();
+        run_until_break_with(&mut Collatz(6, 0), |steps, sim| seen.push((steps, sim.0)));
+        assert_eq!(
+            seen,
+            vec![
+                (1, 3),
+                (2, 10),
+                (3, 5),
+                (4, 16),
+                (5, 8),
+                (6, 4),
+                (7, 2),
+                (8, 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use miette::SourceSpan;
+
+// A value with the bytes of the input it came from, so that diagnostics
+// can point back at them after parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(value: T, start: usize, end: usize) -> Self {
+        Self { value, start, end }
+    }
+
+    // For a `part` sliced out of `input`, like the lines of `str::lines`
+    pub fn of(input: &str, part: &str, value: T) -> Self {
+        let start = (part.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|start| start + part.len() <= input.len())
+            .expect("part is not a slice of input");
+        Self::new(value, start, start + part.len())
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+        Spanned::new(f(self.value), self.start, self.end)
+    }
+
+    pub fn line_col(&self, input: &str) -> (usize, usize) {
+        line_col(input, self.start)
+    }
+}
+
+impl<T> From<&Spanned<T>> for SourceSpan {
+    fn from(spanned: &Spanned<T>) -> Self {
+        (spanned.start, spanned.end - spanned.start).into()
+    }
+}
+
+// 1-based line and column of the character at byte `offset`, counting
+// columns in characters
+pub fn line_col(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let col = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (line, col)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_lines_and_columns() {
+        let input = "move 1 from 2 to 1\nmove 3 from 1 to 3\n";
+        let line = input.lines().nth(1).unwrap();
+        let target = Spanned::of(input, &line[17..], 3);
+        assert_eq!((target.start, target.end), (36, 37));
+        assert_eq!(target.line_col(input), (2, 18));
+        assert_eq!(target.map(|n| n * 2).value, 6);
+        assert_eq!(SourceSpan::from(&target), SourceSpan::from((36, 1)));
+        assert_eq!(line_col(input, 0), (1, 1));
+        assert_eq!(line_col("é\nx", 3), (2, 1));
+        assert_eq!(line_col("éx", 2), (1, 2));
+    }
+}
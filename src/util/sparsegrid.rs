@@ -0,0 +1,309 @@
+use std::ops::{BitAnd, BitOr, BitOrAssign, Not};
+
+use crate::util::prelude::*;
+
+const WORD: usize = u64::BITS as usize;
+
+// Bitset over a width x height rectangle, stored as rows of u64 words with
+// bit `x % 64` of word `x / 64` standing for column x. Shifting and combining
+// whole grids works a word at a time, which is what cellular automata with
+// many live cells (elves, blizzard frontiers) spend their time on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    row_words: usize,
+    pub length: usize,
+    pub height: usize,
+}
+
+impl BitGrid {
+    pub fn new(length: usize, height: usize) -> Self {
+        let row_words = length.div_ceil(WORD);
+        Self {
+            words: vec![0; row_words * height],
+            row_words,
+            length,
+            height,
+        }
+    }
+
+    pub fn full(length: usize, height: usize) -> Self {
+        !&Self::new(length, height)
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.length && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.contains(x, y) && self.row(y)[x / WORD] & (1 << (x % WORD)) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        assert!(self.contains(x, y), "({x}, {y}) outside of grid");
+        let word = &mut self.words[y * self.row_words + x / WORD];
+        if value {
+            *word |= 1 << (x % WORD);
+        } else {
+            *word &= !(1 << (x % WORD));
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height).flat_map(move |y| {
+            self.row(y).iter().enumerate().flat_map(move |(i, word)| {
+                let mut word = *word;
+                std::iter::from_fn(move || {
+                    (word != 0).then(|| {
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        (i * WORD + bit, y)
+                    })
+                })
+            })
+        })
+    }
+
+    // Moves every cell by (dx, dy), dropping whatever ends up outside.
+    pub fn shifted(&self, dx: isize, dy: isize) -> Self {
+        let mut result = Self::new(self.length, self.height);
+        for y in 0..self.height {
+            let source_y = y as isize - dy;
+            if !(0..self.height as isize).contains(&source_y) {
+                continue;
+            }
+            let source = self.row(source_y as usize);
+            let target = &mut result.words[y * self.row_words..(y + 1) * self.row_words];
+            if dx >= 0 {
+                shift_up(source, target, dx as usize);
+            } else {
+                shift_down(source, target, dx.unsigned_abs());
+            }
+        }
+        result.clear_padding();
+        result
+    }
+
+    // The cells themselves together with their orthogonal neighbors.
+    pub fn spread(&self) -> Self {
+        let mut result = self.clone();
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            result |= &self.shifted(dx, dy);
+        }
+        result
+    }
+
+    pub fn and_not(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.row_words..(y + 1) * self.row_words]
+    }
+
+    fn zip_words(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(
+            (self.length, self.height),
+            (other.length, other.height),
+            "grid dimensions differ"
+        );
+        Self {
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+            ..*self
+        }
+    }
+
+    fn clear_padding(&mut self) {
+        let used = self.length % WORD;
+        if used == 0 {
+            return;
+        }
+        let mask = (1 << used) - 1;
+        for row in self.words.chunks_mut(self.row_words) {
+            if let Some(last) = row.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+}
+
+// Reachable cells minute by minute when every step either waits or moves to an
+// orthogonal neighbor, and only cells `open(minute)` marks as free may be occupied.
+// For periodic obstacles like blizzards, `open` can look up `minute % lcm(length, height)`.
+// Stops once nothing is reachable anymore.
+pub fn frontiers(
+    start: BitGrid,
+    mut open: impl FnMut(usize) -> BitGrid,
+) -> impl Iterator<Item = BitGrid> {
+    let mut minute = 0;
+    std::iter::successors((!start.is_empty()).then_some(start), move |frontier| {
+        minute += 1;
+        let next = &frontier.spread() & &open(minute);
+        (!next.is_empty()).then_some(next)
+    })
+}
+
+fn shift_up(source: &[u64], target: &mut [u64], by: usize) {
+    let (words, bits) = (by / WORD, by % WORD);
+    for i in (words..target.len()).rev() {
+        let low = source[i - words];
+        target[i] = if bits == 0 {
+            low
+        } else {
+            let carry = if i > words {
+                source[i - words - 1] >> (WORD - bits)
+            } else {
+                0
+            };
+            (low << bits) | carry
+        };
+    }
+}
+
+fn shift_down(source: &[u64], target: &mut [u64], by: usize) {
+    let (words, bits) = (by / WORD, by % WORD);
+    for i in 0..target.len().saturating_sub(words) {
+        let high = source[i + words];
+        target[i] = if bits == 0 {
+            high
+        } else {
+            let carry = source
+                .get(i + words + 1)
+                .map_or(0, |next| next << (WORD - bits));
+            (high >> bits) | carry
+        };
+    }
+}
+
+impl FromIterator<(usize, usize)> for BitGrid {
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let points = iter.into_iter().collect_vec();
+        let length = points.iter().map(|(x, _)| x + 1).max().unwrap_or_default();
+        let height = points.iter().map(|(_, y)| y + 1).max().unwrap_or_default();
+        let mut result = Self::new(length, height);
+        for (x, y) in points {
+            result.set(x, y, true);
+        }
+        result
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.zip_words(rhs, |a, b| a | b)
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        *self = &*self | rhs;
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.zip_words(rhs, |a, b| a & b)
+    }
+}
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> Self::Output {
+        let mut result = BitGrid {
+            words: self.words.iter().map(|w| !w).collect(),
+            ..*self
+        };
+        result.clear_padding();
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shifts_carry_across_words() {
+        let mut grid = BitGrid::new(130, 3);
+        for (x, y) in [(63, 0), (64, 1), (0, 2)] {
+            grid.set(x, y, true);
+        }
+        assert_eq!(
+            grid.shifted(1, 0).iter().collect_vec(),
+            vec![(64, 0), (65, 1), (1, 2)]
+        );
+        assert_eq!(
+            grid.shifted(-1, 0).iter().collect_vec(),
+            vec![(62, 0), (63, 1)]
+        );
+        assert_eq!(grid.shifted(66, 1).iter().collect_vec(), vec![(129, 1)]);
+        assert_eq!(
+            grid.shifted(66, 0).iter().collect_vec(),
+            vec![(129, 0), (66, 2)]
+        );
+        assert_eq!(grid.shifted(-64, -1).iter().collect_vec(), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn spread_and_combinators() {
+        let mut grid = BitGrid::new(5, 4);
+        grid.set(0, 0, true);
+        grid.set(3, 2, true);
+        let spread = grid.spread();
+        assert_eq!(spread.count(), 3 + 5);
+        assert_eq!(spread.and_not(&grid).count(), 6);
+        assert_eq!((&spread & &grid), grid);
+        assert_eq!((!&spread).count(), 20 - 8);
+        assert_eq!(BitGrid::full(5, 4).count(), 20);
+    }
+
+    #[test]
+    fn frontiers_respect_moving_wall() {
+        // A 5x1 corridor with a wall at x = 2 that disappears after minute 3
+        let mut start = BitGrid::new(5, 1);
+        start.set(0, 0, true);
+        let counts = frontiers(start, |minute| {
+            let mut open = BitGrid::full(5, 1);
+            if minute <= 3 {
+                open.set(2, 0, false);
+            }
+            open
+        })
+        .take(7)
+        .map(|frontier| frontier.count())
+        .collect_vec();
+        assert_eq!(counts, vec![1, 2, 2, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn frontiers_end_when_crushed() {
+        let start = BitGrid::full(3, 3);
+        let minutes = frontiers(start, |minute| {
+            if minute < 2 {
+                BitGrid::full(3, 3)
+            } else {
+                BitGrid::new(3, 3)
+            }
+        })
+        .count();
+        assert_eq!(minutes, 2);
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+// Time spent in labelled phases of a solution, like day 15's candidate search
+// and interval scan, collected per thread for aoc run --stats:
+//
+//     let _timer = ScopedTimer::new("day15 candidates");
+//     let answer = time!("day15 scan", scan(&candidates));
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    pub label: &'static str,
+    pub total: Duration,
+    pub calls: usize,
+}
+
+thread_local! {
+    // In the order labels were first recorded
+    static TIMINGS: RefCell<Vec<Timing>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn record(label: &'static str, elapsed: Duration) {
+    TIMINGS.with(|timings| {
+        let mut timings = timings.borrow_mut();
+        match timings.iter_mut().find(|timing| timing.label == label) {
+            Some(timing) => {
+                timing.total += elapsed;
+                timing.calls += 1;
+            }
+            None => timings.push(Timing {
+                label,
+                total: elapsed,
+                calls: 1,
+            }),
+        }
+    })
+}
+
+// Everything recorded on this thread since the last call
+pub fn take() -> Vec<Timing> {
+    TIMINGS.with(|timings| timings.take())
+}
+
+// Records the time until it is dropped, usually at the end of the scope
+#[derive(Debug)]
+pub struct ScopedTimer {
+    label: &'static str,
+    start: Instant,
+}
+
+impl ScopedTimer {
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for ScopedTimer {
+    fn drop(&mut self) {
+        record(self.label, self.start.elapsed());
+    }
+}
+
+// Evaluates the expression, recording how long it took under the label
+#[macro_export]
+macro_rules! time {
+    ($label:expr, $expr:expr) => {{
+        let _timer = $crate::util::timer::ScopedTimer::new($label);
+        $expr
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn adds_up_by_label() {
+        take();
+        let answer = time!("sum", (1..=10).sum::<u32>());
+        assert_eq!(answer, 55);
+        for _ in 0..2 {
+            let _timer = ScopedTimer::new("loop");
+        }
+        time!("sum", ());
+        let timings = take();
+        let calls: Vec<_> = timings
+            .iter()
+            .map(|timing| (timing.label, timing.calls))
+            .collect();
+        assert_eq!(calls, [("sum", 2), ("loop", 2)]);
+        assert!(take().is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use bitvec::prelude::*;
+
+use crate::util::{flood::Point2, hash::FastHashSet, interval::Interval};
+
+// A set of visited cells, one bit each inside a window around where the walk
+// has been so far and hashed outside of it. The window starts small around
+// the origin and doubles along an axis to take in a cell beyond it, up to
+// MAX_SIDE, so that a walk that stays local never hashes at all while one
+// that wanders far off costs what a hash set would.
+#[derive(Debug, Clone)]
+pub struct DenseVisited {
+    xs: Interval,
+    ys: Interval,
+    bits: BitVec,
+    // Only cells outside of the window
+    outside: FastHashSet<Point2>,
+    len: usize,
+}
+
+impl Default for DenseVisited {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DenseVisited {
+    // 4096 by 4096 bits, 2MB
+    pub const MAX_SIDE: usize = 1 << 12;
+    const START_SIDE: i64 = 64;
+
+    pub fn new() -> Self {
+        let half = Self::START_SIDE / 2;
+        let side = Interval::new(-half, half - 1);
+        Self::with_window(side, side)
+    }
+
+    fn with_window(xs: Interval, ys: Interval) -> Self {
+        Self {
+            xs,
+            ys,
+            bits: bitvec![0; xs.size() * ys.size()],
+            outside: FastHashSet::default(),
+            len: 0,
+        }
+    }
+
+    fn index(&self, (x, y): Point2) -> Option<usize> {
+        (self.xs.contains(x) && self.ys.contains(y))
+            .then(|| (y - self.ys.start) as usize * self.xs.size() + (x - self.xs.start) as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, point: Point2) -> bool {
+        match self.index(point) {
+            Some(i) => self.bits[i],
+            None => self.outside.contains(&point),
+        }
+    }
+
+    // Whether the cell is new
+    pub fn insert(&mut self, point: Point2) -> bool {
+        if self.index(point).is_none() {
+            self.grow_towards(point);
+        }
+        let new = match self.index(point) {
+            Some(i) => !self.bits.replace(i, true),
+            None => self.outside.insert(point),
+        };
+        self.len += usize::from(new);
+        new
+    }
+
+    // Doubles the window along each axis `point` is off, as far as MAX_SIDE
+    // allows, and moves the hashed cells it now covers into it
+    fn grow_towards(&mut self, (x, y): Point2) {
+        let (xs, ys) = (grown(self.xs, x), grown(self.ys, y));
+        if (xs, ys) == (self.xs, self.ys) {
+            return;
+        }
+        let mut grown = Self::with_window(xs, ys);
+        // Old rows land whole in the new ones
+        for (i, row) in self.bits.chunks(self.xs.size()).enumerate() {
+            let start = grown
+                .index((self.xs.start, self.ys.start + i as i64))
+                .unwrap();
+            grown.bits[start..start + row.len()].copy_from_bitslice(row);
+        }
+        // Keeping the hashed cells that stay outside where they are, since
+        // rehashing them all on every growth is what a long walk can't afford
+        self.outside.retain(|point| match grown.index(*point) {
+            Some(i) => {
+                grown.bits.set(i, true);
+                false
+            }
+            None => true,
+        });
+        grown.outside = std::mem::take(&mut self.outside);
+        grown.len = self.len;
+        *self = grown;
+    }
+}
+
+// `side` doubled towards `c` until it reaches it or is MAX_SIDE long
+fn grown(mut side: Interval, c: i64) -> Interval {
+    while !side.contains(c) && side.size() < DenseVisited::MAX_SIDE {
+        let by = side.size().min(DenseVisited::MAX_SIDE - side.size()) as i64;
+        side = if c < side.start {
+            Interval::new(side.start - by, side.end)
+        } else {
+            Interval::new(side.start, side.end + by)
+        };
+    }
+    side
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::random::Rng;
+
+    #[test]
+    fn grows_then_hashes() {
+        let mut visited = DenseVisited::new();
+        assert!(visited.insert((0, 0)));
+        assert!(!visited.insert((0, 0)));
+        // Off the starting window, which doubles to take it in
+        assert!(visited.insert((100, -3)));
+        assert!(visited.outside.is_empty());
+        // Too far for any window
+        assert!(visited.insert((0, 1 << 20)));
+        assert_eq!(visited.outside.len(), 1);
+        assert!(!visited.insert((0, 1 << 20)));
+        assert_eq!(visited.len(), 3);
+        assert!(visited.contains((100, -3)) && !visited.contains((-100, 3)));
+        assert_eq!(visited.xs.size(), 256);
+        assert_eq!(visited.ys.size(), DenseVisited::MAX_SIDE);
+    }
+
+    #[test]
+    fn agrees_with_a_hash_set() {
+        let mut rng = Rng::new(463);
+        let mut visited = DenseVisited::new();
+        let mut expected = FastHashSet::default();
+        let mut pos = (0, 0);
+        for _ in 0..20_000 {
+            // Mostly short steps, with the odd jump far away and back
+            let (dx, dy) = match rng.below(100) {
+                0 => (rng.range(-5000..=5000), rng.range(-5000..=5000)),
+                _ => (rng.range(-2..=2), rng.range(-2..=2)),
+            };
+            pos = (pos.0 + dx, pos.1 + dy);
+            assert_eq!(visited.insert(pos), expected.insert(pos));
+        }
+        assert_eq!(visited.len(), expected.len());
+        assert!(expected.iter().all(|point| visited.contains(*point)));
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use itertools::Itertools;
+
+use crate::util::glyphs::{self, Border};
+
+// Like the glyphs, picked once for the whole process by the commands, so
+// that frames only carry colour codes when the terminal shows them
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_color(on: bool) {
+    COLOR.store(on, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    Dim,
+    Green,
+    Yellow,
+}
+
+impl Paint {
+    // `text` in ANSI colour codes, whatever `set_color` says
+    pub fn apply(self, text: &str) -> String {
+        let code = match self {
+            Paint::Dim => "2",
+            Paint::Green => "32",
+            Paint::Yellow => "33",
+        };
+        format!("\x1b[{code}m{text}\x1b[0m")
+    }
+}
+
+// `text` in colour if colour is on, else as it is
+pub fn paint(text: &str, paint: Paint) -> String {
+    if COLOR.load(Ordering::Relaxed) {
+        paint.apply(text)
+    } else {
+        text.to_string()
+    }
+}
+
+// A titled block of text, like a grid or a few counters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panel {
+    pub title: String,
+    pub body: String,
+}
+
+impl Panel {
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+}
+
+// Characters shown, leaving out colour codes
+fn width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+// The panel's rows with its border and title, with `height` rows of body so
+// that panels of different heights line up
+fn framed(panel: &Panel, height: usize, border: Border) -> Vec<String> {
+    let lines = panel.body.lines().collect_vec();
+    let title = if panel.title.is_empty() {
+        String::new()
+    } else {
+        format!(" {} ", panel.title)
+    };
+    // A space either side of the body, and lines either side of the title
+    let inner = lines
+        .iter()
+        .map(|line| width(line) + 2)
+        .max()
+        .unwrap_or(2)
+        .max(width(&title) + 2);
+    let rule = |n: usize| border.horizontal.to_string().repeat(n);
+    let mut rows = Vec::with_capacity(height + 2);
+    rows.push(format!(
+        "{}{}{title}{}{}",
+        border.top_left,
+        rule(1),
+        rule(inner - 1 - width(&title)),
+        border.top_right
+    ));
+    for i in 0..height {
+        let line = lines.get(i).copied().unwrap_or("");
+        rows.push(format!(
+            "{} {line}{} {}",
+            border.vertical,
+            " ".repeat(inner - 2 - width(line)),
+            border.vertical
+        ));
+    }
+    rows.push(format!(
+        "{}{}{}",
+        border.bottom_left,
+        rule(inner),
+        border.bottom_right
+    ));
+    rows
+}
+
+// Panels side by side in the current glyphs' borders, as tall as the tallest
+pub fn compose(panels: &[Panel]) -> String {
+    compose_with(panels, glyphs::current().border)
+}
+
+pub fn compose_with(panels: &[Panel], border: Border) -> String {
+    let Some(height) = panels.iter().map(|panel| panel.body.lines().count()).max() else {
+        return String::new();
+    };
+    let framed = panels
+        .iter()
+        .map(|panel| framed(panel, height, border))
+        .collect_vec();
+    (0..height + 2)
+        .map(|row| framed.iter().map(|rows| &rows[row]).join(" "))
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::glyphs::{ASCII, UNICODE};
+
+    #[test]
+    fn frames_panels_side_by_side() {
+        let panels = [
+            Panel::new("grid", "#..\n.#.\n..#"),
+            Panel::new("sand", "settled 24"),
+            Panel::new("", ""),
+        ];
+        assert_eq!(
+            compose_with(&panels, ASCII.border),
+            "\
++- grid -+ +- sand -----+ +--+
+| #..    | | settled 24 | |  |
+| .#.    | |            | |  |
+| ..#    | |            | |  |
++--------+ +------------+ +--+"
+        );
+        assert_eq!(
+            compose_with(&panels[1..2], UNICODE.border),
+            "┌─ sand ─────┐\n│ settled 24 │\n└────────────┘"
+        );
+        assert_eq!(compose_with(&[], ASCII.border), "");
+    }
+
+    #[test]
+    fn colour_takes_no_room() {
+        let painted = format!("{}.{}", Paint::Green.apply("#"), Paint::Dim.apply("#"));
+        assert_eq!(width(&painted), 3);
+        let framed = compose_with(&[Panel::new("row", painted.clone())], ASCII.border);
+        assert_eq!(framed, format!("+- row -+\n| {painted}   |\n+-------+"));
+    }
+}
@@ -0,0 +1,221 @@
+// The commands that talk to adventofcode.com, run against a mock of it that
+// AOC_BASE_URL points them at
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use wiremock::{
+    matchers::{body_string_contains, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const EXAMPLE: &str = include_str!("../src/days/test_input/day1.txt");
+
+// A fresh directory to run aoc in, holding the day 1 example as its input
+fn workdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aoc-mock-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("input")).unwrap();
+    fs::write(dir.join("input/day1.txt"), EXAMPLE).unwrap();
+    dir
+}
+
+fn aoc(server: &MockServer, dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(args)
+        .current_dir(dir)
+        .env("AOC_BASE_URL", server.uri())
+        .env("AOC_YEAR", "2022")
+        .env("SESSION", "s3cr3t")
+        .env_remove("AOC_CONFIG")
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+fn answer_page(text: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .set_body_string(format!("<main><article><p>{text}</p></article></main>"))
+}
+
+#[tokio::test]
+async fn downloads_the_input() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/2022/day/3/input"))
+        .and(header("Cookie", "session=s3cr3t"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("vJrwpWtwJgWrhcsFMMfFFhFp\n"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let dir = workdir("download");
+    let output = aoc(&server, &dir, &["wait", "--day", "3"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Saved day 3's input to input/day3.txt"));
+    assert_eq!(
+        fs::read_to_string(dir.join("input/day3.txt")).unwrap(),
+        "vJrwpWtwJgWrhcsFMMfFFhFp\n"
+    );
+    // Never over an input that is already there
+    let output = aoc(&server, &dir, &["wait", "--day", "3"]);
+    assert!(stderr(&output).contains("input/day3.txt is already there"));
+}
+
+#[tokio::test]
+async fn submits_the_answer() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/2022/day/1/answer"))
+        .and(header("Cookie", "session=s3cr3t"))
+        .and(body_string_contains("level=2&answer=45000"))
+        .respond_with(answer_page(
+            "That's the right answer!  You are <em>one gold star</em> closer.",
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let output = aoc(
+        &server,
+        &workdir("right"),
+        &["run", "--day", "1", "--level", "2", "--submit"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stdout = stdout(&output);
+    assert!(stdout.contains("Day 1 level 2: 45,000"));
+    assert!(stdout.contains("That's the right answer!"));
+}
+
+#[tokio::test]
+async fn keeps_wrong_answers_bracketed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/2022/day/1/answer"))
+        .respond_with(answer_page(
+            "That's not the right answer; your answer is too high.  Please wait one minute.",
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let dir = workdir("bracket");
+    let args = ["run", "--day", "1", "--level", "1", "--submit"];
+    let output = aoc(&server, &dir, &args);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("That's not the right answer, it is too high"));
+    assert_eq!(
+        fs::read_to_string(dir.join("answers.tsv")).unwrap(),
+        "1\t1\thigh\t24000\n"
+    );
+    // The same answer again never reaches the site
+    let output = aoc(&server, &dir, &args);
+    assert!(!output.status.success());
+    assert!(stderr(&output)
+        .contains("Not submitting 24000, earlier answers put day 1 level 1 below 24000"));
+}
+
+#[tokio::test]
+async fn verifies_answers_across_threads() {
+    let server = MockServer::start().await;
+    let dir = workdir("verify");
+    let output = aoc(
+        &server,
+        &dir,
+        &["run", "--day", "1", "--both-levels", "--verify"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Day 1 level 2: 45,000"));
+    let output = aoc(&server, &dir, &["run", "--day", "1", "--time", "--verify"]);
+    assert!(stderr(&output).contains("--time only reports timings"));
+}
+
+#[tokio::test]
+async fn retries_only_server_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/2022/day/4/input"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/2022/day/5/input"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/2022/day/5/input"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("move 1 from 2 to 1\n"))
+        .mount(&server)
+        .await;
+    let dir = workdir("retries");
+    let output = aoc(&server, &dir, &["wait", "--day", "4"]);
+    assert!(!output.status.success());
+    assert!(!dir.join("input/day4.txt").exists());
+    let output = aoc(&server, &dir, &["wait", "--day", "5"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(dir.join("input/day5.txt")).unwrap(),
+        "move 1 from 2 to 1\n"
+    );
+}
+
+#[tokio::test]
+async fn reports_rate_limits() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/2022/day/1/answer"))
+        .respond_with(answer_page(
+            "You gave an answer too recently; you have to wait after submitting an answer \
+             before trying again.  You have 34s left to wait.",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/2022"))
+        .respond_with(ResponseTemplate::new(429))
+        .mount(&server)
+        .await;
+    let dir = workdir("rate-limit");
+    let output = aoc(
+        &server,
+        &dir,
+        &["run", "--day", "1", "--level", "1", "--submit"],
+    );
+    assert!(stderr(&output).contains("Answered too recently, 34s left to wait"));
+    assert!(!dir.join("answers.tsv").exists());
+    let output = aoc(&server, &dir, &["status"]);
+    assert!(stderr(&output).contains("Too many requests, try again later"));
+}
+
+#[tokio::test]
+async fn compares_stars_with_solvers() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/2022"))
+        .and(header("Cookie", "session=s3cr3t"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            "<pre class=\"calendar\">\
+             <a aria-label=\"Day 2, one star\" href=\"/2022/day/2\" class=\"calendar-day2 calendar-complete\">\
+             <a aria-label=\"Day 1, two stars\" href=\"/2022/day/1\" class=\"calendar-day1 calendar-verycomplete\">\
+             <a aria-label=\"Day 20, two stars\" href=\"/2022/day/20\" class=\"calendar-day20 calendar-verycomplete\">",
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let output = aoc(&server, &workdir("status"), &["status"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stdout = stdout(&output);
+    assert!(stdout.contains("  1  **    yes     done\n"));
+    assert!(stdout.contains("  2  *     yes     solved locally, level 2 never submitted\n"));
+    assert!(stdout.contains(" 20  **    no      starred, but no local solver\n"));
+}